@@ -0,0 +1,145 @@
+#[macro_use]
+extern crate bencher;
+
+use rust_chess::model::game_state::GameState;
+use rust_chess::model::move_generator::MoveGenerator;
+use rust_chess::search::searcher::{SearchFeatures, SearchLimits, Searcher};
+use rust_chess::search::test_utils;
+
+use bencher::Bencher;
+use std::sync::Once;
+use std::time::{Duration, Instant};
+
+const DEPTH: u16 = 5;
+const TABLE_SIZE_MB: usize = 4;
+
+// bencher calls the bench functions many times, the comparison is printed only once
+static COMPARISON: Once = Once::new();
+
+// positions reached from the starting position by the given moves
+const POSITIONS: [(&str, &[&str]); 5] = [
+    ("start", &[]),
+    ("italian", &["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "f8c5"]),
+    (
+        "queens_gambit",
+        &["d2d4", "d7d5", "c2c4", "e7e6", "b1c3", "g8f6"],
+    ),
+    (
+        "sicilian",
+        &[
+            "e2e4", "c7c5", "g1f3", "d7d6", "d2d4", "c5d4", "f3d4", "g8f6",
+        ],
+    ),
+    ("fools_mate", &["f2f3", "e7e6", "g2g4"]),
+];
+
+fn position(moves: &[&str], move_generator: &MoveGenerator) -> GameState {
+    let mut game_state = GameState::new();
+    let moves = moves.iter().map(|s| s.to_string());
+    test_utils::apply_position(moves, &mut game_state, move_generator);
+    game_state
+}
+
+fn searcher(features: SearchFeatures) -> Searcher {
+    Searcher::new()
+        .with_table_size_mb(TABLE_SIZE_MB)
+        .with_features(features)
+        .with_limits(SearchLimits {
+            depth: Some(DEPTH),
+            ..SearchLimits::default()
+        })
+}
+
+// the other pruning stays on, so that the comparison shows what shallow pruning adds to it
+fn without_shallow_pruning() -> SearchFeatures {
+    SearchFeatures {
+        shallow_pruning: false,
+        ..SearchFeatures::all()
+    }
+}
+
+fn positions() -> Vec<(&'static str, GameState)> {
+    let move_generator = MoveGenerator::new();
+    POSITIONS
+        .iter()
+        .map(|(name, moves)| (*name, position(moves, &move_generator)))
+        .collect()
+}
+
+// nodes searched to complete iterative deepening up to DEPTH with an empty table
+fn node_count(searcher: &mut Searcher, game_state: &mut GameState) -> u64 {
+    searcher.new_game();
+    searcher.search(game_state).nodes
+}
+
+fn timed_node_count(searcher: &mut Searcher, game_state: &mut GameState) -> (u64, Duration) {
+    let start = Instant::now();
+    let nodes = node_count(searcher, game_state);
+    (nodes, start.elapsed())
+}
+
+fn bench_node_counts(bench: &mut Bencher, features: SearchFeatures) {
+    let mut searcher = searcher(features);
+    let mut positions = positions();
+
+    bench.iter(|| {
+        positions
+            .iter_mut()
+            .map(|(_, game_state)| node_count(&mut searcher, game_state))
+            .sum::<u64>()
+    });
+}
+
+// What shallow pruning saves: nodes and time to reach DEPTH without and with it, on every position.
+fn print_pruning_comparison() {
+    let mut without = searcher(without_shallow_pruning());
+    let mut with = searcher(SearchFeatures::all());
+
+    println!(
+        "{:<16}{:>12}{:>10}{:>12}{:>10}",
+        "", "no shallow", "ms", "shallow", "ms"
+    );
+    let mut totals = [(0, Duration::default()); 2];
+    for (name, mut game_state) in positions() {
+        let counts = [
+            timed_node_count(&mut without, &mut game_state),
+            timed_node_count(&mut with, &mut game_state),
+        ];
+        println!(
+            "{:<16}{:>12}{:>10}{:>12}{:>10}",
+            name,
+            counts[0].0,
+            counts[0].1.as_millis(),
+            counts[1].0,
+            counts[1].1.as_millis()
+        );
+        for (total, count) in totals.iter_mut().zip(counts.iter()) {
+            total.0 += count.0;
+            total.1 += count.1;
+        }
+    }
+    println!(
+        "{:<16}{:>12}{:>10}{:>12}{:>10}",
+        "total",
+        totals[0].0,
+        totals[0].1.as_millis(),
+        totals[1].0,
+        totals[1].1.as_millis()
+    );
+}
+
+fn pruning_comparison(bench: &mut Bencher) {
+    COMPARISON.call_once(print_pruning_comparison);
+    bench_node_counts(bench, SearchFeatures::all());
+}
+
+fn fixed_depth_without_shallow_pruning(bench: &mut Bencher) {
+    bench_node_counts(bench, without_shallow_pruning());
+}
+
+benchmark_group!(
+    benches,
+    pruning_comparison,
+    fixed_depth_without_shallow_pruning
+);
+benchmark_main!(benches);
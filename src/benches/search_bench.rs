@@ -1,9 +1,13 @@
 #[macro_use]
 extern crate bencher;
 
+use rust_chess::model::evaluator::HandcraftedEvaluator;
 use rust_chess::model::game_state::GameState;
 use rust_chess::model::move_generator::MoveGenerator;
-use rust_chess::search::minimax_search::negamax_alpha_beta_with_trasposition_table;
+use rust_chess::search::minimax_search::{
+    iterative_alpha_beta_with_control, negamax_alpha_beta_with_trasposition_table,
+};
+use rust_chess::search::search_control::SearchControl;
 use rust_chess::search::transposition_table::TranspositionTable;
 
 use bencher::Bencher;
@@ -24,5 +28,28 @@ fn minimax_search_from_starting_pos(bench: &mut Bencher) {
     });
 }
 
-benchmark_group!(benches, minimax_search_from_starting_pos);
+fn iterative_search_from_starting_pos(bench: &mut Bencher) {
+    let mut game_state = GameState::new();
+    let move_generator = MoveGenerator::new();
+
+    bench.iter(|| {
+        let mut transposition_table = TranspositionTable::with_capacity(100_000);
+        let control = SearchControl::new().with_depth_limit(5);
+
+        iterative_alpha_beta_with_control(
+            &mut game_state,
+            &move_generator,
+            &mut transposition_table,
+            &mut HandcraftedEvaluator,
+            None,
+            &control,
+        )
+    });
+}
+
+benchmark_group!(
+    benches,
+    minimax_search_from_starting_pos,
+    iterative_search_from_starting_pos
+);
 benchmark_main!(benches);
@@ -0,0 +1,107 @@
+use rust_chess::demo::epd_runner::run_suite;
+use rust_chess::notation::epd::parse_epd_file;
+use rust_chess::notation::san::move_to_san;
+use rust_chess::search::score::Score;
+use rust_chess::search::searcher::{SearchLimits, Searcher};
+
+use std::env;
+use std::fs;
+use std::process;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+const DEFAULT_MOVE_TIME_MS: u64 = 1000;
+const USAGE: &str =
+    "Usage: epd <file> [depth <plies>] [movetime <ms>] [nodes <count>] [threads <count>] [hash <mb>]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(msg) = run(&args) {
+        eprintln!("{}", msg);
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let path = args.first().ok_or("Missing EPD file")?;
+    let contents =
+        fs::read_to_string(path).map_err(|err| format!("Cannot read {}: {}", path, err))?;
+    let positions = parse_epd_file(&contents)?;
+
+    let mut limits = SearchLimits::default();
+    let mut searcher = Searcher::new();
+    for option in args[1..].chunks(2) {
+        let (name, value) = match option {
+            [name, value] => (name.as_str(), value.as_str()),
+            _ => return Err(format!("Missing value for {}", option[0])),
+        };
+
+        match name {
+            "depth" => limits.depth = Some(parse_value(name, value)?),
+            "movetime" => limits.time = Some(Duration::from_millis(parse_value(name, value)?)),
+            "nodes" => limits.nodes = Some(parse_value(name, value)?),
+            "threads" => searcher.set_threads(parse_value(name, value)?),
+            "hash" => searcher.resize_table(parse_value(name, value)?),
+            other => return Err(format!("Unknown option: {}", other)),
+        }
+    }
+    if limits == SearchLimits::default() {
+        limits.time = Some(Duration::from_millis(DEFAULT_MOVE_TIME_MS));
+    }
+    searcher.set_limits(limits);
+
+    let start = Instant::now();
+    let mut index = 0;
+    let summary = run_suite(&mut searcher, &positions, |position, result| {
+        index += 1;
+        let id = position.id.clone().unwrap_or_else(|| format!("#{}", index));
+        let found = result
+            .best_move
+            .map(|m| move_to_san(&position.game_state, &m))
+            .unwrap_or_else(|| "none".to_string());
+        let expected: Vec<String> = ["bm", "am", "dm"]
+            .iter()
+            .filter_map(|opcode| {
+                position
+                    .operation(opcode)
+                    .map(|operands| format!("{} {}", opcode, operands.join(" ")))
+            })
+            .collect();
+        let score = match result.score {
+            Score::Evaluation(eval) => format!("cp {}", eval / 10),
+            Score::MateIn(moves) => format!("mate {}", moves),
+        };
+
+        println!(
+            "{} {}: {} ({}) {} depth {} nodes {}",
+            if result.solved { "solved" } else { "FAILED" },
+            id,
+            found,
+            expected.join(", "),
+            score,
+            result.depth,
+            result.nodes
+        );
+    });
+
+    let elapsed = start.elapsed();
+    println!(
+        "Solved {} of {}, failed {}, nodes {}, time {:.1}s, nps {}",
+        summary.solved,
+        positions.len(),
+        summary.failed,
+        summary.nodes,
+        elapsed.as_secs_f64(),
+        (summary.nodes as f64 / elapsed.as_secs_f64().max(0.001)) as u64
+    );
+
+    Ok(())
+}
+
+// values out of the range of the option's type are invalid
+fn parse_value<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse::<T>()
+        .map_err(|_| format!("Invalid value for {}: {}", name, value))
+}
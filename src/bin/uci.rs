@@ -0,0 +1,14 @@
+use rust_chess::uci::engine::UciEngine;
+
+use std::io::{self, BufRead};
+
+fn main() {
+    let mut engine = UciEngine::new(io::stdout());
+
+    for line in io::stdin().lock().lines() {
+        let continues = engine.handle_command(&line.unwrap());
+        if !continues {
+            break;
+        }
+    }
+}
@@ -1,45 +1,42 @@
-use crate::model::game_state::{Color, GameState, Move, Position};
-use crate::model::move_generator::MoveGenerator;
-use crate::search::minimax_search::negamax_alpha_beta_with_trasposition_table;
-use crate::search::transposition_table::TranspositionTable;
+use crate::model::game_state::{Color, GameState, Move};
+use crate::notation::san::{move_to_san, parse_san};
+use crate::search::searcher::{SearchLimits, Searcher};
+use crate::uci::uci_utils::UciMove;
 
-use std::convert::TryFrom;
 use std::io::{self, BufRead, Write};
 
 pub struct Game {
     ai_color: Color,
     game_state: GameState,
-    move_generator: MoveGenerator,
-    transposition_table: TranspositionTable,
-    search_depth: u16,
+    searcher: Searcher,
 }
 
 impl Game {
     pub fn new(ai_color: Color, depth: u16) -> Self {
-        let game_state = GameState::new();
-        let transposition_table = TranspositionTable::with_capacity(1_000_000);
-        let move_generator = MoveGenerator::new();
+        let searcher = Searcher::new().with_limits(SearchLimits {
+            depth: Some(depth),
+            ..SearchLimits::default()
+        });
 
         Game {
-            ai_color: ai_color,
-            game_state: game_state,
-            move_generator: move_generator,
-            transposition_table: transposition_table,
-            search_depth: depth,
+            ai_color,
+            game_state: GameState::new(),
+            searcher,
         }
     }
 
     pub fn proceed(&mut self) -> bool {
         println!("{:?}", self.game_state);
 
+        if self.game_state.is_insufficient_material() {
+            println!("Draw by insufficient material");
+            return false;
+        }
+
         if self.game_state.to_move() == self.ai_color {
-            let (maybe_best_move, _, _) = negamax_alpha_beta_with_trasposition_table(
-                &mut self.game_state,
-                &self.move_generator,
-                &mut self.transposition_table,
-                self.search_depth,
-            );
-            if let Some(best_move) = maybe_best_move {
+            let outcome = self.searcher.search(&mut self.game_state);
+            if let Some(best_move) = outcome.best_move {
+                println!("AI plays {}", move_to_san(&self.game_state, &best_move));
                 self.game_state.apply_move_mut(best_move);
             } else {
                 println!("Game over");
@@ -64,24 +61,25 @@ impl Game {
 
             let (maybe_pos1_raw, maybe_pos2_raw) = (split.next(), split.next());
 
-            let (pos1_raw, pos2_raw) = match (maybe_pos1_raw, maybe_pos2_raw) {
-                (Some(a), Some(b)) => (a, b),
+            let uci_move = match (maybe_pos1_raw, maybe_pos2_raw) {
+                (Some(from), Some(to)) => format!("{}{}", from, to),
+                (Some(san), None) => match parse_san(&self.game_state, san) {
+                    Ok(next_move) => return next_move,
+                    Err(msg) => {
+                        println!("{}", msg);
+                        continue;
+                    }
+                },
                 (_, _) => continue,
             };
 
-            let pos1 = Game::parse_position(
-                pos1_raw.chars().nth(0).unwrap(),
-                pos1_raw.chars().nth(1).unwrap(),
-            );
-
-            let pos2 = Game::parse_position(
-                pos2_raw.chars().nth(0).unwrap(),
-                pos2_raw.chars().nth(1).unwrap(),
-            );
-
-            match (pos1, pos2) {
-                (Ok(from), Ok(to)) => {
-                    match self.move_generator.get_move(&self.game_state, from, to) {
+            match uci_move.parse::<UciMove>() {
+                Ok(parsed) => {
+                    match self
+                        .searcher
+                        .move_generator()
+                        .find_move(&self.game_state, &parsed)
+                    {
                         Some(next_move) => return next_move,
                         _ => {
                             println!("Move is not valid");
@@ -89,37 +87,11 @@ impl Game {
                         }
                     }
                 }
-                (_, _) => {
-                    println!("Bad coordinates");
+                Err(error) => {
+                    println!("{}", error);
                     continue;
                 }
             }
         }
     }
-
-    fn parse_position(file_raw: char, rank_raw: char) -> Result<Position, String> {
-        let file = match file_raw {
-            'a' => Ok(1),
-            'b' => Ok(2),
-            'c' => Ok(3),
-            'd' => Ok(4),
-            'e' => Ok(5),
-            'f' => Ok(6),
-            'g' => Ok(7),
-            'h' => Ok(8),
-            _ => Err("Invalid file"),
-        };
-
-        let rank = rank_raw
-            .to_digit(10)
-            .ok_or("Invalid rank")
-            .and_then(|digit| u8::try_from(digit).map_err(|_| "Cannot convert from u32 to u8"));
-
-        match (file, rank) {
-            (Ok(file), Ok(rank)) => return Ok(Position::new(file, rank)),
-            (Err(a), Ok(_)) => Err(a.to_string()),
-            (Ok(_), Err(a)) => Err(a.to_string()),
-            (Err(a), Err(b)) => Err(format!("{} and {}", a, b)),
-        }
-    }
 }
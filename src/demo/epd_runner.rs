@@ -0,0 +1,81 @@
+use crate::model::game_state::Move;
+use crate::notation::epd::EpdPosition;
+use crate::search::score::Score;
+use crate::search::searcher::Searcher;
+
+pub struct EpdResult {
+    pub solved: bool,
+    pub best_move: Option<Move>,
+    pub score: Score,
+    pub depth: u16,
+    pub nodes: u64,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct SuiteSummary {
+    pub solved: usize,
+    pub failed: usize,
+    pub nodes: u64,
+}
+
+// Searches every position from an empty table within the limits of the searcher and reports
+// each result as soon as it is known.
+pub fn run_suite<F>(
+    searcher: &mut Searcher,
+    positions: &[EpdPosition],
+    mut on_result: F,
+) -> SuiteSummary
+where
+    F: FnMut(&EpdPosition, &EpdResult),
+{
+    let mut summary = SuiteSummary::default();
+
+    for position in positions {
+        let result = run_position(searcher, position);
+        if result.solved {
+            summary.solved += 1;
+        } else {
+            summary.failed += 1;
+        }
+        summary.nodes += result.nodes;
+        on_result(position, &result);
+    }
+
+    summary
+}
+
+pub fn run_position(searcher: &mut Searcher, position: &EpdPosition) -> EpdResult {
+    let mut game_state = position.game_state;
+    searcher.new_game();
+    let outcome = searcher.search(&mut game_state);
+
+    EpdResult {
+        solved: is_solved(position, outcome.best_move, outcome.score),
+        best_move: outcome.best_move,
+        score: outcome.score,
+        depth: outcome.depth,
+        nodes: outcome.nodes,
+    }
+}
+
+// A position without any of the bm, am or dm operations has nothing to solve and always fails
+pub fn is_solved(position: &EpdPosition, best_move: Option<Move>, score: Score) -> bool {
+    let has_target = !position.best_moves.is_empty()
+        || !position.avoid_moves.is_empty()
+        || position.direct_mate.is_some();
+
+    let best_move = match best_move {
+        Some(best_move) if has_target => best_move,
+        _ => return false,
+    };
+
+    let finds_mate = match (position.direct_mate, score) {
+        (Some(moves), Score::MateIn(found)) => found > 0 && found <= moves,
+        (Some(_), Score::Evaluation(_)) => false,
+        (None, _) => true,
+    };
+
+    (position.best_moves.is_empty() || position.best_moves.contains(&best_move))
+        && !position.avoid_moves.contains(&best_move)
+        && finds_mate
+}
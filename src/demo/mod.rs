@@ -1 +1,3 @@
 pub mod cmdline_game;
+pub mod epd_runner;
+mod tests;
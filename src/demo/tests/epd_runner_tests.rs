@@ -0,0 +1,71 @@
+#[cfg(test)]
+use crate::demo::epd_runner::{is_solved, run_suite, SuiteSummary};
+#[cfg(test)]
+use crate::notation::epd::{parse_epd, parse_epd_file};
+#[cfg(test)]
+use crate::notation::san::parse_san;
+#[cfg(test)]
+use crate::search::score::Score;
+#[cfg(test)]
+use crate::search::searcher::{SearchLimits, Searcher};
+
+#[cfg(test)]
+const SUITE: &str = r#"6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra8#; dm 1; id "back rank";
+6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - am Ra8; id "avoid mate";
+r1bqkbnr/pppp1ppp/2n5/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - am g6; id "scholar";
+"#;
+
+#[test]
+fn suite_counts_solved_and_failed_positions() {
+    let positions = parse_epd_file(SUITE).unwrap();
+    let mut searcher = Searcher::new()
+        .with_table_size_mb(1)
+        .with_limits(SearchLimits {
+            depth: Some(3),
+            ..SearchLimits::default()
+        });
+
+    let mut reported = vec![];
+    let summary = run_suite(&mut searcher, &positions, |position, result| {
+        reported.push((position.id.clone().unwrap(), result.solved, result.nodes));
+    });
+
+    assert_eq!(
+        reported
+            .iter()
+            .map(|(id, solved, _)| (id.as_str(), *solved))
+            .collect::<Vec<_>>(),
+        vec![
+            ("back rank", true),
+            ("avoid mate", false),
+            ("scholar", true)
+        ]
+    );
+    assert_eq!(summary.solved, 2);
+    assert_eq!(summary.failed, 1);
+    assert_eq!(
+        summary.nodes,
+        reported.iter().map(|(_, _, nodes)| nodes).sum::<u64>()
+    );
+    assert!(summary.nodes > 0);
+    assert_ne!(summary, SuiteSummary::default());
+}
+
+#[test]
+fn direct_mate_requires_a_short_enough_mate() {
+    let position = parse_epd("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - dm 1;").unwrap();
+    let mate = parse_san(&position.game_state, "Ra8").unwrap();
+
+    assert!(is_solved(&position, Some(mate), Score::MateIn(1)));
+    assert!(!is_solved(&position, Some(mate), Score::MateIn(2)));
+    assert!(!is_solved(&position, Some(mate), Score::Evaluation(9000)));
+    assert!(!is_solved(&position, None, Score::MateIn(1)));
+}
+
+#[test]
+fn positions_without_targets_fail() {
+    let position = parse_epd("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - id \"none\";").unwrap();
+    let mate = parse_san(&position.game_state, "Ra8").unwrap();
+
+    assert!(!is_solved(&position, Some(mate), Score::MateIn(1)));
+}
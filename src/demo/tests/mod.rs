@@ -0,0 +1 @@
+mod epd_runner_tests;
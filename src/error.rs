@@ -0,0 +1,78 @@
+use crate::model::game_state::{Color, Position};
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ChessError {
+    InvalidSquare(String),
+    InvalidMove(String),
+    InvalidPosition(String),
+    UnknownVariant(String),
+}
+
+impl Display for ChessError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChessError::InvalidSquare(square) => write!(f, "Invalid square: {}", square),
+            ChessError::InvalidMove(msg) => write!(f, "Invalid move: {}", msg),
+            ChessError::InvalidPosition(msg) => write!(f, "Invalid position: {}", msg),
+            ChessError::UnknownVariant(name) => write!(f, "Unknown variant: {}", name),
+        }
+    }
+}
+
+impl std::error::Error for ChessError {}
+
+// a reason why a position cannot occur in a game, see GameState::validate
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PositionError {
+    MissingKing(Color),
+    MultipleKings(Color),
+    PawnOnBackRank(Position),
+    OpponentInCheck,
+    InvalidCastlingRights { color: Color, king_side: bool },
+    InvalidEnPassant(Position),
+    OverlappingPieces(Position),
+}
+
+impl Display for PositionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PositionError::MissingKing(color) => write!(f, "{:?} has no king", color),
+            PositionError::MultipleKings(color) => write!(f, "{:?} has several kings", color),
+            PositionError::PawnOnBackRank(square) => {
+                write!(f, "Pawn on {}", square.to_lowercase())
+            }
+            PositionError::OpponentInCheck => write!(f, "Side not to move is in check"),
+            PositionError::InvalidCastlingRights { color, king_side } => write!(
+                f,
+                "{:?} cannot castle {} side without king and rook on their starting squares",
+                color,
+                if *king_side { "king" } else { "queen" }
+            ),
+            PositionError::InvalidEnPassant(pawn) => write!(
+                f,
+                "No pawn can be captured en passant on {}",
+                pawn.to_lowercase()
+            ),
+            PositionError::OverlappingPieces(square) => {
+                write!(f, "Several pieces on {}", square.to_lowercase())
+            }
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
+
+impl From<Vec<PositionError>> for ChessError {
+    fn from(errors: Vec<PositionError>) -> ChessError {
+        let reasons: Vec<String> = errors.iter().map(PositionError::to_string).collect();
+        ChessError::InvalidPosition(reasons.join(", "))
+    }
+}
+
+// most parsers report errors as strings, so a chess error can be propagated with `?`
+impl From<ChessError> for String {
+    fn from(error: ChessError) -> String {
+        error.to_string()
+    }
+}
@@ -2,6 +2,8 @@
 extern crate lazy_static;
 
 pub mod demo;
+pub mod error;
 pub mod model;
+pub mod notation;
 pub mod search;
 pub mod uci;
@@ -48,7 +48,7 @@ pub fn attack_trace_for_queen() -> Vec<Vec<Vec<Position>>> {
     let bishop_trace = attack_trace_for_bishop();
     rook_trace
         .into_iter()
-        .zip(bishop_trace.into_iter())
+        .zip(bishop_trace)
         .map(|(mut rook_trace_pos, mut bishop_trace_pos)| {
             rook_trace_pos.append(&mut bishop_trace_pos);
             rook_trace_pos
@@ -75,7 +75,7 @@ pub fn attack_trace_for_knight() -> Vec<Vec<Vec<Position>>> {
                 starting_pos.delta(-2, -1),
             ]
             .into_iter()
-            .filter_map(|x| x)
+            .flatten()
             .collect();
 
             for x in candidates {
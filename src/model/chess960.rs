@@ -0,0 +1,90 @@
+use super::game_state::{CastlingRights, Color, GameState, Piece, Position};
+use super::game_state_builder::GameStateBuilder;
+use crate::error::ChessError;
+use rand::Rng;
+
+pub const POSITION_COUNT: u16 = 960;
+pub const STANDARD_POSITION_NUMBER: u16 = 518;
+
+// the squares of the two knights among the five squares left after placing bishops and queen
+const KNIGHT_SQUARES: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+// The back rank of the starting position with the given number in Scharnagl's numbering. The
+// bishops, the queen and the knights are placed in turn, and the king between the two rooks on
+// the three squares left.
+pub fn back_rank(number: u16) -> Result<[Piece; 8], ChessError> {
+    if number >= POSITION_COUNT {
+        return Err(ChessError::InvalidPosition(format!(
+            "Chess960 positions are numbered from 0 to 959: {}",
+            number
+        )));
+    }
+
+    let mut squares: [Option<Piece>; 8] = [None; 8];
+    let mut rest = usize::from(number);
+
+    squares[2 * (rest % 4) + 1] = Some(Piece::BISHOP);
+    rest /= 4;
+    squares[2 * (rest % 4)] = Some(Piece::BISHOP);
+    rest /= 4;
+    place_on_empty(&mut squares, rest % 6, Piece::QUEEN);
+    rest /= 6;
+
+    let (first_knight, second_knight) = KNIGHT_SQUARES[rest];
+    place_on_empty(&mut squares, second_knight, Piece::KNIGHT);
+    place_on_empty(&mut squares, first_knight, Piece::KNIGHT);
+    for piece in [Piece::ROOK, Piece::KING, Piece::ROOK].iter() {
+        place_on_empty(&mut squares, 0, *piece);
+    }
+
+    let mut pieces = [Piece::PAWN; 8];
+    for (piece, square) in pieces.iter_mut().zip(squares.iter()) {
+        *piece = square.unwrap();
+    }
+    Ok(pieces)
+}
+
+pub fn starting_position(number: u16) -> Result<GameState, ChessError> {
+    let pieces = back_rank(number)?;
+    let rook_files: Vec<u8> = (1..9)
+        .filter(|file| pieces[usize::from(*file - 1)] == Piece::ROOK)
+        .collect();
+
+    let mut builder = GameStateBuilder::new()
+        .with_castling_rights(CastlingRights::chess960(rook_files[1], rook_files[0]));
+    for (file, piece) in (1..9).zip(pieces.iter()) {
+        builder = builder
+            .with_piece(*piece, Color::WHITE, Position::new(file, 1))
+            .with_piece(Piece::PAWN, Color::WHITE, Position::new(file, 2))
+            .with_piece(Piece::PAWN, Color::BLACK, Position::new(file, 7))
+            .with_piece(*piece, Color::BLACK, Position::new(file, 8));
+    }
+
+    builder.build()
+}
+
+pub fn random_starting_position() -> GameState {
+    let number = rand::thread_rng().gen_range(0..POSITION_COUNT);
+    starting_position(number).unwrap()
+}
+
+fn place_on_empty(squares: &mut [Option<Piece>; 8], index: usize, piece: Piece) {
+    if let Some(square) = squares
+        .iter_mut()
+        .filter(|square| square.is_none())
+        .nth(index)
+    {
+        *square = Some(piece);
+    }
+}
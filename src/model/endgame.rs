@@ -0,0 +1,234 @@
+use super::game_state::{Color, GameState, Piece, Position};
+
+// evaluation of a won basic endgame, above any material balance but well below checkmate
+const KNOWN_WIN: i32 = 100_000;
+
+const VALUE_QUEEN: i32 = 9000;
+const VALUE_ROOK: i32 = 5000;
+const VALUE_MINOR: i32 = 3000;
+
+const DARK_SQUARES: u64 = 0xaa55aa55aa55aa55;
+const LIGHT_SQUARES: u64 = !DARK_SQUARES;
+
+const MASK_FILE1: u64 = 0x0101010101010101;
+const MASK_FILE8: u64 = 0x8080808080808080;
+
+struct Material {
+    pawns: u32,
+    knights: u32,
+    bishops: u32,
+    rooks: u32,
+    queens: u32,
+}
+
+impl Material {
+    fn of(game_state: &GameState, color: Color) -> Material {
+        let count = |piece| game_state.get_piece_mask(piece, color).count_ones();
+
+        Material {
+            pawns: count(Piece::PAWN),
+            knights: count(Piece::KNIGHT),
+            bishops: count(Piece::BISHOP),
+            rooks: count(Piece::ROOK),
+            queens: count(Piece::QUEEN),
+        }
+    }
+
+    fn is_bare_king(&self) -> bool {
+        self.pawns + self.pieces() == 0
+    }
+
+    fn pieces(&self) -> u32 {
+        self.knights + self.bishops + self.rooks + self.queens
+    }
+
+    fn non_pawn_value(&self) -> i32 {
+        (self.knights + self.bishops) as i32 * VALUE_MINOR
+            + self.rooks as i32 * VALUE_ROOK
+            + self.queens as i32 * VALUE_QUEEN
+    }
+}
+
+// Evaluation of endgames whose outcome is known, from the point of view of white. Returns None
+// when the position should be evaluated normally.
+pub fn evaluate(game_state: &GameState) -> Option<i32> {
+    if game_state.is_insufficient_material() {
+        return Some(0);
+    }
+
+    let white = Material::of(game_state, Color::WHITE);
+    let black = Material::of(game_state, Color::BLACK);
+
+    let (strong_side, strong, weak) = if black.is_bare_king() {
+        (Color::WHITE, &white, &black)
+    } else if white.is_bare_king() {
+        (Color::BLACK, &black, &white)
+    } else {
+        return None;
+    };
+
+    if strong.pawns > 0 || weak.pieces() > 0 {
+        return None;
+    }
+
+    let strong_king = king_position(game_state, strong_side);
+    let weak_king = king_position(game_state, strong_side.opposite());
+
+    let eval = match (strong.knights, strong.bishops, strong.rooks, strong.queens) {
+        (0, 0, 0, 1) | (0, 0, 1, 0) => {
+            KNOWN_WIN + strong.non_pawn_value() + mate_to_edge(strong_king, weak_king)
+        }
+        (1, 1, 0, 0) => {
+            let bishop_squares =
+                if game_state.get_piece_mask(Piece::BISHOP, strong_side) & DARK_SQUARES != 0 {
+                    DARK_SQUARES
+                } else {
+                    LIGHT_SQUARES
+                };
+            KNOWN_WIN
+                + strong.non_pawn_value()
+                + mate_to_corner(strong_king, weak_king, bishop_squares)
+        }
+        _ => return None,
+    };
+
+    Some(if strong_side == Color::WHITE {
+        eval
+    } else {
+        -eval
+    })
+}
+
+// Scales down evaluations of endgames which are hard or impossible to win despite a material
+// advantage.
+pub fn scale(game_state: &GameState, evaluation: i32) -> i32 {
+    let strong_side = if evaluation >= 0 {
+        Color::WHITE
+    } else {
+        Color::BLACK
+    };
+    let strong = Material::of(game_state, strong_side);
+    let weak = Material::of(game_state, strong_side.opposite());
+
+    if is_wrong_rook_pawn(game_state, strong_side, &strong, &weak) {
+        return evaluation / 16;
+    }
+
+    if is_opposite_colored_bishops(game_state, &strong, &weak) {
+        return evaluation / 2;
+    }
+
+    // without pawns, an advantage of at most a minor piece is usually not enough to win,
+    // neither are two knights
+    if strong.pawns == 0
+        && (strong.non_pawn_value() - weak.non_pawn_value() <= VALUE_MINOR
+            || strong.knights == 2 && strong.bishops + strong.rooks + strong.queens == 0)
+    {
+        return evaluation / 8;
+    }
+
+    evaluation
+}
+
+fn is_opposite_colored_bishops(game_state: &GameState, strong: &Material, weak: &Material) -> bool {
+    let only_bishops = |material: &Material| {
+        material.bishops == 1 && material.knights + material.rooks + material.queens == 0
+    };
+
+    if !only_bishops(strong) || !only_bishops(weak) {
+        return false;
+    }
+
+    let white_bishop = game_state.get_piece_mask(Piece::BISHOP, Color::WHITE);
+    let black_bishop = game_state.get_piece_mask(Piece::BISHOP, Color::BLACK);
+
+    (white_bishop & DARK_SQUARES != 0) != (black_bishop & DARK_SQUARES != 0)
+}
+
+// King, bishop(s) and rook pawn(s) against a bare king is a draw if the bishop does not control
+// the promotion square and the defending king reaches the corner.
+fn is_wrong_rook_pawn(
+    game_state: &GameState,
+    strong_side: Color,
+    strong: &Material,
+    weak: &Material,
+) -> bool {
+    if !weak.is_bare_king()
+        || strong.pawns == 0
+        || strong.knights + strong.rooks + strong.queens > 0
+    {
+        return false;
+    }
+
+    let pawns = *game_state.get_piece_mask(Piece::PAWN, strong_side);
+    let file = if pawns & !MASK_FILE1 == 0 {
+        1
+    } else if pawns & !MASK_FILE8 == 0 {
+        8
+    } else {
+        return false;
+    };
+
+    let promotion_rank = if strong_side == Color::WHITE { 8 } else { 1 };
+    let promotion_square = Position::new(file, promotion_rank);
+    let promotion_square_color = if promotion_square.to_bit_mask() & DARK_SQUARES != 0 {
+        DARK_SQUARES
+    } else {
+        LIGHT_SQUARES
+    };
+
+    if game_state.get_piece_mask(Piece::BISHOP, strong_side) & promotion_square_color != 0 {
+        return false;
+    }
+
+    distance(
+        king_position(game_state, strong_side.opposite()),
+        promotion_square,
+    ) <= 1
+}
+
+fn king_position(game_state: &GameState, color: Color) -> Position {
+    Position::from_numeric(
+        game_state
+            .get_piece_mask(Piece::KING, color)
+            .trailing_zeros() as u8,
+    )
+}
+
+fn distance(a: Position, b: Position) -> i32 {
+    let file_distance = (i32::from(a.file()) - i32::from(b.file())).abs();
+    let rank_distance = (i32::from(a.rank()) - i32::from(b.rank())).abs();
+    i32::max(file_distance, rank_distance)
+}
+
+fn distance_to_edge(position: Position) -> i32 {
+    let file = i32::from(position.file());
+    let rank = i32::from(position.rank());
+    [file - 1, 8 - file, rank - 1, 8 - rank]
+        .iter()
+        .copied()
+        .min()
+        .unwrap()
+}
+
+// bonus for driving the weak king to the edge of the board and keeping the kings close
+fn mate_to_edge(strong_king: Position, weak_king: Position) -> i32 {
+    100 * (3 - distance_to_edge(weak_king)) + 20 * (7 - distance(strong_king, weak_king))
+}
+
+// bonus for driving the weak king to a corner of the given square color
+fn mate_to_corner(strong_king: Position, weak_king: Position, corner_color: u64) -> i32 {
+    let corner_distance = [
+        Position::new(1, 1),
+        Position::new(8, 1),
+        Position::new(1, 8),
+        Position::new(8, 8),
+    ]
+    .iter()
+    .filter(|corner| corner.to_bit_mask() & corner_color != 0)
+    .map(|corner| distance(weak_king, *corner))
+    .min()
+    .unwrap();
+
+    100 * (7 - corner_distance) + 20 * (7 - distance(strong_king, weak_king))
+}
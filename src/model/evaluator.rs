@@ -1,4 +1,6 @@
-use super::game_state::{bit_mask_to_positions, Color, GameState, Piece};
+use super::endgame;
+use super::game_state::{bit_mask_to_positions, Color, GameState, Move, Piece};
+use super::variant::Variant;
 
 const BASE_VALUE_PAWN: i32 = 1000;
 const BASE_VALUE_KNIGT: i32 = 3000;
@@ -43,7 +45,37 @@ const POSITION_KING: [i32; 64] = [
     0, 0, 0,
 ];
 
+// Evaluation used by the search. Evaluations are from the point of view of white. Incremental
+// evaluators are set up with the root position of a search, and notified of every move before it
+// is applied to and after it is taken back from the board.
+pub trait PositionEvaluator {
+    fn evaluate(&self, game_state: &GameState) -> i32;
+
+    fn refresh(&mut self, _game_state: &GameState) {}
+
+    fn apply_move(&mut self, _game_state: &GameState, _to_apply: Move) {}
+
+    fn unapply_move(&mut self, _game_state: &GameState, _to_unapply: Move) {}
+}
+
+#[derive(Clone, Copy)]
+pub struct HandcraftedEvaluator;
+
+impl PositionEvaluator for HandcraftedEvaluator {
+    fn evaluate(&self, game_state: &GameState) -> i32 {
+        evaluate(game_state)
+    }
+}
+
 pub fn evaluate(game_state: &GameState) -> i32 {
+    // the endgame knowledge assumes that only checkmate wins
+    let variant = game_state.variant();
+    if variant == Variant::Standard {
+        if let Some(evaluation) = endgame::evaluate(game_state) {
+            return evaluation;
+        }
+    }
+
     let mut evaluation = 0;
 
     evaluation += evaluate_pawns(game_state);
@@ -52,7 +84,12 @@ pub fn evaluate(game_state: &GameState) -> i32 {
     evaluation += evaluate_rooks(game_state);
     evaluation += evaluate_queens(game_state);
     evaluation += evaluate_kings(game_state);
+    evaluation += variant.evaluate(game_state);
 
+    // material does not decide the variants, their goals are not scaled down with it
+    if variant == Variant::Standard {
+        evaluation = endgame::scale(game_state, evaluation);
+    }
     evaluation
 }
 
@@ -1,6 +1,27 @@
+use super::move_generator::MoveGenerator;
+use super::variant::Variant;
 use super::zobrist_hash;
+use crate::error::{ChessError, PositionError};
 use std::convert::TryFrom;
 use std::fmt::{Debug, Display, Error, Formatter};
+use std::str::FromStr;
+
+const DARK_SQUARES: u64 = 0xaa55aa55aa55aa55;
+const MASK_RANK1: u64 = 0x00000000000000ff;
+const MASK_RANK8: u64 = 0xff00000000000000;
+
+// finds checks when validating positions and counting the checks given in Three-check
+lazy_static! {
+    static ref CHECK_MOVE_GENERATOR: MoveGenerator = MoveGenerator::new();
+}
+
+// Moves that can be unapplied, enough for any search on top of a game. Older undo information
+// is overwritten, so the moves of a long game cannot all be taken back.
+const UNDO_CAPACITY: usize = 256;
+
+const PACKED_TO_SHIFT: u16 = 6;
+const PACKED_PROMOTION_SHIFT: u16 = 12;
+const PACKED_SQUARE_BITS: u16 = 0b11_1111;
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub enum MoveType {
@@ -17,25 +38,240 @@ pub struct Move {
     pub from: Position,
     pub to: Position,
     pub promotes_to: Option<Piece>,
-    pub last_en_passant: Option<Position>,
-    pub last_castling_rights: CastlingRights,
 }
 
+impl Move {
+    pub fn pack(&self) -> PackedMove {
+        PackedMove::new(self.from, self.to, self.promotes_to)
+    }
+
+    // Castling is stored as the king capturing its own rook, which tells the sides apart in
+    // Chess960. The king ends on the g or c file and the rook next to it on the f or d file.
+    pub fn castling_destinations(&self) -> (Position, Position) {
+        let rank = self.from.rank();
+        if self.to.file() > self.from.file() {
+            (Position::new(7, rank), Position::new(6, rank))
+        } else {
+            (Position::new(3, rank), Position::new(4, rank))
+        }
+    }
+
+    // the square the moving piece ends on
+    pub fn destination(&self) -> Position {
+        match self.move_type {
+            MoveType::Castling => self.castling_destinations().0,
+            _ => self.to,
+        }
+    }
+}
+
+// long algebraic notation as used by UCI, e.g. e2e4 or e7e8q, with castling as the king move
+impl Display for Move {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        Display::fmt(
+            &PackedMove::new(self.from, self.destination(), self.promotes_to),
+            f,
+        )
+    }
+}
+
+// A move in 16 bits for storage: the from square in the low six bits, then the to square and the
+// promotion piece. The rest of the move is recovered from the position it is played in.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct PackedMove(u16);
+
+impl PackedMove {
+    pub fn new(from: Position, to: Position, promotes_to: Option<Piece>) -> PackedMove {
+        let promotion = match promotes_to {
+            None => 0,
+            Some(Piece::KNIGHT) => 1,
+            Some(Piece::BISHOP) => 2,
+            Some(Piece::ROOK) => 3,
+            Some(_) => 4,
+        };
+
+        PackedMove(
+            u16::from(from.to_numeric())
+                | u16::from(to.to_numeric()) << PACKED_TO_SHIFT
+                | promotion << PACKED_PROMOTION_SHIFT,
+        )
+    }
+
+    pub fn from_bits(bits: u16) -> PackedMove {
+        PackedMove(bits)
+    }
+
+    pub fn bits(&self) -> u16 {
+        self.0
+    }
+
+    pub fn from(&self) -> Position {
+        Position::from_numeric((self.0 & PACKED_SQUARE_BITS) as u8)
+    }
+
+    pub fn to(&self) -> Position {
+        Position::from_numeric((self.0 >> PACKED_TO_SHIFT & PACKED_SQUARE_BITS) as u8)
+    }
+
+    pub fn promotes_to(&self) -> Option<Piece> {
+        match self.0 >> PACKED_PROMOTION_SHIFT {
+            1 => Some(Piece::KNIGHT),
+            2 => Some(Piece::BISHOP),
+            3 => Some(Piece::ROOK),
+            4 => Some(Piece::QUEEN),
+            _ => None,
+        }
+    }
+
+    // The move of the piece on the from square, or None when the side to move has no piece there
+    // or would capture its own piece. Castling is the king taking its own rook, or the king moving
+    // two files as in standard chess. Legality is not checked.
+    pub fn unpack(&self, game_state: &GameState) -> Option<Move> {
+        let (from, mut to) = (self.from(), self.to());
+        let to_move = game_state.to_move();
+        let moving_piece = match game_state.get_piece(from) {
+            Some((piece, color)) if color == to_move => piece,
+            _ => return None,
+        };
+
+        let move_type = match game_state.get_piece(to) {
+            Some((Piece::ROOK, color)) if color == to_move && moving_piece == Piece::KING => {
+                MoveType::Castling
+            }
+            Some((_, color)) if color == to_move => return None,
+            Some((captured, _)) => MoveType::Capture(captured),
+            None if moving_piece == Piece::PAWN && from.file() != to.file() => MoveType::EnPassant,
+            None if moving_piece == Piece::KING
+                && from.rank() == to.rank()
+                && (from.file() as i8 - to.file() as i8).abs() == 2 =>
+            {
+                let king_side = to.file() > from.file();
+                to = game_state.castling_rights.rook_square(to_move, king_side);
+                MoveType::Castling
+            }
+            None => MoveType::Step,
+        };
+
+        Some(Move {
+            move_type,
+            moving_piece,
+            from,
+            to,
+            promotes_to: self.promotes_to(),
+        })
+    }
+}
+
+impl Display for PackedMove {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(
+            f,
+            "{}{}",
+            self.from().to_lowercase(),
+            self.to().to_lowercase()
+        )?;
+
+        match self.promotes_to() {
+            Some(Piece::KNIGHT) => write!(f, "n"),
+            Some(Piece::BISHOP) => write!(f, "b"),
+            Some(Piece::ROOK) => write!(f, "r"),
+            Some(_) => write!(f, "q"),
+            None => Ok(()),
+        }
+    }
+}
+
+// the state lost when a move is applied, restored when it is unapplied
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+struct UndoInfo {
+    en_passant: Option<Position>,
+    castling_rights: CastlingRights,
+    checks_given: [u8; 2],
+}
+
+// The rook files are those of the rooks the king castles with, h and a in standard chess. Both
+// sides castle with rooks on the same files, as in every Chess960 starting position.
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
 pub struct CastlingRights {
     pub white_king_side: bool,
     pub white_queen_side: bool,
     pub black_king_side: bool,
     pub black_queen_side: bool,
+    pub king_side_rook_file: u8,
+    pub queen_side_rook_file: u8,
 }
 
 impl CastlingRights {
     pub fn initial() -> CastlingRights {
+        CastlingRights::chess960(8, 1)
+    }
+
+    pub fn none() -> CastlingRights {
+        CastlingRights {
+            white_king_side: false,
+            white_queen_side: false,
+            black_king_side: false,
+            black_queen_side: false,
+            king_side_rook_file: 8,
+            queen_side_rook_file: 1,
+        }
+    }
+
+    // all rights, castling with rooks on the given files
+    pub fn chess960(king_side_rook_file: u8, queen_side_rook_file: u8) -> CastlingRights {
         CastlingRights {
             white_king_side: true,
             white_queen_side: true,
             black_king_side: true,
             black_queen_side: true,
+            king_side_rook_file,
+            queen_side_rook_file,
+        }
+    }
+
+    pub fn is_allowed(&self, color: Color, king_side: bool) -> bool {
+        match (color, king_side) {
+            (Color::WHITE, true) => self.white_king_side,
+            (Color::WHITE, false) => self.white_queen_side,
+            (Color::BLACK, true) => self.black_king_side,
+            (Color::BLACK, false) => self.black_queen_side,
+        }
+    }
+
+    pub fn rook_square(&self, color: Color, king_side: bool) -> Position {
+        let file = if king_side {
+            self.king_side_rook_file
+        } else {
+            self.queen_side_rook_file
+        };
+        Position::new(file, color.back_rank())
+    }
+
+    // Moving the king gives up both rights of its side. Moving a castling rook, or having it
+    // captured, gives up the right of that rook.
+    pub fn after_move(&self, m: &Move, to_move: Color) -> CastlingRights {
+        let mut rights = *self;
+
+        if m.moving_piece == Piece::KING {
+            *rights.get_king_side_mut(to_move) = false;
+            *rights.get_queen_side_mut(to_move) = false;
+        }
+        if m.moving_piece == Piece::ROOK {
+            rights.remove_rook(to_move, m.from);
+        }
+        if let MoveType::Capture(Piece::ROOK) = m.move_type {
+            rights.remove_rook(to_move.opposite(), m.to);
+        }
+
+        rights
+    }
+
+    fn remove_rook(&mut self, color: Color, square: Position) {
+        if square == self.rook_square(color, true) {
+            *self.get_king_side_mut(color) = false;
+        }
+        if square == self.rook_square(color, false) {
+            *self.get_queen_side_mut(color) = false;
         }
     }
 
@@ -69,6 +305,14 @@ impl Color {
             Color::BLACK => Color::WHITE,
         }
     }
+
+    // the rank the pieces of the color start on
+    pub fn back_rank(&self) -> u8 {
+        match self {
+            Color::WHITE => 1,
+            Color::BLACK => 8,
+        }
+    }
 }
 
 #[derive(Eq, PartialEq, Copy, Clone, Ord, PartialOrd)]
@@ -77,11 +321,12 @@ pub struct Position {
 }
 
 impl Position {
+    // panics on coordinates outside the board, use try_new for untrusted input
     pub fn new(file: u8, rank: u8) -> Position {
-        if file < 1 || file > 8 {
+        if !(1..=8).contains(&file) {
             panic!("File must be between 1 and 8")
         }
-        if rank < 1 || rank > 8 {
+        if !(1..=8).contains(&rank) {
             panic!("Rank must be between 1 and 8")
         }
 
@@ -90,6 +335,17 @@ impl Position {
         }
     }
 
+    pub fn try_new(file: u8, rank: u8) -> Result<Position, ChessError> {
+        if !(1..=8).contains(&file) || !(1..=8).contains(&rank) {
+            return Err(ChessError::InvalidSquare(format!(
+                "file {} rank {}",
+                file, rank
+            )));
+        }
+
+        Ok(Position::new(file, rank))
+    }
+
     pub fn to_bit_mask(&self) -> u64 {
         1 << self.position
     }
@@ -98,10 +354,10 @@ impl Position {
         let new_file = i16::from(self.file()) + i16::from(delta_file);
         let new_rank = i16::from(self.rank()) + i16::from(delta_rank);
 
-        if new_file < 1 || new_file > 8 {
+        if !(1..=8).contains(&new_file) {
             return Option::None;
         }
-        if new_rank < 1 || new_rank > 8 {
+        if !(1..=8).contains(&new_rank) {
             return Option::None;
         }
 
@@ -134,6 +390,24 @@ impl Position {
     pub fn mirror_rank(&self) -> Position {
         Position::new(self.file(), 9 - self.rank())
     }
+
+    // the square in algebraic notation, e.g. e4
+    pub fn to_lowercase(&self) -> String {
+        format!("{}{}", (b'a' + self.file() - 1) as char, self.rank())
+    }
+}
+
+impl FromStr for Position {
+    type Err = ChessError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.as_bytes() {
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => {
+                Ok(Position::new(file - b'a' + 1, rank - b'0'))
+            }
+            _ => Err(ChessError::InvalidSquare(s.to_string())),
+        }
+    }
 }
 
 impl Display for Position {
@@ -165,7 +439,7 @@ pub enum Piece {
     KING,
 }
 
-#[derive(Eq, PartialEq, Copy, Clone)]
+#[derive(Copy, Clone)]
 pub struct GameState {
     white_pawn: u64,
     white_knight: u64,
@@ -188,10 +462,49 @@ pub struct GameState {
     pub to_move: Color,
 
     pub zobrist_hash: u64,
+
+    variant: Variant,
+    // by white and black, only counted in variants where checks decide the game
+    checks_given: [u8; 2],
+
+    undo_stack: [UndoInfo; UNDO_CAPACITY],
+    undo_len: usize,
+}
+
+// positions are equal regardless of the moves that led to them
+impl PartialEq for GameState {
+    fn eq(&self, other: &Self) -> bool {
+        self.white_pawn == other.white_pawn
+            && self.white_knight == other.white_knight
+            && self.white_bishop == other.white_bishop
+            && self.white_rook == other.white_rook
+            && self.white_queen == other.white_queen
+            && self.white_king == other.white_king
+            && self.black_pawn == other.black_pawn
+            && self.black_knight == other.black_knight
+            && self.black_bishop == other.black_bishop
+            && self.black_rook == other.black_rook
+            && self.black_queen == other.black_queen
+            && self.black_king == other.black_king
+            && self.en_passant == other.en_passant
+            && self.castling_rights == other.castling_rights
+            && self.to_move == other.to_move
+            && self.variant == other.variant
+            && self.checks_given == other.checks_given
+            && self.zobrist_hash == other.zobrist_hash
+    }
+}
+
+impl Eq for GameState {}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GameState {
-    fn new_empty() -> Self {
+    pub(crate) fn new_empty() -> Self {
         GameState {
             white_pawn: 0,
             white_knight: 0,
@@ -214,6 +527,16 @@ impl GameState {
             to_move: Color::WHITE,
 
             zobrist_hash: 0,
+
+            variant: Variant::Standard,
+            checks_given: [0; 2],
+
+            undo_stack: [UndoInfo {
+                en_passant: None,
+                castling_rights: CastlingRights::none(),
+                checks_given: [0; 2],
+            }; UNDO_CAPACITY],
+            undo_len: 0,
         }
     }
 
@@ -258,16 +581,185 @@ impl GameState {
         self.en_passant
     }
 
+    pub(crate) fn set_en_passant(&mut self, en_passant: Option<Position>) {
+        self.en_passant = en_passant;
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    pub(crate) fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+    }
+
+    pub fn checks_given(&self, color: Color) -> u8 {
+        self.checks_given[color_index(color)]
+    }
+
+    pub(crate) fn set_checks_given(&mut self, color: Color, checks: u8) {
+        self.checks_given[color_index(color)] = checks;
+    }
+
+    // leaves the hash as it is, GameStateBuilder edits positions and recomputes it
     pub fn set_piece(&mut self, piece: Piece, color: Color, position: Position) {
         let position_bit_mask = position.to_bit_mask();
         let piece_mask = self.get_piece_mask_mut(piece, color);
-        *piece_mask = *piece_mask | position_bit_mask;
+        *piece_mask |= position_bit_mask;
     }
 
     pub fn get_piece_position(&self, piece: Piece, color: Color) -> Vec<Position> {
         bit_mask_to_positions(*self.get_piece_mask(piece, color))
     }
 
+    // neither side can checkmate: bare kings, a single minor piece, or only bishops on squares
+    // of one color
+    pub fn is_insufficient_material(&self) -> bool {
+        match self.variant {
+            Variant::Standard => (),
+            // a bare king can still walk to the hill
+            Variant::KingOfTheHill => return false,
+            // any piece can give check
+            Variant::ThreeCheck => {
+                return (self.white_mask() | self.black_mask())
+                    & !(self.white_king | self.black_king)
+                    == 0;
+            }
+        }
+
+        if self.white_pawn | self.black_pawn | self.white_rook | self.black_rook != 0
+            || self.white_queen | self.black_queen != 0
+        {
+            return false;
+        }
+
+        let knights = self.white_knight | self.black_knight;
+        let bishops = self.white_bishop | self.black_bishop;
+        let minor_pieces = (knights | bishops).count_ones();
+
+        if minor_pieces <= 1 {
+            return true;
+        }
+
+        knights == 0 && (bishops & DARK_SQUARES == 0 || bishops & !DARK_SQUARES == 0)
+    }
+
+    // any piece besides pawns and the king, positions without one are prone to zugzwang
+    pub fn has_non_pawn_material(&self, color: Color) -> bool {
+        let pieces = match color {
+            Color::WHITE => {
+                self.white_knight | self.white_bishop | self.white_rook | self.white_queen
+            }
+            Color::BLACK => {
+                self.black_knight | self.black_bishop | self.black_rook | self.black_queen
+            }
+        };
+
+        pieces != 0
+    }
+
+    // reports every way the position differs from one reachable in a game
+    pub fn validate(&self) -> Result<(), Vec<PositionError>> {
+        let mut errors = vec![];
+        let colors = [Color::WHITE, Color::BLACK];
+        let pieces = [
+            Piece::PAWN,
+            Piece::KNIGHT,
+            Piece::BISHOP,
+            Piece::ROOK,
+            Piece::QUEEN,
+            Piece::KING,
+        ];
+
+        let mut occupied = 0;
+        let mut overlapping = 0;
+        for color in colors.iter() {
+            for piece in pieces.iter() {
+                let mask = *self.get_piece_mask(*piece, *color);
+                overlapping |= occupied & mask;
+                occupied |= mask;
+            }
+        }
+        for square in bit_mask_to_positions(overlapping) {
+            errors.push(PositionError::OverlappingPieces(square));
+        }
+
+        for color in colors.iter() {
+            match self.get_piece_mask(Piece::KING, *color).count_ones() {
+                0 => errors.push(PositionError::MissingKing(*color)),
+                1 => (),
+                _ => errors.push(PositionError::MultipleKings(*color)),
+            }
+        }
+
+        let back_ranks = MASK_RANK1 | MASK_RANK8;
+        for square in bit_mask_to_positions((self.white_pawn | self.black_pawn) & back_ranks) {
+            errors.push(PositionError::PawnOnBackRank(square));
+        }
+
+        let waiting = self.to_move.opposite();
+        if self.get_piece_mask(Piece::KING, waiting).count_ones() == 1
+            && CHECK_MOVE_GENERATOR.is_check(self, waiting)
+        {
+            errors.push(PositionError::OpponentInCheck);
+        }
+
+        for color in colors.iter() {
+            for king_side in [true, false].iter() {
+                if self.castling_rights.is_allowed(*color, *king_side)
+                    && !self.can_castle_with_rook(*color, *king_side)
+                {
+                    errors.push(PositionError::InvalidCastlingRights {
+                        color: *color,
+                        king_side: *king_side,
+                    });
+                }
+            }
+        }
+
+        if let Some(pawn) = self.en_passant {
+            if !self.is_valid_en_passant(pawn) {
+                errors.push(PositionError::InvalidEnPassant(pawn));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // the king on its back rank, with the castling rook on the same rank to the side it castles to
+    fn can_castle_with_rook(&self, color: Color, king_side: bool) -> bool {
+        let rook = self.castling_rights.rook_square(color, king_side);
+        let kings = self.get_piece_position(Piece::KING, color);
+
+        match kings.as_slice() {
+            [king] => {
+                king.rank() == color.back_rank()
+                    && (rook.file() > king.file()) == king_side
+                    && self.get_piece(rook) == Some((Piece::ROOK, color))
+            }
+            _ => false,
+        }
+    }
+
+    // the pawn must stand next to the squares it has just crossed, which are both empty
+    fn is_valid_en_passant(&self, pawn: Position) -> bool {
+        let (pawn_color, pawn_rank, direction) = if self.to_move == Color::WHITE {
+            (Color::BLACK, 5, 1)
+        } else {
+            (Color::WHITE, 4, -1)
+        };
+
+        pawn.rank() == pawn_rank
+            && self.get_piece(pawn) == Some((Piece::PAWN, pawn_color))
+            && [pawn.delta(0, direction), pawn.delta(0, 2 * direction)]
+                .iter()
+                .all(|square| matches!(square.map(|s| self.get_piece(s)), Some(None)))
+    }
+
     pub fn collide(&self, position: Position) -> Option<Color> {
         let white_mask = self.white_mask();
         let black_mask = self.black_mask();
@@ -324,9 +816,11 @@ impl GameState {
         self.zobrist_hash = zobrist_hash::apply_move(
             self.zobrist_hash,
             &self.castling_rights,
+            self.en_passant,
             to_apply,
             self.to_move(),
         );
+        self.push_undo_info();
 
         let moving_piece = to_apply.moving_piece;
         let piece_mask_for_moving = *self.get_piece_mask(moving_piece, self.to_move());
@@ -336,53 +830,32 @@ impl GameState {
                 let new_state_taken_piece_mask =
                     self.get_piece_mask_mut(captured_piece, self.to_move().opposite());
                 *new_state_taken_piece_mask ^= to_apply.to.to_bit_mask();
-
-                if captured_piece == Piece::ROOK {
-                    if to_apply.to.file() == 1 {
-                        *self
-                            .castling_rights
-                            .get_queen_side_mut(self.to_move().opposite()) = false;
-                    } else if to_apply.to.file() == 8 {
-                        *self
-                            .castling_rights
-                            .get_king_side_mut(self.to_move().opposite()) = false;
-                    }
-                }
             }
             MoveType::EnPassant => {
                 let direction_multiplier = if self.to_move == Color::WHITE { 1 } else { -1 };
                 let new_state_taken_piece_mask =
                     self.get_piece_mask_mut(Piece::PAWN, self.to_move().opposite());
-                *new_state_taken_piece_mask = *new_state_taken_piece_mask
-                    ^ (to_apply
-                        .to
-                        .delta(0, -direction_multiplier)
-                        .unwrap()
-                        .to_bit_mask());
+                *new_state_taken_piece_mask ^= to_apply
+                    .to
+                    .delta(0, -direction_multiplier)
+                    .unwrap()
+                    .to_bit_mask();
             }
             MoveType::Castling => {
-                let rank = to_apply.from.rank();
-                let (old_rook_file, new_rook_file) = if to_apply.to.file() < 5 {
-                    (1, 4)
-                } else {
-                    (8, 6)
-                };
-                let old_rook_position = Position::new(old_rook_file, rank);
-                let new_rook_position = Position::new(new_rook_file, rank);
+                let new_rook_position = to_apply.castling_destinations().1;
                 let rook_piece_mask = self.get_piece_mask_mut(Piece::ROOK, self.to_move());
-                *rook_piece_mask ^= old_rook_position.to_bit_mask();
+                *rook_piece_mask ^= to_apply.to.to_bit_mask();
                 *rook_piece_mask ^= new_rook_position.to_bit_mask();
-                *self.castling_rights.get_king_side_mut(self.to_move) = false;
-                *self.castling_rights.get_queen_side_mut(self.to_move) = false;
             }
             MoveType::Step => (),
         }
 
+        // the king may already stand on its castling destination in Chess960
         let mut moving_piece_zor = to_apply.from.to_bit_mask();
 
         match to_apply.promotes_to {
             None => {
-                moving_piece_zor = moving_piece_zor | to_apply.to.to_bit_mask();
+                moving_piece_zor ^= to_apply.destination().to_bit_mask();
             }
             Some(piece) => {
                 *self.get_piece_mask_mut(piece, self.to_move) ^= to_apply.to.to_bit_mask();
@@ -407,29 +880,26 @@ impl GameState {
             self.en_passant = None;
         }
 
-        // lose all castling rights when king moves
-        if moving_piece == Piece::KING {
-            *self.castling_rights.get_king_side_mut(self.to_move) = false;
-            *self.castling_rights.get_queen_side_mut(self.to_move) = false;
-        }
+        self.castling_rights = self.castling_rights.after_move(&to_apply, self.to_move);
 
-        // lose queen side castling rights when queen side rook moves
-        if moving_piece == Piece::ROOK && to_apply.from.file() == 1 {
-            *self.castling_rights.get_queen_side_mut(self.to_move()) = false;
-        }
+        self.to_move = self.to_move.opposite();
 
-        // lose king side castling rights when king side rook moves
-        if moving_piece == Piece::ROOK && to_apply.from.file() == 8 {
-            *self.castling_rights.get_king_side_mut(self.to_move()) = false;
+        if self.variant.counts_checks() && CHECK_MOVE_GENERATOR.is_check(self, self.to_move) {
+            let mut checks_given = self.checks_given;
+            checks_given[color_index(self.to_move.opposite())] += 1;
+            self.change_checks_given(checks_given);
         }
-
-        self.to_move = self.to_move.opposite();
     }
 
+    // unapplies the last applied move, which must be the given one
     pub fn unapply_move_mut(&mut self, to_unapply: Move) {
+        let undo_info = self.pop_undo_info();
+        self.change_checks_given(undo_info.checks_given);
         self.zobrist_hash = zobrist_hash::unapply_move(
             self.zobrist_hash,
             &self.castling_rights,
+            &undo_info.castling_rights,
+            undo_info.en_passant,
             to_unapply,
             self.to_move(),
         );
@@ -441,8 +911,7 @@ impl GameState {
             MoveType::Capture(captured_piece) => {
                 let new_state_taken_piece_mask =
                     self.get_piece_mask_mut(captured_piece, self.to_move());
-                *new_state_taken_piece_mask =
-                    *new_state_taken_piece_mask ^ to_unapply.to.to_bit_mask();
+                *new_state_taken_piece_mask ^= to_unapply.to.to_bit_mask();
             }
             MoveType::EnPassant => {
                 let direction_multiplier = if self.to_move.opposite() == Color::WHITE {
@@ -452,26 +921,18 @@ impl GameState {
                 };
                 let new_state_taken_piece_mask =
                     self.get_piece_mask_mut(Piece::PAWN, self.to_move());
-                *new_state_taken_piece_mask = *new_state_taken_piece_mask
-                    ^ (to_unapply
-                        .to
-                        .delta(0, -direction_multiplier)
-                        .unwrap()
-                        .to_bit_mask());
+                *new_state_taken_piece_mask ^= to_unapply
+                    .to
+                    .delta(0, -direction_multiplier)
+                    .unwrap()
+                    .to_bit_mask();
             }
             MoveType::Castling => {
-                let rank = to_unapply.from.rank();
-                let (old_rook_file, new_rook_file) = if to_unapply.to.file() < 5 {
-                    (1, 4)
-                } else {
-                    (8, 6)
-                };
-                let old_rook_position = Position::new(old_rook_file, rank);
-                let new_rook_position = Position::new(new_rook_file, rank);
+                let new_rook_position = to_unapply.castling_destinations().1;
                 let rook_piece_mask =
                     self.get_piece_mask_mut(Piece::ROOK, self.to_move().opposite());
-                *rook_piece_mask = *rook_piece_mask ^ old_rook_position.to_bit_mask();
-                *rook_piece_mask = *rook_piece_mask ^ new_rook_position.to_bit_mask();
+                *rook_piece_mask ^= to_unapply.to.to_bit_mask();
+                *rook_piece_mask ^= new_rook_position.to_bit_mask();
             }
             MoveType::Step => (),
         }
@@ -480,7 +941,7 @@ impl GameState {
 
         match to_unapply.promotes_to {
             None => {
-                moving_piece_zor = moving_piece_zor | to_unapply.to.to_bit_mask();
+                moving_piece_zor ^= to_unapply.destination().to_bit_mask();
             }
             Some(piece) => {
                 *self.get_piece_mask_mut(piece, self.to_move.opposite()) ^=
@@ -492,11 +953,46 @@ impl GameState {
         *self.get_piece_mask_mut(moving_piece, self.to_move().opposite()) =
             piece_mask_for_moving ^ moving_piece_zor;
 
-        self.en_passant = to_unapply.last_en_passant;
-        self.castling_rights = to_unapply.last_castling_rights;
+        self.en_passant = undo_info.en_passant;
+        self.castling_rights = undo_info.castling_rights;
         self.to_move = self.to_move.opposite();
     }
 
+    // passes the turn to the opponent
+    pub fn apply_null_move_mut(&mut self) {
+        self.push_undo_info();
+        self.zobrist_hash = zobrist_hash::null_move(self.zobrist_hash, self.en_passant);
+        self.en_passant = None;
+        self.to_move = self.to_move.opposite();
+    }
+
+    pub fn unapply_null_move_mut(&mut self) {
+        let undo_info = self.pop_undo_info();
+        self.zobrist_hash = zobrist_hash::null_move(self.zobrist_hash, undo_info.en_passant);
+        self.en_passant = undo_info.en_passant;
+        self.to_move = self.to_move.opposite();
+    }
+
+    fn push_undo_info(&mut self) {
+        self.undo_stack[self.undo_len % UNDO_CAPACITY] = UndoInfo {
+            en_passant: self.en_passant,
+            castling_rights: self.castling_rights,
+            checks_given: self.checks_given,
+        };
+        self.undo_len += 1;
+    }
+
+    fn change_checks_given(&mut self, checks_given: [u8; 2]) {
+        self.zobrist_hash =
+            zobrist_hash::change_checks_given(self.zobrist_hash, self.checks_given, checks_given);
+        self.checks_given = checks_given;
+    }
+
+    fn pop_undo_info(&mut self) -> UndoInfo {
+        self.undo_len = self.undo_len.checked_sub(1).expect("No move to unapply");
+        self.undo_stack[self.undo_len % UNDO_CAPACITY]
+    }
+
     pub fn get_piece_mask(&self, piece: Piece, color: Color) -> &u64 {
         match (piece, color) {
             (Piece::PAWN, Color::WHITE) => &self.white_pawn,
@@ -580,26 +1076,26 @@ impl GameState {
             Option::None
         }
     }
+}
 
-    pub fn to_string(&self) -> String {
-        let mut builder = String::from("");
-
+impl Display for GameState {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         for rank in (1..9).rev() {
             for file in 1..9 {
-                builder.push_str(self.square_to_unicode(Position::new(file, rank)));
+                write!(f, "{}", self.square_to_unicode(Position::new(file, rank)))?;
             }
             if rank > 1 {
-                builder.push_str("\n");
+                writeln!(f)?;
             }
         }
 
-        builder
+        Ok(())
     }
 }
 
 impl Debug for GameState {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        write!(f, "\n{}", self.to_string())
+        write!(f, "\n{}", self)
     }
 }
 
@@ -621,3 +1117,10 @@ pub fn bit_mask_to_positions(bit_mask: u64) -> Vec<Position> {
 
     vec
 }
+
+pub(crate) fn color_index(color: Color) -> usize {
+    match color {
+        Color::WHITE => 0,
+        Color::BLACK => 1,
+    }
+}
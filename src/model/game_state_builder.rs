@@ -0,0 +1,118 @@
+use super::game_state::{color_index, CastlingRights, Color, GameState, Piece, Position};
+use super::variant::Variant;
+use super::zobrist_hash;
+use crate::error::ChessError;
+
+// Sets up or edits a position square by square. The position is validated and hashed when it is
+// built, so the game state handed out can be searched without running into missing kings or
+// impossible castling.
+#[derive(Clone, Debug)]
+pub struct GameStateBuilder {
+    squares: [Option<(Piece, Color)>; 64],
+    to_move: Color,
+    castling_rights: CastlingRights,
+    en_passant: Option<Position>,
+    variant: Variant,
+    checks_given: [u8; 2],
+}
+
+impl Default for GameStateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// starts editing from an existing position
+impl From<&GameState> for GameStateBuilder {
+    fn from(game_state: &GameState) -> Self {
+        let mut builder = GameStateBuilder::new()
+            .with_to_move(game_state.to_move())
+            .with_castling_rights(game_state.castling_rights)
+            .with_en_passant(game_state.en_passant())
+            .with_variant(game_state.variant());
+        for color in [Color::WHITE, Color::BLACK].iter() {
+            builder = builder.with_checks_given(*color, game_state.checks_given(*color));
+        }
+        for (index, square) in builder.squares.iter_mut().enumerate() {
+            *square = game_state.get_piece(Position::from_numeric(index as u8));
+        }
+        builder
+    }
+}
+
+impl GameStateBuilder {
+    // an empty standard chess board with white to move and no castling rights
+    pub fn new() -> GameStateBuilder {
+        GameStateBuilder {
+            squares: [None; 64],
+            to_move: Color::WHITE,
+            castling_rights: CastlingRights::none(),
+            en_passant: None,
+            variant: Variant::Standard,
+            checks_given: [0; 2],
+        }
+    }
+
+    // replaces any piece already on the square
+    pub fn with_piece(mut self, piece: Piece, color: Color, position: Position) -> Self {
+        self.squares[position.to_numeric() as usize] = Some((piece, color));
+        self
+    }
+
+    pub fn without_piece(mut self, position: Position) -> Self {
+        self.squares[position.to_numeric() as usize] = None;
+        self
+    }
+
+    pub fn piece(&self, position: Position) -> Option<(Piece, Color)> {
+        self.squares[position.to_numeric() as usize]
+    }
+
+    pub fn with_to_move(mut self, to_move: Color) -> Self {
+        self.to_move = to_move;
+        self
+    }
+
+    pub fn with_castling_rights(mut self, castling_rights: CastlingRights) -> Self {
+        self.castling_rights = castling_rights;
+        self
+    }
+
+    // the pawn that has just moved two squares, like GameState::en_passant
+    pub fn with_en_passant(mut self, en_passant: Option<Position>) -> Self {
+        self.en_passant = en_passant;
+        self
+    }
+
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    // the checks the color has given so far, only counted in Three-check
+    pub fn with_checks_given(mut self, color: Color, checks: u8) -> Self {
+        self.checks_given[color_index(color)] = checks;
+        self
+    }
+
+    pub fn build(self) -> Result<GameState, ChessError> {
+        let mut game_state = GameState::new_empty();
+        for (index, square) in self.squares.iter().enumerate() {
+            if let Some((piece, color)) = square {
+                game_state.set_piece(*piece, *color, Position::from_numeric(index as u8));
+            }
+        }
+        game_state.to_move = self.to_move;
+        game_state.castling_rights = self.castling_rights;
+        game_state.set_en_passant(self.en_passant);
+        game_state.set_variant(self.variant);
+        for color in [Color::WHITE, Color::BLACK].iter() {
+            game_state.set_checks_given(*color, self.checks_given[color_index(*color)]);
+        }
+
+        game_state.validate()?;
+
+        game_state.zobrist_hash = zobrist_hash::hash(&game_state);
+        Ok(game_state)
+    }
+}
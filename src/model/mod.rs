@@ -1,7 +1,15 @@
 pub mod attack_trace;
+pub mod chess960;
+pub mod endgame;
 pub mod evaluator;
 pub mod game_state;
+pub mod game_state_builder;
 pub mod move_generator;
+#[cfg(feature = "nnue")]
+pub mod nnue;
+#[cfg(feature = "syzygy")]
+pub mod syzygy;
+pub mod variant;
 pub mod zobrist_hash;
 
 mod tests;
@@ -6,6 +6,7 @@ use super::game_state::Move;
 use super::game_state::MoveType;
 use super::game_state::Piece;
 use super::game_state::Position;
+use crate::uci::uci_utils::UciMove;
 
 const MASK_FILE1: u64 = 0x0101010101010101;
 const MASK_FILE8: u64 = 0x8080808080808080;
@@ -19,7 +20,7 @@ pub struct GeneratedMoves {
 
 impl GeneratedMoves {
     pub fn is_checkmate(&self) -> bool {
-        self.is_check && self.moves.len() == 0
+        self.is_check && self.moves.is_empty()
     }
 }
 
@@ -30,6 +31,12 @@ pub struct MoveGenerator {
     knight_trace: Vec<Vec<Vec<Position>>>,
 }
 
+impl Default for MoveGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MoveGenerator {
     pub fn new() -> Self {
         MoveGenerator {
@@ -40,29 +47,49 @@ impl MoveGenerator {
         }
     }
 
+    // returns the first legal move between the squares, which is the queen promotion for a pawn
+    // reaching the last rank
     pub fn get_move(&self, board: &GameState, from: Position, to: Position) -> Option<Move> {
-        self.generate_moves(board)
-            .moves
-            .into_iter()
-            .find(|m| m.to == to && m.from == from)
+        let moves = self.generate_moves(board).moves;
+        find_move_between(&moves, from, to, |_| true)
+    }
+
+    pub fn find_move(&self, board: &GameState, uci_move: &UciMove) -> Option<Move> {
+        let moves = self.generate_moves(board).moves;
+        find_move_between(&moves, uci_move.0, uci_move.1, |m| {
+            m.promotes_to == uci_move.2
+        })
     }
 
-    // TODO: use mutable board with apply_move_mut and takeback to avoid copying
+    // there are no moves once a side has won by the rules of the variant
     pub fn generate_moves(&self, board: &GameState) -> GeneratedMoves {
-        let candidate_moves = self.generate_moves_unchecked(board);
         let is_check = self.is_check(board, board.to_move());
+        if board.variant().winner(board).is_some() {
+            return GeneratedMoves {
+                moves: vec![],
+                is_check,
+            };
+        }
+
+        let candidate_moves = self.generate_moves_unchecked(board);
         let to_move = board.to_move();
+        let mut next_board = *board;
 
         GeneratedMoves {
             moves: candidate_moves
                 .into_iter()
-                .filter(|m| !self.is_check(&board.apply_move(*m), to_move))
+                .filter(|m| {
+                    next_board.apply_move_mut(*m);
+                    let is_legal = !self.is_check(&next_board, to_move);
+                    next_board.unapply_move_mut(*m);
+                    is_legal
+                })
                 .collect(),
-            is_check: is_check,
+            is_check,
         }
     }
 
-    fn is_check(&self, board: &GameState, color: Color) -> bool {
+    pub(crate) fn is_check(&self, board: &GameState, color: Color) -> bool {
         (board.get_piece_mask(Piece::KING, color) & self.generate_threats(board, color.opposite()))
             != 0
     }
@@ -134,61 +161,43 @@ impl MoveGenerator {
         to_move: Color,
         target: &mut Vec<Move>,
     ) {
-        let opponent_threats = self.generate_threats(board, to_move.opposite());
-        let king = board.get_piece_position(Piece::KING, to_move)[0];
-
-        if to_move == Color::WHITE && board.castling_rights.white_king_side
-            || to_move == Color::BLACK && board.castling_rights.black_king_side
-        {
-            let positions = [king, king.delta(1, 0).unwrap(), king.delta(2, 0).unwrap()];
-
-            let is_no_threat_for_castling = positions[1..=2]
-                .iter()
-                .all(|pos| (pos.to_bit_mask() & opponent_threats) == 0);
-            let is_room_for_castling = positions[1..=2]
-                .iter()
-                .all(|pos| board.get_piece(*pos).is_none());
-
-            if is_no_threat_for_castling && is_room_for_castling {
-                target.push(Move {
-                    move_type: MoveType::Castling,
-                    moving_piece: Piece::KING,
-                    from: king,
-                    to: positions[2],
-                    promotes_to: None,
-                    last_en_passant: board.en_passant(),
-                    last_castling_rights: board.castling_rights,
-                });
-            }
+        let rights = board.castling_rights;
+        if !rights.is_allowed(to_move, true) && !rights.is_allowed(to_move, false) {
+            return;
         }
+        let king = match board.get_piece_position(Piece::KING, to_move).first() {
+            Some(king) if king.rank() == to_move.back_rank() => *king,
+            _ => return,
+        };
+        let attacked = self.generate_threats(board, to_move.opposite())
+            | pawn_attacks(board, to_move.opposite());
+
+        for king_side in [true, false].iter() {
+            let rook = rights.rook_square(to_move, *king_side);
+            if !rights.is_allowed(to_move, *king_side)
+                || (rook.file() > king.file()) != *king_side
+                || board.get_piece(rook) != Some((Piece::ROOK, to_move))
+            {
+                continue;
+            }
 
-        if to_move == Color::WHITE && board.castling_rights.white_queen_side
-            || to_move == Color::BLACK && board.castling_rights.black_queen_side
-        {
-            let positions = [
-                king,
-                king.delta(-1, 0).unwrap(),
-                king.delta(-2, 0).unwrap(),
-                king.delta(-3, 0).unwrap(),
-            ];
-
-            let is_no_threat_for_castling = positions[1..=2]
-                .iter()
-                .all(|pos| (pos.to_bit_mask() & opponent_threats) == 0);
-            let is_room_for_castling = positions[1..=3]
-                .iter()
-                .all(|pos| board.get_piece(*pos).is_none());
-
-            if is_no_threat_for_castling && is_room_for_castling {
-                target.push(Move {
-                    move_type: MoveType::Castling,
-                    moving_piece: Piece::KING,
-                    from: king,
-                    to: positions[2],
-                    promotes_to: None,
-                    last_en_passant: board.en_passant(),
-                    last_castling_rights: board.castling_rights,
-                });
+            let castling = Move {
+                move_type: MoveType::Castling,
+                moving_piece: Piece::KING,
+                from: king,
+                to: rook,
+                promotes_to: None,
+            };
+            let (king_to, rook_to) = castling.castling_destinations();
+
+            // Apart from the king and the rook, the squares both of them cross must be empty. The
+            // king must not start on, cross or end on an attacked square.
+            let king_path = rank_span(king, king_to);
+            let blocking = board.collide_mask(king_path | rank_span(rook, rook_to))
+                & !(king.to_bit_mask() | rook.to_bit_mask());
+
+            if blocking == 0 && king_path & attacked == 0 {
+                target.push(castling);
             }
         }
     }
@@ -211,7 +220,10 @@ impl MoveGenerator {
     }
 
     pub fn generate_king_moves(&self, board: &GameState, to_move: Color, target: &mut Vec<Move>) {
-        let king = board.get_piece_position(Piece::KING, to_move)[0];
+        let king = match board.get_piece_position(Piece::KING, to_move).first() {
+            Some(king) => *king,
+            None => return,
+        };
         let target_squares = vec![
             king.delta(0, 1),
             king.delta(0, -1),
@@ -223,7 +235,7 @@ impl MoveGenerator {
             king.delta(1, -1),
         ]
         .into_iter()
-        .filter_map(|x| x);
+        .flatten();
 
         for square in target_squares {
             match board.collide(square) {
@@ -233,8 +245,6 @@ impl MoveGenerator {
                     from: king,
                     to: square,
                     promotes_to: None,
-                    last_en_passant: board.en_passant(),
-                    last_castling_rights: board.castling_rights,
                 }),
                 Some(color) if color == to_move.opposite() => {
                     target.push(Move {
@@ -243,8 +253,6 @@ impl MoveGenerator {
                         from: king,
                         to: square,
                         promotes_to: None,
-                        last_en_passant: board.en_passant(),
-                        last_castling_rights: board.castling_rights,
                     });
                 }
                 Some(color) if color == to_move => continue,
@@ -259,7 +267,7 @@ impl MoveGenerator {
         to_move: Color,
         piece_positions: Vec<Position>,
         piece: Piece,
-        trace: &Vec<Vec<Vec<Position>>>,
+        trace: &[Vec<Vec<Position>>],
         target: &mut Vec<Move>,
     ) {
         for piece_position in piece_positions {
@@ -293,8 +301,6 @@ impl MoveGenerator {
                     from: position,
                     to: *square,
                     promotes_to: None,
-                    last_en_passant: board.en_passant(),
-                    last_castling_rights: board.castling_rights,
                 }),
                 Some(color) if color == opposite_color => {
                     target.push(Move {
@@ -303,8 +309,6 @@ impl MoveGenerator {
                         from: position,
                         to: *square,
                         promotes_to: None,
-                        last_en_passant: board.en_passant(),
-                        last_castling_rights: board.castling_rights,
                     });
                     break;
                 }
@@ -320,7 +324,7 @@ impl MoveGenerator {
         to_move: Color,
         position: Position,
         piece: Piece,
-        trace: &Vec<Vec<Vec<Position>>>,
+        trace: &[Vec<Vec<Position>>],
         target: &mut Vec<Move>,
     ) {
         for ray in &trace[usize::from(position.to_numeric())] {
@@ -356,10 +360,10 @@ impl MoveGenerator {
             let rank = position.rank();
             let is_promotes_on_move =
                 (rank == 8 && color == Color::WHITE) || (rank == 1 && color == Color::BLACK);
-            let move_from = position.delta(0, -1 * direction_multiplier).unwrap();
+            let move_from = position.delta(0, -direction_multiplier).unwrap();
 
             if is_promotes_on_move {
-                self.get_pawn_promotions(board, move_from, position, None, target)
+                self.get_pawn_promotions(move_from, position, None, target)
             } else {
                 target.push(Move {
                     move_type: MoveType::Step,
@@ -367,8 +371,6 @@ impl MoveGenerator {
                     from: move_from,
                     to: position,
                     promotes_to: None,
-                    last_en_passant: board.en_passant(),
-                    last_castling_rights: board.castling_rights,
                 })
             }
         }
@@ -380,25 +382,13 @@ impl MoveGenerator {
                 from: position.delta(0, -2 * direction_multiplier).unwrap(),
                 to: position,
                 promotes_to: None,
-                last_en_passant: board.en_passant(),
-                last_castling_rights: board.castling_rights,
             });
         }
     }
 
     // does not include en passant captures
     fn generate_pawn_captures(&self, board: &GameState, color: Color, target: &mut Vec<Move>) {
-        let current_pawns = board.get_piece_mask(Piece::PAWN, color);
-
-        // calculate squares where pawns may attack
-        // shift pawn mask by 7 and 9 to get "forward facing diagonals" except on files 1 and 8 (where it would wrap)
-        let attack_mask = if color == Color::WHITE {
-            ((current_pawns & !MASK_FILE1) << 7) | ((current_pawns & !MASK_FILE8) << 9)
-        } else {
-            ((current_pawns & !MASK_FILE1) >> 9) | ((current_pawns & !MASK_FILE8) >> 7)
-        };
-
-        let valid_captures = board.collide_mask_color(attack_mask, color.opposite());
+        let valid_captures = board.collide_mask_color(pawn_attacks(board, color), color.opposite());
 
         let direction_multiplier = if color == Color::WHITE { 1 } else { -1 };
 
@@ -419,7 +409,6 @@ impl MoveGenerator {
 
                 if is_promotes_on_move {
                     self.get_pawn_promotions(
-                        board,
                         left_candidate.unwrap(),
                         square,
                         Some(captured_piece),
@@ -432,8 +421,6 @@ impl MoveGenerator {
                         from: left_candidate.unwrap(),
                         to: square,
                         promotes_to: None,
-                        last_en_passant: board.en_passant(),
-                        last_castling_rights: board.castling_rights,
                     });
                 }
             }
@@ -448,7 +435,6 @@ impl MoveGenerator {
 
                 if is_promotes_on_move {
                     self.get_pawn_promotions(
-                        board,
                         right_candidate.unwrap(),
                         square,
                         Some(captured_piece),
@@ -461,8 +447,6 @@ impl MoveGenerator {
                         from: right_candidate.unwrap(),
                         to: square,
                         promotes_to: None,
-                        last_en_passant: board.en_passant(),
-                        last_castling_rights: board.castling_rights,
                     });
                 }
             }
@@ -471,7 +455,6 @@ impl MoveGenerator {
 
     fn get_pawn_promotions(
         &self,
-        board: &GameState,
         from: Position,
         to: Position,
         capture: Option<Piece>,
@@ -481,15 +464,11 @@ impl MoveGenerator {
 
         for piece in pieces.iter() {
             target.push(Move {
-                move_type: capture
-                    .map(|p| MoveType::Capture(p))
-                    .unwrap_or(MoveType::Step),
+                move_type: capture.map(MoveType::Capture).unwrap_or(MoveType::Step),
                 moving_piece: Piece::PAWN,
-                from: from,
-                to: to,
+                from,
+                to,
                 promotes_to: Some(*piece),
-                last_en_passant: board.en_passant(),
-                last_castling_rights: board.castling_rights,
             });
         }
     }
@@ -515,8 +494,6 @@ impl MoveGenerator {
                     from: en_passant_square.delta(-1, 0).unwrap(),
                     to: en_passant_square.delta(0, direction_multiplier).unwrap(),
                     promotes_to: None,
-                    last_en_passant: board.en_passant(),
-                    last_castling_rights: board.castling_rights,
                 };
                 target.push(en_passant);
             }
@@ -528,11 +505,51 @@ impl MoveGenerator {
                     from: en_passant_square.delta(1, 0).unwrap(),
                     to: en_passant_square.delta(0, direction_multiplier).unwrap(),
                     promotes_to: None,
-                    last_en_passant: board.en_passant(),
-                    last_castling_rights: board.castling_rights,
                 };
                 target.push(en_passant);
             }
         }
     }
 }
+
+// calculate squares where pawns may attack
+// shift pawn mask by 7 and 9 to get "forward facing diagonals" except on files 1 and 8 (where it would wrap)
+fn pawn_attacks(board: &GameState, color: Color) -> u64 {
+    let pawns = board.get_piece_mask(Piece::PAWN, color);
+
+    if color == Color::WHITE {
+        ((pawns & !MASK_FILE1) << 7) | ((pawns & !MASK_FILE8) << 9)
+    } else {
+        ((pawns & !MASK_FILE1) >> 9) | ((pawns & !MASK_FILE8) >> 7)
+    }
+}
+
+// the squares from one to the other on the same rank, both included
+fn rank_span(a: Position, b: Position) -> u64 {
+    let (low, high) = if a.to_numeric() < b.to_numeric() {
+        (a.to_numeric(), b.to_numeric())
+    } else {
+        (b.to_numeric(), a.to_numeric())
+    };
+
+    (low..=high).fold(0, |mask, square| mask | 1 << square)
+}
+
+// Castling is found by the square of its rook as well as by the destination of the king, unless
+// the king can step there.
+fn find_move_between<F: Fn(&Move) -> bool>(
+    moves: &[Move],
+    from: Position,
+    to: Position,
+    matches: F,
+) -> Option<Move> {
+    moves
+        .iter()
+        .find(|m| m.from == from && m.to == to && matches(m))
+        .or_else(|| {
+            moves.iter().find(|m| {
+                m.move_type == MoveType::Castling && m.from == from && m.destination() == to
+            })
+        })
+        .copied()
+}
@@ -0,0 +1,444 @@
+use std::convert::TryInto;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use super::evaluator::PositionEvaluator;
+use super::game_state::{bit_mask_to_positions, Color, GameState, Move, MoveType, Piece, Position};
+
+// network file layout (all values little endian):
+//   magic "RCNN", version: u32, hidden size: u32, output scale: i32,
+//   feature weights: [i16; FEATURE_COUNT * hidden size] (feature major),
+//   feature biases: [i16; hidden size],
+//   output weights: [i8; 2 * hidden size] (side to move half first),
+//   output bias: i32
+const MAGIC: &[u8; 4] = b"RCNN";
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 16;
+
+// one input per (own/opponent, piece, square) from the point of view of each side
+pub const FEATURE_COUNT: usize = 2 * 6 * 64;
+
+// accumulator values are clipped into 0..=ACTIVATION_MAX before the output layer
+const ACTIVATION_MAX: i16 = 127;
+const OUTPUT_DIVISOR: i64 = ACTIVATION_MAX as i64 * 64;
+
+const MAX_HIDDEN_SIZE: usize = 4096;
+const MAX_OUTPUT_SCALE: i32 = 1 << 20;
+
+// evaluations of the network are clipped far below the mate scores of the search
+const MAX_OUTPUT: i64 = 100_000_000;
+
+pub struct Network {
+    hidden_size: usize,
+    output_scale: i32,
+    feature_weights: Vec<i16>,
+    feature_biases: Vec<i16>,
+    output_weights: Vec<i8>,
+    output_bias: i32,
+}
+
+impl Network {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Network, String> {
+        let bytes = fs::read(path.as_ref()).map_err(|e| {
+            format!(
+                "Cannot read network file {}: {}",
+                path.as_ref().display(),
+                e
+            )
+        })?;
+        Network::from_bytes(&bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Network, String> {
+        if bytes.len() < HEADER_SIZE {
+            return Err("Network file is too short".to_string());
+        }
+        if &bytes[0..4] != MAGIC {
+            return Err("Not a network file".to_string());
+        }
+
+        let version = read_u32(bytes, 4);
+        if version != VERSION {
+            return Err(format!("Unsupported network version: {}", version));
+        }
+
+        let hidden_size = read_u32(bytes, 8) as usize;
+        if hidden_size == 0 || hidden_size > MAX_HIDDEN_SIZE {
+            return Err(format!("Invalid hidden layer size: {}", hidden_size));
+        }
+        let output_scale = read_u32(bytes, 12) as i32;
+        if output_scale <= 0 || output_scale > MAX_OUTPUT_SCALE {
+            return Err(format!("Invalid output scale: {}", output_scale));
+        }
+
+        let expected_len =
+            HEADER_SIZE + 2 * FEATURE_COUNT * hidden_size + 2 * hidden_size + 2 * hidden_size + 4;
+        if bytes.len() != expected_len {
+            return Err(format!(
+                "Network file has size {}, expected {}",
+                bytes.len(),
+                expected_len
+            ));
+        }
+
+        let mut offset = HEADER_SIZE;
+        let feature_weights = read_i16_vec(bytes, &mut offset, FEATURE_COUNT * hidden_size);
+        let feature_biases = read_i16_vec(bytes, &mut offset, hidden_size);
+        let output_weights = bytes[offset..offset + 2 * hidden_size]
+            .iter()
+            .map(|b| *b as i8)
+            .collect();
+        offset += 2 * hidden_size;
+        let output_bias = read_u32(bytes, offset) as i32;
+
+        Ok(Network {
+            hidden_size,
+            output_scale,
+            feature_weights,
+            feature_biases,
+            output_weights,
+            output_bias,
+        })
+    }
+
+    pub fn hidden_size(&self) -> usize {
+        self.hidden_size
+    }
+
+    fn feature_weights(&self, feature: usize) -> &[i16] {
+        &self.feature_weights[feature * self.hidden_size..(feature + 1) * self.hidden_size]
+    }
+
+    fn output(&self, own: &[i16], opponent: &[i16]) -> i32 {
+        let (own_weights, opponent_weights) = self.output_weights.split_at(self.hidden_size);
+        let sum =
+            simd::clipped_dot(own, own_weights) + simd::clipped_dot(opponent, opponent_weights);
+
+        // a full hidden layer plus the bias can exceed i32 once scaled
+        let output = (i64::from(sum) + i64::from(self.output_bias)) * i64::from(self.output_scale)
+            / OUTPUT_DIVISOR;
+        output.clamp(-MAX_OUTPUT, MAX_OUTPUT) as i32
+    }
+}
+
+#[derive(Clone)]
+struct Accumulator {
+    white: Vec<i16>,
+    black: Vec<i16>,
+}
+
+impl Accumulator {
+    fn new(network: &Network) -> Self {
+        Accumulator {
+            white: network.feature_biases.clone(),
+            black: network.feature_biases.clone(),
+        }
+    }
+
+    fn perspective_mut(&mut self, perspective: Color) -> &mut Vec<i16> {
+        match perspective {
+            Color::WHITE => &mut self.white,
+            Color::BLACK => &mut self.black,
+        }
+    }
+
+    fn add(&mut self, network: &Network, piece: Piece, color: Color, position: Position) {
+        for perspective in [Color::WHITE, Color::BLACK].iter() {
+            let weights =
+                network.feature_weights(feature_index(*perspective, piece, color, position));
+            simd::add_assign(self.perspective_mut(*perspective), weights);
+        }
+    }
+
+    fn remove(&mut self, network: &Network, piece: Piece, color: Color, position: Position) {
+        for perspective in [Color::WHITE, Color::BLACK].iter() {
+            let weights =
+                network.feature_weights(feature_index(*perspective, piece, color, position));
+            simd::sub_assign(self.perspective_mut(*perspective), weights);
+        }
+    }
+}
+
+// Evaluates positions with a network, keeping one accumulator per ply so that moves only
+// touch the features of the pieces they move instead of refreshing the whole input layer.
+#[derive(Clone)]
+pub struct NnueEvaluator {
+    network: Arc<Network>,
+    accumulators: Vec<Accumulator>,
+    ply: usize,
+}
+
+impl NnueEvaluator {
+    pub fn new(network: Arc<Network>, game_state: &GameState) -> Self {
+        let mut evaluator = NnueEvaluator {
+            accumulators: vec![Accumulator::new(&network)],
+            network,
+            ply: 0,
+        };
+        evaluator.refresh(game_state);
+        evaluator
+    }
+
+    pub fn refresh(&mut self, game_state: &GameState) {
+        let mut accumulator = Accumulator::new(&self.network);
+
+        for color in [Color::WHITE, Color::BLACK].iter() {
+            for piece in PIECES.iter() {
+                for position in bit_mask_to_positions(*game_state.get_piece_mask(*piece, *color)) {
+                    accumulator.add(&self.network, *piece, *color, position);
+                }
+            }
+        }
+
+        self.accumulators.truncate(1);
+        self.accumulators[0] = accumulator;
+        self.ply = 0;
+    }
+
+    // must be called with the side that makes the move, before the move is applied to the board
+    pub fn apply_move_mut(&mut self, to_move: Color, to_apply: Move) {
+        if self.accumulators.len() == self.ply + 1 {
+            let copy = self.accumulators[self.ply].clone();
+            self.accumulators.push(copy);
+        } else {
+            let (done, next) = self.accumulators.split_at_mut(self.ply + 1);
+            next[0].white.copy_from_slice(&done[self.ply].white);
+            next[0].black.copy_from_slice(&done[self.ply].black);
+        }
+        self.ply += 1;
+
+        let network = &self.network;
+        let accumulator = &mut self.accumulators[self.ply];
+        let opponent = to_move.opposite();
+
+        match to_apply.move_type {
+            MoveType::Capture(captured_piece) => {
+                accumulator.remove(network, captured_piece, opponent, to_apply.to);
+            }
+            MoveType::EnPassant => {
+                let direction_multiplier = if to_move == Color::WHITE { 1 } else { -1 };
+                let captured_square = to_apply.to.delta(0, -direction_multiplier).unwrap();
+                accumulator.remove(network, Piece::PAWN, opponent, captured_square);
+            }
+            MoveType::Castling => {
+                let new_rook_position = to_apply.castling_destinations().1;
+                accumulator.remove(network, Piece::ROOK, to_move, to_apply.to);
+                accumulator.add(network, Piece::ROOK, to_move, new_rook_position);
+            }
+            MoveType::Step => (),
+        }
+
+        accumulator.remove(network, to_apply.moving_piece, to_move, to_apply.from);
+        accumulator.add(
+            network,
+            to_apply.promotes_to.unwrap_or(to_apply.moving_piece),
+            to_move,
+            to_apply.destination(),
+        );
+    }
+
+    pub fn unapply_move_mut(&mut self) {
+        if self.ply > 0 {
+            self.ply -= 1;
+        }
+    }
+
+    // evaluation from the point of view of the side to move
+    pub fn evaluate(&self, to_move: Color) -> i32 {
+        let accumulator = &self.accumulators[self.ply];
+
+        match to_move {
+            Color::WHITE => self.network.output(&accumulator.white, &accumulator.black),
+            Color::BLACK => self.network.output(&accumulator.black, &accumulator.white),
+        }
+    }
+}
+
+impl PositionEvaluator for NnueEvaluator {
+    fn evaluate(&self, game_state: &GameState) -> i32 {
+        let eval = NnueEvaluator::evaluate(self, game_state.to_move());
+
+        // the network is trained on standard chess and knows nothing of the variant's goals
+        let variant_eval = game_state.variant().evaluate(game_state);
+        if game_state.to_move() == Color::WHITE {
+            eval + variant_eval
+        } else {
+            -eval + variant_eval
+        }
+    }
+
+    fn refresh(&mut self, game_state: &GameState) {
+        NnueEvaluator::refresh(self, game_state);
+    }
+
+    fn apply_move(&mut self, game_state: &GameState, to_apply: Move) {
+        self.apply_move_mut(game_state.to_move(), to_apply);
+    }
+
+    fn unapply_move(&mut self, _game_state: &GameState, _to_unapply: Move) {
+        self.unapply_move_mut();
+    }
+}
+
+const PIECES: [Piece; 6] = [
+    Piece::PAWN,
+    Piece::KNIGHT,
+    Piece::BISHOP,
+    Piece::ROOK,
+    Piece::QUEEN,
+    Piece::KING,
+];
+
+fn feature_index(perspective: Color, piece: Piece, color: Color, position: Position) -> usize {
+    let square = if perspective == Color::WHITE {
+        position
+    } else {
+        position.mirror_rank()
+    };
+    let side = if color == perspective { 0 } else { 1 };
+    let piece_index = match piece {
+        Piece::PAWN => 0,
+        Piece::KNIGHT => 1,
+        Piece::BISHOP => 2,
+        Piece::ROOK => 3,
+        Piece::QUEEN => 4,
+        Piece::KING => 5,
+    };
+
+    (side * 6 + piece_index) * 64 + usize::from(square.to_numeric())
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_i16_vec(bytes: &[u8], offset: &mut usize, count: usize) -> Vec<i16> {
+    let values = bytes[*offset..*offset + 2 * count]
+        .chunks_exact(2)
+        .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]))
+        .collect();
+    *offset += 2 * count;
+    values
+}
+
+mod simd {
+    use super::ACTIVATION_MAX;
+
+    pub fn add_assign(target: &mut [i16], values: &[i16]) {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                return unsafe { avx2::add_assign(target, values) };
+            }
+        }
+        scalar::add_assign(target, values)
+    }
+
+    pub fn sub_assign(target: &mut [i16], values: &[i16]) {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                return unsafe { avx2::sub_assign(target, values) };
+            }
+        }
+        scalar::sub_assign(target, values)
+    }
+
+    pub fn clipped_dot(activations: &[i16], weights: &[i8]) -> i32 {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                return unsafe { avx2::clipped_dot(activations, weights) };
+            }
+        }
+        scalar::clipped_dot(activations, weights)
+    }
+
+    mod scalar {
+        use super::ACTIVATION_MAX;
+
+        pub fn add_assign(target: &mut [i16], values: &[i16]) {
+            for (t, v) in target.iter_mut().zip(values.iter()) {
+                *t = t.wrapping_add(*v);
+            }
+        }
+
+        pub fn sub_assign(target: &mut [i16], values: &[i16]) {
+            for (t, v) in target.iter_mut().zip(values.iter()) {
+                *t = t.wrapping_sub(*v);
+            }
+        }
+
+        pub fn clipped_dot(activations: &[i16], weights: &[i8]) -> i32 {
+            activations
+                .iter()
+                .zip(weights.iter())
+                .map(|(a, w)| i32::from((*a).clamp(0, ACTIVATION_MAX)) * i32::from(*w))
+                .sum()
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    mod avx2 {
+        use super::{scalar, ACTIVATION_MAX};
+        use std::arch::x86_64::*;
+
+        const LANES: usize = 16;
+
+        #[target_feature(enable = "avx2")]
+        pub unsafe fn add_assign(target: &mut [i16], values: &[i16]) {
+            let chunks = target.len().min(values.len()) / LANES;
+
+            for i in 0..chunks {
+                let t = target.as_mut_ptr().add(i * LANES) as *mut __m256i;
+                let v = values.as_ptr().add(i * LANES) as *const __m256i;
+                _mm256_storeu_si256(
+                    t,
+                    _mm256_add_epi16(_mm256_loadu_si256(t), _mm256_loadu_si256(v)),
+                );
+            }
+
+            scalar::add_assign(&mut target[chunks * LANES..], &values[chunks * LANES..]);
+        }
+
+        #[target_feature(enable = "avx2")]
+        pub unsafe fn sub_assign(target: &mut [i16], values: &[i16]) {
+            let chunks = target.len().min(values.len()) / LANES;
+
+            for i in 0..chunks {
+                let t = target.as_mut_ptr().add(i * LANES) as *mut __m256i;
+                let v = values.as_ptr().add(i * LANES) as *const __m256i;
+                _mm256_storeu_si256(
+                    t,
+                    _mm256_sub_epi16(_mm256_loadu_si256(t), _mm256_loadu_si256(v)),
+                );
+            }
+
+            scalar::sub_assign(&mut target[chunks * LANES..], &values[chunks * LANES..]);
+        }
+
+        #[target_feature(enable = "avx2")]
+        pub unsafe fn clipped_dot(activations: &[i16], weights: &[i8]) -> i32 {
+            let chunks = activations.len().min(weights.len()) / LANES;
+            let zero = _mm256_setzero_si256();
+            let max = _mm256_set1_epi16(ACTIVATION_MAX);
+            let mut sum = _mm256_setzero_si256();
+
+            for i in 0..chunks {
+                let a = _mm256_loadu_si256(activations.as_ptr().add(i * LANES) as *const __m256i);
+                let clipped = _mm256_min_epi16(_mm256_max_epi16(a, zero), max);
+                let w = _mm256_cvtepi8_epi16(_mm_loadu_si128(
+                    weights.as_ptr().add(i * LANES) as *const __m128i
+                ));
+                sum = _mm256_add_epi32(sum, _mm256_madd_epi16(clipped, w));
+            }
+
+            let mut lanes = [0i32; 8];
+            _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, sum);
+
+            lanes.iter().sum::<i32>()
+                + scalar::clipped_dot(&activations[chunks * LANES..], &weights[chunks * LANES..])
+        }
+    }
+}
@@ -0,0 +1,1305 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use super::game_state::{Color, GameState, Move, MoveType, Piece, Position};
+use super::move_generator::MoveGenerator;
+use super::variant::Variant;
+
+// Probing of the Syzygy WDL (win/draw/loss) and DTZ (distance to zeroing move) tablebases, in
+// the format of the tables as they are distributed. The layout of the files follows the probing
+// code of Stockfish and Fathom.
+const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+const WDL_EXTENSION: &str = "rtbw";
+const DTZ_EXTENSION: &str = "rtbz";
+
+const MAX_PIECES: usize = 7;
+
+// pieces in the order of their codes in the tables, white codes are 1..=6 and black 9..=14
+const PIECES: [Piece; 6] = [
+    Piece::PAWN,
+    Piece::KNIGHT,
+    Piece::BISHOP,
+    Piece::ROOK,
+    Piece::QUEEN,
+    Piece::KING,
+];
+const PIECE_LETTERS: [char; 6] = ['P', 'N', 'B', 'R', 'Q', 'K'];
+const BLACK_PIECE: u8 = 8;
+
+// flags of the table files
+const SPLIT: u8 = 1;
+const HAS_PAWNS: u8 = 2;
+
+// flags of each compressed table
+const STM: u8 = 1;
+const MAPPED: u8 = 2;
+const WIN_PLIES: u8 = 4;
+const LOSS_PLIES: u8 = 8;
+const WIDE: u8 = 16;
+const SINGLE_VALUE: u8 = 128;
+
+// ranks positions by their DTZ at the root, above any distance to zeroing
+const MAX_DTZ: i32 = 1 << 16;
+
+// Results of the tables. Cursed wins and blessed losses are decided by the fifty move rule, and
+// are played as draws by the search.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Debug)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+impl Wdl {
+    fn from_value(value: i32) -> Wdl {
+        match value {
+            -2 => Wdl::Loss,
+            -1 => Wdl::BlessedLoss,
+            0 => Wdl::Draw,
+            1 => Wdl::CursedWin,
+            _ => Wdl::Win,
+        }
+    }
+
+    fn value(self) -> i32 {
+        match self {
+            Wdl::Loss => -2,
+            Wdl::BlessedLoss => -1,
+            Wdl::Draw => 0,
+            Wdl::CursedWin => 1,
+            Wdl::Win => 2,
+        }
+    }
+
+    fn opposite(self) -> Wdl {
+        Wdl::from_value(-self.value())
+    }
+
+    fn signum(self) -> i32 {
+        self.value().signum()
+    }
+}
+
+// Lookup tables of the position indexing, the names are those of Stockfish. Squares are numbered
+// from a1 = 0 to h8 = 63.
+struct Encoding {
+    // ways to choose k elements out of n
+    binomial: [[u64; 64]; 6],
+    // squares a2-h7 to 0..47, the pawn with the highest value leads
+    map_pawns: [usize; 64],
+    lead_pawn_idx: [[u64; 64]; 6],
+    lead_pawns_size: [[u64; 4]; 6],
+    // squares below the a1-h8 diagonal to 0..27
+    map_b1h1h7: [u64; 64],
+    // squares of the a1-d1-d4 triangle to 0..9, the diagonal last
+    map_a1d1d4: [usize; 64],
+    // the 462 placements of two kings with the first in the a1-d1-d4 triangle
+    map_kk: [[u64; 64]; 10],
+}
+
+lazy_static! {
+    static ref ENCODING: Encoding = Encoding::new();
+}
+
+impl Encoding {
+    fn new() -> Encoding {
+        let mut encoding = Encoding {
+            binomial: [[0; 64]; 6],
+            map_pawns: [0; 64],
+            lead_pawn_idx: [[0; 64]; 6],
+            lead_pawns_size: [[0; 4]; 6],
+            map_b1h1h7: [0; 64],
+            map_a1d1d4: [0; 64],
+            map_kk: [[0; 64]; 10],
+        };
+
+        let mut code = 0;
+        for square in 0..64 {
+            if off_diagonal(square) < 0 {
+                encoding.map_b1h1h7[square] = code;
+                code += 1;
+            }
+        }
+
+        let mut code = 0;
+        let mut diagonal = Vec::new();
+        for square in 0..=27 {
+            if off_diagonal(square) < 0 && file_of(square) <= 3 {
+                encoding.map_a1d1d4[square] = code;
+                code += 1;
+            } else if off_diagonal(square) == 0 && file_of(square) <= 3 {
+                diagonal.push(square);
+            }
+        }
+        for square in diagonal {
+            encoding.map_a1d1d4[square] = code;
+            code += 1;
+        }
+
+        // with the first king on the diagonal, the second is not above it
+        let mut code = 0;
+        let mut both_on_diagonal = Vec::new();
+        for idx in 0..10 {
+            for first in 0..=27 {
+                // squares outside of the triangle map to 0 as well, b1 is the one of the triangle
+                if encoding.map_a1d1d4[first] != idx || (idx == 0 && first != 1) {
+                    continue;
+                }
+                for second in 0..64 {
+                    if distance(first, second) <= 1
+                        || (off_diagonal(first) == 0 && off_diagonal(second) > 0)
+                    {
+                        continue;
+                    }
+                    if off_diagonal(first) == 0 && off_diagonal(second) == 0 {
+                        both_on_diagonal.push((idx, second));
+                    } else {
+                        encoding.map_kk[idx][second] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (idx, second) in both_on_diagonal {
+            encoding.map_kk[idx][second] = code;
+            code += 1;
+        }
+
+        encoding.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..usize::min(6, n + 1) {
+                encoding.binomial[k][n] = if k > 0 {
+                    encoding.binomial[k - 1][n - 1]
+                } else {
+                    0
+                } + if k < n {
+                    encoding.binomial[k][n - 1]
+                } else {
+                    0
+                };
+            }
+        }
+
+        // the leading pawn is the one closest to the a or h file, and the lowest on that file
+        let mut available_squares = 48;
+        for lead_pawn_count in 1..=5 {
+            for file in 0..4 {
+                let mut idx = 0;
+                for rank in 1..=6 {
+                    let square = file + 8 * rank;
+                    if lead_pawn_count == 1 {
+                        encoding.map_pawns[square] = available_squares - 1;
+                        encoding.map_pawns[flip_file(square)] = available_squares - 2;
+                        available_squares -= 2;
+                    }
+                    encoding.lead_pawn_idx[lead_pawn_count][square] = idx;
+                    idx += encoding.binomial[lead_pawn_count - 1][encoding.map_pawns[square]];
+                }
+                encoding.lead_pawns_size[lead_pawn_count][file] = idx;
+            }
+        }
+
+        encoding
+    }
+}
+
+fn file_of(square: usize) -> usize {
+    square % 8
+}
+
+fn rank_of(square: usize) -> usize {
+    square / 8
+}
+
+fn flip_file(square: usize) -> usize {
+    square ^ 7
+}
+
+fn flip_rank(square: usize) -> usize {
+    square ^ 56
+}
+
+// negative below the a1-h8 diagonal
+fn off_diagonal(square: usize) -> i32 {
+    rank_of(square) as i32 - file_of(square) as i32
+}
+
+fn distance(first: usize, second: usize) -> usize {
+    usize::max(
+        (file_of(first) as i32 - file_of(second) as i32).unsigned_abs() as usize,
+        (rank_of(first) as i32 - rank_of(second) as i32).unsigned_abs() as usize,
+    )
+}
+
+fn squares_of_mask(mut bit_mask: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if bit_mask == 0 {
+            return None;
+        }
+        let square = bit_mask.trailing_zeros() as usize;
+        bit_mask &= bit_mask - 1;
+        Some(square)
+    })
+}
+
+fn piece_code(piece: Piece, color: Color) -> u8 {
+    let code = PIECES.iter().position(|p| *p == piece).unwrap() as u8 + 1;
+    match color {
+        Color::WHITE => code,
+        Color::BLACK => code | BLACK_PIECE,
+    }
+}
+
+// pieces of each color, by the order of PIECES
+type Material = [[u8; 6]; 2];
+
+fn material(game_state: &GameState) -> Material {
+    let mut material = [[0; 6]; 2];
+    for (side, color) in [Color::WHITE, Color::BLACK].iter().enumerate() {
+        for (index, piece) in PIECES.iter().enumerate() {
+            material[side][index] = game_state.get_piece_mask(*piece, *color).count_ones() as u8;
+        }
+    }
+    material
+}
+
+// e.g. KRPvKR, the pieces of each side ordered from the king down to the pawns
+fn table_name(first: &[u8; 6], second: &[u8; 6]) -> String {
+    let side = |counts: &[u8; 6]| -> String {
+        (0..6)
+            .rev()
+            .flat_map(|index| {
+                std::iter::repeat(PIECE_LETTERS[index]).take(usize::from(counts[index]))
+            })
+            .collect()
+    };
+    format!("{}v{}", side(first), side(second))
+}
+
+fn parse_table_name(name: &str) -> Option<Material> {
+    let mut material = [[0; 6]; 2];
+    let mut sides = name.split('v');
+
+    for counts in material.iter_mut() {
+        for letter in sides.next()?.chars() {
+            counts[PIECE_LETTERS.iter().position(|l| *l == letter)?] += 1;
+        }
+        if counts[5] != 1 {
+            return None;
+        }
+    }
+
+    let pieces: u8 = material.iter().flatten().sum();
+    if sides.next().is_some() || usize::from(pieces) > MAX_PIECES {
+        return None;
+    }
+    Some(material)
+}
+
+// A table read when first probed, holding None when its file cannot be read.
+type LoadedTable = RwLock<Option<Option<Arc<Table>>>>;
+
+// The tables of one material balance, stored with the stronger side as white. They are read
+// when first probed.
+struct TableEntry {
+    wdl_path: Option<PathBuf>,
+    dtz_path: Option<PathBuf>,
+    wdl: LoadedTable,
+    dtz: LoadedTable,
+    piece_count: usize,
+    has_pawns: bool,
+    has_unique_pieces: bool,
+    // pawns of the leading color first, the side with fewer pawns leads when both have some
+    pawn_count: [u8; 2],
+    // both sides have the same pieces, only white to move is stored
+    symmetric: bool,
+}
+
+impl TableEntry {
+    fn new(material: &Material) -> TableEntry {
+        let [white, black] = material;
+        let white_leads = black[0] == 0 || (white[0] != 0 && black[0] >= white[0]);
+
+        TableEntry {
+            wdl_path: None,
+            dtz_path: None,
+            wdl: RwLock::new(None),
+            dtz: RwLock::new(None),
+            piece_count: material
+                .iter()
+                .flatten()
+                .map(|count| usize::from(*count))
+                .sum(),
+            has_pawns: white[0] + black[0] != 0,
+            has_unique_pieces: material.iter().any(|counts| counts[..5].contains(&1)),
+            pawn_count: if white_leads {
+                [white[0], black[0]]
+            } else {
+                [black[0], white[0]]
+            },
+            symmetric: white == black,
+        }
+    }
+
+    fn wdl_table(&self) -> Option<Arc<Table>> {
+        self.load(&self.wdl, self.wdl_path.as_deref(), &WDL_MAGIC, false)
+    }
+
+    fn dtz_table(&self) -> Option<Arc<Table>> {
+        self.load(&self.dtz, self.dtz_path.as_deref(), &DTZ_MAGIC, true)
+    }
+
+    fn load(
+        &self,
+        table: &LoadedTable,
+        path: Option<&Path>,
+        magic: &[u8; 4],
+        dtz: bool,
+    ) -> Option<Arc<Table>> {
+        if let Some(table) = table.read().unwrap().as_ref() {
+            return table.clone();
+        }
+        table
+            .write()
+            .unwrap()
+            .get_or_insert_with(|| Table::read(path?, magic, self, dtz).ok().map(Arc::new))
+            .clone()
+    }
+}
+
+// A compressed table, positions are mapped to an index into a sequence of values compressed by
+// recursive pairing and canonical Huffman codes.
+#[derive(Clone, Default)]
+struct PairsData {
+    flags: u8,
+    pieces: [u8; MAX_PIECES],
+    // pieces encoded together, zero terminated
+    group_len: [usize; MAX_PIECES + 1],
+    group_idx: [u64; MAX_PIECES + 1],
+    block_size: usize,
+    span: u64,
+    sparse_index: usize,
+    sparse_index_size: usize,
+    block_lengths: usize,
+    block_lengths_size: usize,
+    blocks: usize,
+    block_count: usize,
+    // the value of all positions with SINGLE_VALUE
+    min_sym_len: u8,
+    lowest_sym: usize,
+    base64: Vec<u64>,
+    // number of values a symbol expands to, minus one
+    symlen: Vec<u8>,
+    btree: usize,
+    // offsets of the DTZ value maps of each result
+    map_idx: [u16; 4],
+}
+
+// offsets into the table file are kept instead of references, the file stays in memory
+struct Table {
+    data: Vec<u8>,
+    // by side to move and by the file of the leading pawn
+    pairs: Vec<Vec<PairsData>>,
+    map: usize,
+}
+
+impl Table {
+    fn read(path: &Path, magic: &[u8; 4], entry: &TableEntry, dtz: bool) -> Result<Table, String> {
+        let data = fs::read(path)
+            .map_err(|e| format!("Cannot read tablebase file {}: {}", path.display(), e))?;
+        let corrupted = || format!("Corrupted tablebase file {}", path.display());
+
+        if data.len() % 64 != 16 || !data.starts_with(magic) {
+            return Err(corrupted());
+        }
+        let flags = data[4];
+        if (flags & HAS_PAWNS != 0) != entry.has_pawns
+            || (!dtz && (flags & SPLIT != 0) == entry.symmetric)
+        {
+            return Err(corrupted());
+        }
+
+        let sides = if dtz || entry.symmetric { 1 } else { 2 };
+        let files = if entry.has_pawns { 4 } else { 1 };
+        let pawns_on_both_sides = entry.has_pawns && entry.pawn_count[1] != 0;
+        let mut pairs = vec![vec![PairsData::default(); files]; sides];
+        let mut offset = 5;
+
+        for file in 0..files {
+            let first = read_u8(&data, offset)?;
+            let second = if pawns_on_both_sides {
+                read_u8(&data, offset + 1)?
+            } else {
+                0xFF
+            };
+            let order = [[first & 0xF, second & 0xF], [first >> 4, second >> 4]];
+            offset += 1 + usize::from(pawns_on_both_sides);
+
+            for k in 0..entry.piece_count {
+                let byte = read_u8(&data, offset)?;
+                for (side, side_pairs) in pairs.iter_mut().enumerate() {
+                    side_pairs[file].pieces[k] = if side == 0 { byte & 0xF } else { byte >> 4 };
+                }
+                offset += 1;
+            }
+            for (side, side_pairs) in pairs.iter_mut().enumerate() {
+                set_groups(entry, &mut side_pairs[file], order[side], file);
+            }
+        }
+        offset += offset & 1;
+
+        for file in 0..files {
+            for side_pairs in pairs.iter_mut() {
+                offset = set_sizes(&mut side_pairs[file], &data, offset).ok_or_else(corrupted)?;
+            }
+        }
+
+        let map = offset;
+        if dtz {
+            offset = set_dtz_map(&mut pairs[0], &data, map).ok_or_else(corrupted)?;
+        }
+
+        for file in 0..files {
+            for side_pairs in pairs.iter_mut() {
+                side_pairs[file].sparse_index = offset;
+                offset += 6 * side_pairs[file].sparse_index_size;
+            }
+        }
+        for file in 0..files {
+            for side_pairs in pairs.iter_mut() {
+                side_pairs[file].block_lengths = offset;
+                offset += 2 * side_pairs[file].block_lengths_size;
+            }
+        }
+        for file in 0..files {
+            for side_pairs in pairs.iter_mut() {
+                offset = (offset + 0x3F) & !0x3F;
+                side_pairs[file].blocks = offset;
+                offset += side_pairs[file].block_count * side_pairs[file].block_size;
+            }
+        }
+
+        if offset > data.len() {
+            return Err(corrupted());
+        }
+
+        Ok(Table { data, pairs, map })
+    }
+
+    fn pairs(&self, side: usize, file: usize) -> &PairsData {
+        &self.pairs[side % self.pairs.len()][file]
+    }
+
+    // the value stored at the given index
+    fn decompress(&self, d: &PairsData, idx: u64) -> Result<i32, String> {
+        if d.flags & SINGLE_VALUE != 0 {
+            return Ok(i32::from(d.min_sym_len));
+        }
+
+        // the sparse index points to the middle of every span of indices
+        let sparse_entry = d.sparse_index + 6 * (idx / d.span) as usize;
+        let mut block = le_u32(&self.data, sparse_entry)? as usize;
+        let mut offset = i64::from(le_u16(&self.data, sparse_entry + 4)?) + (idx % d.span) as i64
+            - (d.span / 2) as i64;
+
+        let block_length = |block: usize| -> Result<i64, String> {
+            Ok(i64::from(le_u16(&self.data, d.block_lengths + 2 * block)?))
+        };
+        while offset < 0 {
+            block = block
+                .checked_sub(1)
+                .ok_or_else(|| "Corrupted tablebase block index".to_string())?;
+            offset += block_length(block)? + 1;
+        }
+        while offset > block_length(block)? {
+            offset -= block_length(block)? + 1;
+            block += 1;
+        }
+
+        // each symbol of the block expands to one or more values
+        let mut pointer = d.blocks + block * d.block_size;
+        let mut buffer = be_u64(&self.data, pointer);
+        pointer += 8;
+        let mut buffer_size = 64;
+        let min_sym_len = usize::from(d.min_sym_len);
+
+        let mut sym = loop {
+            let mut len = 0;
+            while buffer < d.base64[len] {
+                len += 1;
+            }
+
+            let sym = ((buffer - d.base64[len]) >> (64 - len - min_sym_len)) as u16;
+            let sym = sym.wrapping_add(le_u16(&self.data, d.lowest_sym + 2 * len)?);
+            let sym_values = i64::from(symlen(d, sym)?) + 1;
+            if offset < sym_values {
+                break sym;
+            }
+
+            offset -= sym_values;
+            let len = len + min_sym_len;
+            buffer = buffer.checked_shl(len as u32).unwrap_or(0);
+            buffer_size -= len;
+
+            if buffer_size <= 32 {
+                buffer_size += 32;
+                buffer |= u64::from(be_u32(&self.data, pointer)) << (64 - buffer_size);
+                pointer += 4;
+            }
+        };
+
+        // the pairs a symbol stands for are adjacent, descend into the one holding the value
+        while symlen(d, sym)? != 0 {
+            let left = self.left_symbol(d, sym)?;
+            let left_values = i64::from(symlen(d, left)?) + 1;
+            if offset < left_values {
+                sym = left;
+            } else {
+                offset -= left_values;
+                sym = self.right_symbol(d, sym)?;
+            }
+        }
+
+        Ok(i32::from(self.left_symbol(d, sym)?))
+    }
+
+    // the tree stores two 12 bit symbols in three bytes for every symbol
+    fn left_symbol(&self, d: &PairsData, sym: u16) -> Result<u16, String> {
+        left_symbol(&self.data, d.btree, sym)
+    }
+
+    fn right_symbol(&self, d: &PairsData, sym: u16) -> Result<u16, String> {
+        right_symbol(&self.data, d.btree, sym)
+    }
+
+    // DTZ values are stored by frequency for each result, in moves unless stored in plies
+    fn map_dtz(&self, file: usize, value: i32, wdl: Wdl) -> Result<i32, String> {
+        const WDL_MAP: [usize; 5] = [1, 3, 0, 2, 0];
+
+        let d = self.pairs(0, file);
+        let mut value = value as usize;
+        if d.flags & MAPPED != 0 {
+            let idx = usize::from(d.map_idx[WDL_MAP[(wdl.value() + 2) as usize]]);
+            value = if d.flags & WIDE != 0 {
+                usize::from(le_u16(&self.data, self.map + 2 * (idx + value))?)
+            } else {
+                usize::from(read_u8(&self.data, self.map + idx + value)?)
+            };
+        }
+
+        let mut value = value as i32;
+        if (wdl == Wdl::Win && d.flags & WIN_PLIES == 0)
+            || (wdl == Wdl::Loss && d.flags & LOSS_PLIES == 0)
+            || wdl == Wdl::CursedWin
+            || wdl == Wdl::BlessedLoss
+        {
+            value *= 2;
+        }
+        Ok(value + 1)
+    }
+}
+
+fn set_groups(entry: &TableEntry, d: &mut PairsData, order: [u8; 2], file: usize) {
+    let encoding = &*ENCODING;
+
+    // the leading group holds the kings and a unique piece, or the leading pawns
+    let mut first_len: i32 = if entry.has_pawns {
+        0
+    } else if entry.has_unique_pieces {
+        3
+    } else {
+        2
+    };
+    let mut n = 0;
+    d.group_len[0] = 1;
+    for i in 1..entry.piece_count {
+        first_len -= 1;
+        if first_len > 0 || d.pieces[i] == d.pieces[i - 1] {
+            d.group_len[n] += 1;
+        } else {
+            n += 1;
+            d.group_len[n] = 1;
+        }
+    }
+    n += 1;
+    d.group_len[n] = 0;
+
+    // the groups are encoded in the order stored in the table, the leading group at order[0]
+    // and the remaining pawns at order[1]
+    let pawns_on_both_sides = entry.has_pawns && entry.pawn_count[1] != 0;
+    let mut next = if pawns_on_both_sides { 2 } else { 1 };
+    let mut free_squares = 64
+        - d.group_len[0]
+        - if pawns_on_both_sides {
+            d.group_len[1]
+        } else {
+            0
+        };
+    let mut idx: u64 = 1;
+    let mut k = 0;
+
+    while next < n || k == usize::from(order[0]) || k == usize::from(order[1]) {
+        if k == usize::from(order[0]) {
+            d.group_idx[0] = idx;
+            idx *= if entry.has_pawns {
+                encoding.lead_pawns_size[d.group_len[0]][file]
+            } else if entry.has_unique_pieces {
+                31332
+            } else {
+                462
+            };
+        } else if k == usize::from(order[1]) {
+            d.group_idx[1] = idx;
+            idx *= encoding.binomial[d.group_len[1]][48 - d.group_len[0]];
+        } else {
+            d.group_idx[next] = idx;
+            idx *= encoding.binomial[d.group_len[next]][free_squares];
+            free_squares -= d.group_len[next];
+            next += 1;
+        }
+        k += 1;
+    }
+    d.group_idx[n] = idx;
+}
+
+fn set_sizes(d: &mut PairsData, data: &[u8], mut offset: usize) -> Option<usize> {
+    d.flags = read_u8(data, offset).ok()?;
+    offset += 1;
+
+    if d.flags & SINGLE_VALUE != 0 {
+        d.min_sym_len = read_u8(data, offset).ok()?;
+        return Some(offset + 1);
+    }
+
+    let groups = d.group_len.iter().position(|len| *len == 0)?;
+    let table_size = d.group_idx[groups];
+
+    d.block_size = 1usize.checked_shl(u32::from(read_u8(data, offset).ok()?))?;
+    d.span = 1u64.checked_shl(u32::from(read_u8(data, offset + 1).ok()?))?;
+    d.sparse_index_size = ((table_size + d.span - 1) / d.span) as usize;
+    let padding = usize::from(read_u8(data, offset + 2).ok()?);
+    d.block_count = le_u32(data, offset + 3).ok()? as usize;
+    d.block_lengths_size = d.block_count + padding;
+    let max_sym_len = read_u8(data, offset + 7).ok()?;
+    d.min_sym_len = read_u8(data, offset + 8).ok()?;
+    offset += 9;
+
+    if d.min_sym_len == 0 || max_sym_len < d.min_sym_len {
+        return None;
+    }
+
+    // canonical Huffman codes: longer codes have lower values, base64[i] is the lowest code of
+    // length min_sym_len + i left aligned in 64 bits
+    d.lowest_sym = offset;
+    let lengths = usize::from(max_sym_len - d.min_sym_len) + 1;
+    d.base64 = vec![0; lengths];
+    for i in (0..lengths - 1).rev() {
+        d.base64[i] = d.base64[i + 1]
+            .wrapping_add(u64::from(le_u16(data, offset + 2 * i).ok()?))
+            .wrapping_sub(u64::from(le_u16(data, offset + 2 * i + 2).ok()?))
+            / 2;
+    }
+    for (i, base) in d.base64.iter_mut().enumerate() {
+        *base = base
+            .checked_shl((64 - i - usize::from(d.min_sym_len)) as u32)
+            .unwrap_or(0);
+    }
+    offset += 2 * lengths;
+
+    let symbols = usize::from(le_u16(data, offset).ok()?);
+    offset += 2;
+    d.btree = offset;
+
+    d.symlen = vec![0; symbols];
+    let mut visited = vec![false; symbols];
+    for sym in 0..symbols {
+        if !visited[sym] {
+            d.symlen[sym] = set_symlen(d, data, sym, &mut visited)?;
+        }
+    }
+
+    Some(offset + 3 * symbols + (symbols & 1))
+}
+
+fn set_symlen(d: &mut PairsData, data: &[u8], sym: usize, visited: &mut [bool]) -> Option<u8> {
+    visited[sym] = true;
+    let right = usize::from(right_symbol(data, d.btree, sym as u16).ok()?);
+    if right == 0xFFF {
+        return Some(0);
+    }
+    let left = usize::from(left_symbol(data, d.btree, sym as u16).ok()?);
+    if left >= visited.len() || right >= visited.len() {
+        return None;
+    }
+
+    if !visited[left] {
+        d.symlen[left] = set_symlen(d, data, left, visited)?;
+    }
+    if !visited[right] {
+        d.symlen[right] = set_symlen(d, data, right, visited)?;
+    }
+    Some(d.symlen[left].wrapping_add(d.symlen[right]).wrapping_add(1))
+}
+
+fn set_dtz_map(pairs: &mut [PairsData], data: &[u8], map: usize) -> Option<usize> {
+    let mut offset = map;
+
+    for d in pairs.iter_mut() {
+        if d.flags & MAPPED == 0 {
+            continue;
+        }
+        if d.flags & WIDE != 0 {
+            offset += offset & 1;
+            for idx in d.map_idx.iter_mut() {
+                *idx = u16::try_from((offset - map) / 2 + 1).ok()?;
+                offset += 2 * usize::from(le_u16(data, offset).ok()?) + 2;
+            }
+        } else {
+            for idx in d.map_idx.iter_mut() {
+                *idx = u16::try_from(offset - map + 1).ok()?;
+                offset += usize::from(read_u8(data, offset).ok()?) + 1;
+            }
+        }
+    }
+
+    Some(offset + (offset & 1))
+}
+
+fn left_symbol(data: &[u8], btree: usize, sym: u16) -> Result<u16, String> {
+    let entry = btree + 3 * usize::from(sym);
+    Ok((u16::from(read_u8(data, entry + 1)? & 0xF) << 8) | u16::from(read_u8(data, entry)?))
+}
+
+fn right_symbol(data: &[u8], btree: usize, sym: u16) -> Result<u16, String> {
+    let entry = btree + 3 * usize::from(sym);
+    Ok((u16::from(read_u8(data, entry + 2)?) << 4) | u16::from(read_u8(data, entry + 1)? >> 4))
+}
+
+fn symlen(d: &PairsData, sym: u16) -> Result<u8, String> {
+    d.symlen
+        .get(usize::from(sym))
+        .copied()
+        .ok_or_else(|| "Corrupted tablebase symbol".to_string())
+}
+
+fn read_u8(data: &[u8], offset: usize) -> Result<u8, String> {
+    data.get(offset)
+        .copied()
+        .ok_or_else(|| "Tablebase file is too short".to_string())
+}
+
+fn le_u16(data: &[u8], offset: usize) -> Result<u16, String> {
+    Ok(u16::from_le_bytes([
+        read_u8(data, offset)?,
+        read_u8(data, offset + 1)?,
+    ]))
+}
+
+fn le_u32(data: &[u8], offset: usize) -> Result<u32, String> {
+    Ok(u32::from_le_bytes([
+        read_u8(data, offset)?,
+        read_u8(data, offset + 1)?,
+        read_u8(data, offset + 2)?,
+        read_u8(data, offset + 3)?,
+    ]))
+}
+
+// the compressed blocks are read as big endian words, past the end of the file reads zeros
+fn be_u32(data: &[u8], offset: usize) -> u32 {
+    let mut bytes = [0; 4];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = data.get(offset + i).copied().unwrap_or(0);
+    }
+    u32::from_be_bytes(bytes)
+}
+
+fn be_u64(data: &[u8], offset: usize) -> u64 {
+    (u64::from(be_u32(data, offset)) << 32) | u64::from(be_u32(data, offset + 4))
+}
+
+enum TableKind {
+    Wdl,
+    // the DTZ table of a position with the given result
+    Dtz(Wdl),
+}
+
+enum TableValue {
+    Value(i32),
+    // DTZ tables only store one side to move
+    OtherSideToMove,
+    Missing,
+}
+
+pub struct Tablebase {
+    tables: HashMap<String, TableEntry>,
+    max_pieces: usize,
+    move_generator: MoveGenerator,
+}
+
+impl Tablebase {
+    // The tables found in the directories, given like the PATH environment variable. Tables are
+    // named by their pieces, e.g. KRvK.rtbw and KRvK.rtbz.
+    pub fn open(paths: &str) -> Result<Tablebase, String> {
+        let mut tables: HashMap<String, TableEntry> = HashMap::new();
+
+        for directory in env::split_paths(paths) {
+            let files = fs::read_dir(&directory).map_err(|e| {
+                format!(
+                    "Cannot read tablebase directory {}: {}",
+                    directory.display(),
+                    e
+                )
+            })?;
+
+            for path in files.filter_map(|file| file.ok()).map(|file| file.path()) {
+                let extension = path.extension().and_then(|extension| extension.to_str());
+                let material = match path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(parse_table_name)
+                {
+                    Some(material) => material,
+                    None => continue,
+                };
+                let entry = tables
+                    .entry(table_name(&material[0], &material[1]))
+                    .or_insert_with(|| TableEntry::new(&material));
+
+                match extension {
+                    Some(WDL_EXTENSION) if entry.wdl_path.is_none() => entry.wdl_path = Some(path),
+                    Some(DTZ_EXTENSION) if entry.dtz_path.is_none() => entry.dtz_path = Some(path),
+                    _ => (),
+                }
+            }
+        }
+
+        tables.retain(|_, entry| entry.wdl_path.is_some());
+        let max_pieces = tables
+            .values()
+            .map(|entry| entry.piece_count)
+            .max()
+            .unwrap_or(0);
+
+        Ok(Tablebase {
+            tables,
+            max_pieces,
+            move_generator: MoveGenerator::new(),
+        })
+    }
+
+    // number of WDL tables found
+    pub fn table_count(&self) -> usize {
+        self.tables.len()
+    }
+
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    // Positions with castling rights are not in the tables, and the tables know nothing of the
+    // variants.
+    pub fn can_probe(&self, game_state: &GameState) -> bool {
+        let pieces: u8 = material(game_state).iter().flatten().sum();
+        game_state.variant() == Variant::Standard
+            && usize::from(pieces) <= self.max_pieces
+            && [Color::WHITE, Color::BLACK].iter().all(|color| {
+                !game_state.castling_rights.is_allowed(*color, true)
+                    && !game_state.castling_rights.is_allowed(*color, false)
+            })
+    }
+
+    // the result for the side to move, None when the position is not covered by the tables
+    pub fn probe_wdl(&self, game_state: &GameState) -> Option<Wdl> {
+        if !self.can_probe(game_state) {
+            return None;
+        }
+        let mut game_state = *game_state;
+        self.search(&mut game_state, false).map(|(wdl, _)| wdl)
+    }
+
+    // Plies to the next capture or pawn move on the way to the result, positive when the side to
+    // move wins and beyond 100 for results decided by the fifty move rule. 0 for draws.
+    pub fn probe_dtz(&self, game_state: &GameState) -> Option<i32> {
+        if !self.can_probe(game_state) {
+            return None;
+        }
+        let mut game_state = *game_state;
+        self.dtz(&mut game_state)
+    }
+
+    // The legal moves that keep the best result: the fastest wins, any draw, or the slowest
+    // losses by DTZ. None when the position is not covered by the tables.
+    pub fn best_root_moves(&self, game_state: &GameState) -> Option<Vec<Move>> {
+        if !self.can_probe(game_state) {
+            return None;
+        }
+
+        let mut game_state = *game_state;
+        let mut ranked_moves = Vec::new();
+
+        for m in self.move_generator.generate_moves(&game_state).moves {
+            game_state.apply_move_mut(m);
+            let dtz = if is_zeroing(&m) {
+                self.search(&mut game_state, false)
+                    .map(|(wdl, _)| dtz_before_zeroing(wdl.opposite()))
+            } else {
+                self.dtz(&mut game_state).map(|dtz| -dtz + (-dtz).signum())
+            };
+            let is_checkmate = self
+                .move_generator
+                .generate_moves(&game_state)
+                .is_checkmate();
+            game_state.unapply_move_mut(m);
+
+            let dtz = match dtz? {
+                2 if is_checkmate => 1,
+                dtz => dtz,
+            };
+            let rank = match dtz {
+                dtz if dtz > 0 => MAX_DTZ - dtz,
+                0 => 0,
+                dtz => -MAX_DTZ - dtz,
+            };
+            ranked_moves.push((m, rank));
+        }
+
+        let best_rank = ranked_moves.iter().map(|(_, rank)| *rank).max()?;
+        Some(
+            ranked_moves
+                .into_iter()
+                .filter(|(_, rank)| *rank == best_rank)
+                .map(|(m, _)| m)
+                .collect(),
+        )
+    }
+
+    // The tables leave out positions where a capture is best, and DTZ tables also positions where
+    // a pawn move is, so these moves are searched before probing. Also returns whether such a
+    // move is best.
+    fn search(&self, game_state: &mut GameState, pawn_moves: bool) -> Option<(Wdl, bool)> {
+        let moves = self.move_generator.generate_moves(game_state).moves;
+        let mut best = Wdl::Loss;
+        let mut searched = 0;
+
+        for m in moves.iter() {
+            if !(is_capture(m) || pawn_moves && m.moving_piece == Piece::PAWN) {
+                continue;
+            }
+            searched += 1;
+
+            game_state.apply_move_mut(*m);
+            let result = self.search(game_state, false);
+            game_state.unapply_move_mut(*m);
+
+            let wdl = result?.0.opposite();
+            if wdl > best {
+                best = wdl;
+                if wdl == Wdl::Win {
+                    return Some((wdl, true));
+                }
+            }
+        }
+
+        // the table may be wrong when every move was searched, e.g. after en passant
+        let all_searched = searched != 0 && searched == moves.len();
+        let wdl = if all_searched {
+            best
+        } else {
+            match self.probe_table(game_state, TableKind::Wdl) {
+                Ok(TableValue::Value(value)) => Wdl::from_value(value),
+                _ => return None,
+            }
+        };
+
+        if best >= wdl {
+            Some((best, best > Wdl::Draw || all_searched))
+        } else {
+            Some((wdl, false))
+        }
+    }
+
+    fn dtz(&self, game_state: &mut GameState) -> Option<i32> {
+        let (wdl, zeroing_is_best) = self.search(game_state, true)?;
+        if wdl == Wdl::Draw {
+            return Some(0);
+        }
+        if zeroing_is_best {
+            return Some(dtz_before_zeroing(wdl));
+        }
+
+        match self.probe_table(game_state, TableKind::Dtz(wdl)) {
+            Ok(TableValue::Value(dtz)) => {
+                let fifty_move_rule = matches!(wdl, Wdl::CursedWin | Wdl::BlessedLoss);
+                Some((dtz + if fifty_move_rule { 100 } else { 0 }) * wdl.signum())
+            }
+            Ok(TableValue::Missing) | Err(_) => None,
+            // the best DTZ among the moves
+            Ok(TableValue::OtherSideToMove) => {
+                let mut min_dtz = None;
+
+                for m in self.move_generator.generate_moves(game_state).moves {
+                    let zeroing = is_zeroing(&m);
+                    game_state.apply_move_mut(m);
+                    let dtz = if zeroing {
+                        self.search(game_state, false)
+                            .map(|(wdl, _)| -dtz_before_zeroing(wdl))
+                    } else {
+                        self.dtz(game_state).map(|dtz| -dtz)
+                    };
+                    let is_checkmate = dtz == Some(1)
+                        && self
+                            .move_generator
+                            .generate_moves(game_state)
+                            .is_checkmate();
+                    game_state.unapply_move_mut(m);
+
+                    let mut dtz = dtz?;
+                    if is_checkmate {
+                        min_dtz = Some(1);
+                    }
+                    if !zeroing {
+                        dtz += dtz.signum();
+                    }
+                    if dtz.signum() == wdl.signum() && min_dtz.map_or(true, |min| dtz < min) {
+                        min_dtz = Some(dtz);
+                    }
+                }
+
+                // without legal moves the side to move is mated
+                Some(min_dtz.unwrap_or(-1))
+            }
+        }
+    }
+
+    // fails on a table that is cut short or corrupted
+    fn probe_table(&self, game_state: &GameState, kind: TableKind) -> Result<TableValue, String> {
+        let encoding = &*ENCODING;
+        let material = material(game_state);
+        let piece_count: usize = material
+            .iter()
+            .flatten()
+            .map(|count| usize::from(*count))
+            .sum();
+        if piece_count == 2 {
+            return Ok(TableValue::Value(Wdl::Draw.value()));
+        }
+
+        // the tables are stored with the stronger side as white
+        let (entry, black_stronger) = match self.tables.get(&table_name(&material[0], &material[1]))
+        {
+            Some(entry) => (entry, false),
+            None => match self.tables.get(&table_name(&material[1], &material[0])) {
+                Some(entry) => (entry, true),
+                None => return Ok(TableValue::Missing),
+            },
+        };
+        let table = match kind {
+            TableKind::Wdl => entry.wdl_table(),
+            TableKind::Dtz(_) => entry.dtz_table(),
+        };
+        let table = match table {
+            Some(table) => table,
+            None => return Ok(TableValue::Missing),
+        };
+
+        // positions of symmetric tables are stored with white to move, others are looked up
+        // with colors swapped and the board mirrored when black is the stronger side
+        let flip = black_stronger || (entry.symmetric && game_state.to_move() == Color::BLACK);
+        let flip_color = if flip { BLACK_PIECE } else { 0 };
+        let flip_squares = |square: usize| if flip { flip_rank(square) } else { square };
+        let side_to_move = usize::from(flip != (game_state.to_move() == Color::BLACK));
+
+        let mut squares = [0; MAX_PIECES];
+        let mut pieces = [0; MAX_PIECES];
+        let mut size = 0;
+        let mut lead_pawns = 0;
+        let mut file = 0;
+
+        // the tables are split by the file of the leading pawn, the pawns of the leading color
+        // are the first pieces of every table
+        if entry.has_pawns {
+            let lead_color = if (table.pairs(0, 0).pieces[0] ^ flip_color) & BLACK_PIECE == 0 {
+                Color::WHITE
+            } else {
+                Color::BLACK
+            };
+            lead_pawns = *game_state.get_piece_mask(Piece::PAWN, lead_color);
+            for square in squares_of_mask(lead_pawns) {
+                squares[size] = flip_squares(square);
+                size += 1;
+            }
+            let lead = (0..size)
+                .max_by_key(|i| encoding.map_pawns[squares[*i]])
+                .unwrap();
+            squares.swap(0, lead);
+            file = usize::min(file_of(squares[0]), 7 - file_of(squares[0]));
+        }
+        let lead_pawn_count = size;
+
+        if let TableKind::Dtz(_) = kind {
+            // symmetric tables without pawns are always looked up with white to move
+            let flags = table.pairs(side_to_move, file).flags;
+            let stored =
+                usize::from(flags & STM) == side_to_move || (entry.symmetric && !entry.has_pawns);
+            if !stored {
+                return Ok(TableValue::OtherSideToMove);
+            }
+        }
+
+        let occupied = squares_of_mask(occupied(game_state) & !lead_pawns);
+        for square in occupied {
+            let (piece, color) = game_state
+                .get_piece(Position::from_numeric(square as u8))
+                .unwrap();
+            squares[size] = flip_squares(square);
+            pieces[size] = piece_code(piece, color) ^ flip_color;
+            size += 1;
+        }
+
+        // the pieces in the order of the table
+        let d = table.pairs(side_to_move, file);
+        for i in lead_pawn_count..size - 1 {
+            if let Some(j) = (i + 1..size).find(|j| pieces[*j] == d.pieces[i]) {
+                pieces.swap(i, j);
+                squares.swap(i, j);
+            }
+        }
+
+        // the leading piece is mirrored into the a1-d1-d4 triangle, or the a to d files
+        if file_of(squares[0]) > 3 {
+            for square in squares[..size].iter_mut() {
+                *square = flip_file(*square);
+            }
+        }
+
+        let mut idx = if entry.has_pawns {
+            let mut idx = encoding.lead_pawn_idx[lead_pawn_count][squares[0]];
+            squares[1..lead_pawn_count].sort_by_key(|square| encoding.map_pawns[*square]);
+            for (i, square) in squares.iter().enumerate().take(lead_pawn_count).skip(1) {
+                idx += encoding.binomial[i][encoding.map_pawns[*square]];
+            }
+            idx
+        } else {
+            if rank_of(squares[0]) > 3 {
+                for square in squares[..size].iter_mut() {
+                    *square = flip_rank(*square);
+                }
+            }
+
+            // the first piece of the leading group off the diagonal goes below it
+            for i in 0..d.group_len[0] {
+                let off = off_diagonal(squares[i]);
+                if off == 0 {
+                    continue;
+                }
+                if off > 0 {
+                    for square in squares[i..size].iter_mut() {
+                        *square = ((*square >> 3) | (*square << 3)) & 63;
+                    }
+                }
+                break;
+            }
+
+            if entry.has_unique_pieces {
+                leading_group_index(&squares)
+            } else {
+                encoding.map_kk[encoding.map_a1d1d4[squares[0]]][squares[1]]
+            }
+        };
+        idx *= d.group_idx[0];
+
+        // every other group is indexed by its squares, skipping the squares of earlier groups
+        let mut group_start = d.group_len[0];
+        let mut remaining_pawns = entry.has_pawns && entry.pawn_count[1] != 0;
+        let mut next = 1;
+        while d.group_len[next] != 0 {
+            let group_end = group_start + d.group_len[next];
+            squares[group_start..group_end].sort_unstable();
+
+            let mut n = 0;
+            for i in 0..d.group_len[next] {
+                let square = squares[group_start + i];
+                let adjust = squares[..group_start]
+                    .iter()
+                    .filter(|earlier| square > **earlier)
+                    .count();
+                let pawn_adjust = if remaining_pawns { 8 } else { 0 };
+                n += encoding.binomial[i + 1][square - adjust - pawn_adjust];
+            }
+
+            remaining_pawns = false;
+            idx += n * d.group_idx[next];
+            group_start = group_end;
+            next += 1;
+        }
+
+        let value = table.decompress(d, idx)?;
+        Ok(TableValue::Value(match kind {
+            TableKind::Wdl => value - 2,
+            TableKind::Dtz(wdl) => table.map_dtz(file, value, wdl)?,
+        }))
+    }
+}
+
+// index of the kings and a unique piece, with the first one in the a1-d1-d4 triangle
+fn leading_group_index(squares: &[usize]) -> u64 {
+    let encoding = &*ENCODING;
+    let [first, second, third] = [squares[0], squares[1], squares[2]];
+    let adjust1 = usize::from(second > first);
+    let adjust2 = usize::from(third > first) + usize::from(third > second);
+
+    if off_diagonal(first) != 0 {
+        ((encoding.map_a1d1d4[first] * 63 + second - adjust1) * 62 + third - adjust2) as u64
+    } else if off_diagonal(second) != 0 {
+        (6 * 63 + rank_of(first) as u64 * 28 + encoding.map_b1h1h7[second]) * 62
+            + (third - adjust2) as u64
+    } else if off_diagonal(third) != 0 {
+        (6 * 63 * 62 + 4 * 28 * 62 + rank_of(first) * 7 * 28 + (rank_of(second) - adjust1) * 28)
+            as u64
+            + encoding.map_b1h1h7[third]
+    } else {
+        (6 * 63 * 62
+            + 4 * 28 * 62
+            + 4 * 7 * 28
+            + rank_of(first) * 7 * 6
+            + (rank_of(second) - adjust1) * 6
+            + (rank_of(third) - adjust2)) as u64
+    }
+}
+
+fn occupied(game_state: &GameState) -> u64 {
+    [Color::WHITE, Color::BLACK]
+        .iter()
+        .flat_map(|color| {
+            PIECES
+                .iter()
+                .map(move |piece| *game_state.get_piece_mask(*piece, *color))
+        })
+        .fold(0, |occupied, mask| occupied | mask)
+}
+
+fn is_capture(m: &Move) -> bool {
+    matches!(m.move_type, MoveType::Capture(_) | MoveType::EnPassant)
+}
+
+// captures and pawn moves reset the fifty move counter
+fn is_zeroing(m: &Move) -> bool {
+    is_capture(m) || m.moving_piece == Piece::PAWN
+}
+
+fn dtz_before_zeroing(wdl: Wdl) -> i32 {
+    match wdl {
+        Wdl::Win => 1,
+        Wdl::CursedWin => 101,
+        Wdl::Draw => 0,
+        Wdl::BlessedLoss => -101,
+        Wdl::Loss => -1,
+    }
+}
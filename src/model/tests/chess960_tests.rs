@@ -0,0 +1,97 @@
+#[cfg(test)]
+use crate::model::chess960;
+#[cfg(test)]
+use crate::model::game_state::{Color, GameState, Piece, Position};
+#[cfg(test)]
+use crate::notation::fen::{parse_fen, to_fen};
+
+#[test]
+fn standard_position_has_number_518() {
+    assert_eq!(
+        GameState::new(),
+        chess960::starting_position(chess960::STANDARD_POSITION_NUMBER).unwrap()
+    );
+}
+
+#[test]
+fn positions_are_numbered_like_scharnagl() {
+    let expected = [
+        (
+            0,
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1",
+        ),
+        (
+            959,
+            "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w KQkq - 0 1",
+        ),
+        (
+            534,
+            "rnbkqbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBKQBNR w KQkq - 0 1",
+        ),
+    ];
+
+    for (number, fen) in expected.iter() {
+        let game_state = chess960::starting_position(*number).unwrap();
+        assert_eq!(*fen, to_fen(&game_state));
+        assert_eq!(parse_fen(fen).unwrap(), game_state);
+    }
+    assert!(chess960::starting_position(960).is_err());
+}
+
+#[test]
+fn every_position_is_a_valid_start() {
+    let mut back_ranks = Vec::new();
+
+    for number in 0..chess960::POSITION_COUNT {
+        let pieces = chess960::back_rank(number).unwrap();
+        let files_of =
+            |piece: Piece| -> Vec<usize> { (0..8).filter(|file| pieces[*file] == piece).collect() };
+
+        let bishops = files_of(Piece::BISHOP);
+        let rooks = files_of(Piece::ROOK);
+        let king = files_of(Piece::KING)[0];
+        assert_eq!(1, (bishops[0] + bishops[1]) % 2, "{}", number);
+        assert!(rooks[0] < king && king < rooks[1], "{}", number);
+        assert_eq!(2, files_of(Piece::KNIGHT).len());
+        assert_eq!(1, files_of(Piece::QUEEN).len());
+
+        let game_state = chess960::starting_position(number).unwrap();
+        assert_eq!(Ok(()), game_state.validate());
+        back_ranks.push(pieces);
+    }
+
+    back_ranks.sort_by_key(|pieces| format!("{:?}", pieces));
+    back_ranks.dedup();
+    assert_eq!(960, back_ranks.len());
+}
+
+#[test]
+fn castling_in_place_moves_only_the_rook() {
+    let game_state = parse_fen("1r4kr/8/8/8/8/8/8/1R4KR w HBhb - 0 1").unwrap();
+    let castling = crate::model::move_generator::MoveGenerator::new()
+        .find_move(&game_state, &"g1h1".parse().unwrap())
+        .unwrap();
+
+    let next = game_state.apply_move(castling);
+
+    assert_eq!(
+        Some((Piece::KING, Color::WHITE)),
+        next.get_piece(Position::new(7, 1))
+    );
+    assert_eq!(
+        Some((Piece::ROOK, Color::WHITE)),
+        next.get_piece(Position::new(6, 1))
+    );
+    assert_eq!(None, next.get_piece(Position::new(8, 1)));
+    assert_eq!("1r4kr/8/8/8/8/8/8/1R3RK1 b kq - 0 1", to_fen(&next));
+}
+
+#[test]
+fn x_fen_names_inner_rooks_by_file() {
+    let fen = "rr2k3/8/8/8/8/8/8/RR2K3 w Bb - 0 1";
+    let game_state = parse_fen(fen).unwrap();
+
+    assert_eq!(2, game_state.castling_rights.queen_side_rook_file);
+    assert_eq!(fen, to_fen(&game_state));
+    assert!(parse_fen("rr2k3/8/8/8/8/8/8/RR2K3 w Ba - 0 1").is_err());
+}
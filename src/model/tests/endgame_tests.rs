@@ -0,0 +1,201 @@
+#[cfg(test)]
+use crate::model::evaluator;
+#[cfg(test)]
+use crate::model::game_state::{CastlingRights, Color, GameState, Piece, Position};
+#[cfg(test)]
+use crate::model::zobrist_hash;
+
+#[cfg(test)]
+fn position_with(pieces: &[(Piece, Color, Position)], to_move: Color) -> GameState {
+    let mut state = GameState::new_empty();
+    state.castling_rights = CastlingRights::none();
+    state.to_move = to_move;
+
+    for (piece, color, position) in pieces.iter() {
+        state.set_piece(*piece, *color, *position);
+    }
+
+    state.zobrist_hash = zobrist_hash::hash(&state);
+    state
+}
+
+#[cfg(test)]
+fn kings_and(pieces: &[(Piece, Color, Position)]) -> GameState {
+    let mut all_pieces = vec![
+        (Piece::KING, Color::WHITE, Position::new(5, 1)),
+        (Piece::KING, Color::BLACK, Position::new(5, 8)),
+    ];
+    all_pieces.extend_from_slice(pieces);
+    position_with(&all_pieces, Color::WHITE)
+}
+
+#[test]
+fn insufficient_material() {
+    assert!(kings_and(&[]).is_insufficient_material());
+    assert!(
+        kings_and(&[(Piece::KNIGHT, Color::WHITE, Position::new(2, 1))]).is_insufficient_material()
+    );
+    assert!(
+        kings_and(&[(Piece::BISHOP, Color::BLACK, Position::new(3, 8))]).is_insufficient_material()
+    );
+
+    // c1 and f8 are both dark squares
+    assert!(kings_and(&[
+        (Piece::BISHOP, Color::WHITE, Position::new(3, 1)),
+        (Piece::BISHOP, Color::BLACK, Position::new(6, 8)),
+    ])
+    .is_insufficient_material());
+}
+
+#[test]
+fn sufficient_material() {
+    assert!(!GameState::new().is_insufficient_material());
+    assert!(
+        !kings_and(&[(Piece::PAWN, Color::WHITE, Position::new(1, 2))]).is_insufficient_material()
+    );
+    assert!(
+        !kings_and(&[(Piece::ROOK, Color::BLACK, Position::new(1, 8))]).is_insufficient_material()
+    );
+    assert!(!kings_and(&[
+        (Piece::BISHOP, Color::WHITE, Position::new(3, 1)),
+        (Piece::BISHOP, Color::BLACK, Position::new(3, 8)),
+    ])
+    .is_insufficient_material());
+    assert!(!kings_and(&[
+        (Piece::BISHOP, Color::WHITE, Position::new(3, 1)),
+        (Piece::KNIGHT, Color::WHITE, Position::new(2, 1)),
+    ])
+    .is_insufficient_material());
+}
+
+#[test]
+fn insufficient_material_evaluates_as_draw() {
+    let state = kings_and(&[
+        (Piece::BISHOP, Color::WHITE, Position::new(3, 1)),
+        (Piece::BISHOP, Color::BLACK, Position::new(6, 8)),
+    ]);
+    assert_eq!(0, evaluator::evaluate(&state));
+
+    let state = kings_and(&[(Piece::KNIGHT, Color::WHITE, Position::new(4, 4))]);
+    assert_eq!(0, evaluator::evaluate(&state));
+}
+
+#[test]
+fn queen_mate_drives_king_to_edge() {
+    let queen = (Piece::QUEEN, Color::WHITE, Position::new(1, 1));
+    let on_edge = position_with(
+        &[
+            queen,
+            (Piece::KING, Color::WHITE, Position::new(4, 4)),
+            (Piece::KING, Color::BLACK, Position::new(8, 5)),
+        ],
+        Color::WHITE,
+    );
+    let in_centre = position_with(
+        &[
+            queen,
+            (Piece::KING, Color::WHITE, Position::new(4, 2)),
+            (Piece::KING, Color::BLACK, Position::new(5, 5)),
+        ],
+        Color::WHITE,
+    );
+
+    assert!(evaluator::evaluate(&in_centre) > 50_000);
+    assert!(evaluator::evaluate(&on_edge) > evaluator::evaluate(&in_centre));
+}
+
+#[test]
+fn rook_mate_for_black() {
+    let state = position_with(
+        &[
+            (Piece::ROOK, Color::BLACK, Position::new(1, 8)),
+            (Piece::KING, Color::WHITE, Position::new(5, 1)),
+            (Piece::KING, Color::BLACK, Position::new(5, 3)),
+        ],
+        Color::WHITE,
+    );
+
+    assert!(evaluator::evaluate(&state) < -50_000);
+}
+
+#[test]
+fn bishop_and_knight_mate_drives_king_to_bishop_corner() {
+    // dark squared bishop, so a1 and h8 are the mating corners
+    let minor_pieces = [
+        (Piece::BISHOP, Color::WHITE, Position::new(3, 1)),
+        (Piece::KNIGHT, Color::WHITE, Position::new(4, 4)),
+    ];
+    let right_corner = position_with(
+        &[
+            minor_pieces[0],
+            minor_pieces[1],
+            (Piece::KING, Color::WHITE, Position::new(6, 6)),
+            (Piece::KING, Color::BLACK, Position::new(8, 8)),
+        ],
+        Color::BLACK,
+    );
+    let wrong_corner = position_with(
+        &[
+            minor_pieces[0],
+            minor_pieces[1],
+            (Piece::KING, Color::WHITE, Position::new(3, 6)),
+            (Piece::KING, Color::BLACK, Position::new(1, 8)),
+        ],
+        Color::BLACK,
+    );
+
+    assert!(evaluator::evaluate(&wrong_corner) > 50_000);
+    assert!(evaluator::evaluate(&right_corner) > evaluator::evaluate(&wrong_corner));
+}
+
+#[test]
+fn opposite_colored_bishops_are_drawish() {
+    let pawns = [
+        (Piece::PAWN, Color::WHITE, Position::new(1, 2)),
+        (Piece::PAWN, Color::WHITE, Position::new(2, 2)),
+        (Piece::PAWN, Color::BLACK, Position::new(8, 7)),
+    ];
+    let opposite = kings_and(&[
+        pawns[0],
+        pawns[1],
+        pawns[2],
+        (Piece::BISHOP, Color::WHITE, Position::new(3, 1)),
+        (Piece::BISHOP, Color::BLACK, Position::new(3, 8)),
+    ]);
+    let same = kings_and(&[
+        pawns[0],
+        pawns[1],
+        pawns[2],
+        (Piece::BISHOP, Color::WHITE, Position::new(3, 1)),
+        (Piece::BISHOP, Color::BLACK, Position::new(6, 8)),
+    ]);
+
+    assert!(evaluator::evaluate(&opposite) > 0);
+    assert!(evaluator::evaluate(&opposite) < evaluator::evaluate(&same));
+}
+
+#[test]
+fn wrong_rook_pawn_is_drawish() {
+    // h8 is a dark square, which the light squared bishop on f1 cannot control
+    let wrong_bishop = position_with(
+        &[
+            (Piece::KING, Color::WHITE, Position::new(5, 1)),
+            (Piece::BISHOP, Color::WHITE, Position::new(6, 1)),
+            (Piece::PAWN, Color::WHITE, Position::new(8, 5)),
+            (Piece::KING, Color::BLACK, Position::new(8, 8)),
+        ],
+        Color::WHITE,
+    );
+    let right_bishop = position_with(
+        &[
+            (Piece::KING, Color::WHITE, Position::new(5, 1)),
+            (Piece::BISHOP, Color::WHITE, Position::new(3, 1)),
+            (Piece::PAWN, Color::WHITE, Position::new(8, 5)),
+            (Piece::KING, Color::BLACK, Position::new(8, 8)),
+        ],
+        Color::WHITE,
+    );
+
+    assert!(evaluator::evaluate(&wrong_bishop) < 1000);
+    assert!(evaluator::evaluate(&right_bishop) > 3000);
+}
@@ -0,0 +1,155 @@
+#[cfg(test)]
+use crate::error::ChessError;
+#[cfg(test)]
+use crate::model::game_state::{CastlingRights, Color, GameState, Piece, Position};
+#[cfg(test)]
+use crate::model::game_state_builder::GameStateBuilder;
+#[cfg(test)]
+use crate::model::move_generator::MoveGenerator;
+#[cfg(test)]
+use crate::model::zobrist_hash;
+
+#[cfg(test)]
+fn kings() -> GameStateBuilder {
+    GameStateBuilder::new()
+        .with_piece(Piece::KING, Color::WHITE, Position::new(5, 1))
+        .with_piece(Piece::KING, Color::BLACK, Position::new(5, 8))
+}
+
+#[test]
+fn builds_the_starting_position() {
+    let mut builder = GameStateBuilder::new().with_castling_rights(CastlingRights::initial());
+    let back_rank = [
+        Piece::ROOK,
+        Piece::KNIGHT,
+        Piece::BISHOP,
+        Piece::QUEEN,
+        Piece::KING,
+        Piece::BISHOP,
+        Piece::KNIGHT,
+        Piece::ROOK,
+    ];
+    for (file, piece) in (1..9).zip(back_rank.iter()) {
+        builder = builder
+            .with_piece(*piece, Color::WHITE, Position::new(file, 1))
+            .with_piece(Piece::PAWN, Color::WHITE, Position::new(file, 2))
+            .with_piece(Piece::PAWN, Color::BLACK, Position::new(file, 7))
+            .with_piece(*piece, Color::BLACK, Position::new(file, 8));
+    }
+
+    let game_state = builder.build().unwrap();
+
+    assert_eq!(GameState::new(), game_state);
+    assert_eq!(zobrist_hash::hash(&game_state), game_state.zobrist_hash);
+}
+
+#[test]
+fn piece_replaces_the_previous_one() {
+    let game_state = kings()
+        .with_piece(Piece::ROOK, Color::WHITE, Position::new(1, 1))
+        .with_piece(Piece::KNIGHT, Color::BLACK, Position::new(1, 1))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        Some((Piece::KNIGHT, Color::BLACK)),
+        game_state.get_piece(Position::new(1, 1))
+    );
+    assert!(game_state
+        .get_piece_position(Piece::ROOK, Color::WHITE)
+        .is_empty());
+}
+
+#[test]
+fn kings_are_required() {
+    let result = GameStateBuilder::new()
+        .with_piece(Piece::KING, Color::WHITE, Position::new(5, 1))
+        .build();
+
+    assert_eq!(
+        Err(ChessError::InvalidPosition("BLACK has no king".to_string())),
+        result
+    );
+    assert!(kings()
+        .with_piece(Piece::KING, Color::WHITE, Position::new(1, 1))
+        .build()
+        .is_err());
+}
+
+#[test]
+fn pawns_on_back_ranks_are_rejected() {
+    assert!(kings()
+        .with_piece(Piece::PAWN, Color::WHITE, Position::new(1, 8))
+        .build()
+        .is_err());
+    assert!(kings()
+        .with_piece(Piece::PAWN, Color::BLACK, Position::new(1, 1))
+        .build()
+        .is_err());
+}
+
+#[test]
+fn castling_rights_need_king_and_rook() {
+    let white_king_side = CastlingRights {
+        white_king_side: true,
+        ..CastlingRights::none()
+    };
+
+    assert!(kings()
+        .with_castling_rights(white_king_side)
+        .build()
+        .is_err());
+    assert!(kings()
+        .with_piece(Piece::ROOK, Color::WHITE, Position::new(8, 1))
+        .with_castling_rights(white_king_side)
+        .build()
+        .is_ok());
+}
+
+#[test]
+fn en_passant_needs_a_pawn_that_moved_two_squares() {
+    let black_pawn = kings()
+        .with_piece(Piece::PAWN, Color::BLACK, Position::new(4, 5))
+        .with_en_passant(Some(Position::new(4, 5)));
+
+    assert!(black_pawn.clone().build().is_ok());
+    assert!(black_pawn
+        .clone()
+        .with_to_move(Color::BLACK)
+        .build()
+        .is_err());
+    assert!(black_pawn
+        .with_piece(Piece::KNIGHT, Color::BLACK, Position::new(4, 7))
+        .build()
+        .is_err());
+    assert!(kings()
+        .with_en_passant(Some(Position::new(4, 5)))
+        .build()
+        .is_err());
+}
+
+#[test]
+fn edits_an_existing_position() {
+    let mut after_e4 = GameState::new();
+    after_e4.apply_move_mut(
+        MoveGenerator::new()
+            .get_move(&after_e4, Position::new(5, 2), Position::new(5, 4))
+            .unwrap(),
+    );
+
+    let builder = GameStateBuilder::from(&GameState::new())
+        .without_piece(Position::new(5, 2))
+        .with_piece(Piece::PAWN, Color::WHITE, Position::new(5, 4))
+        .with_to_move(Color::BLACK)
+        .with_en_passant(Some(Position::new(5, 4)));
+
+    assert_eq!(None, builder.piece(Position::new(5, 2)));
+    assert_eq!(after_e4, builder.build().unwrap());
+}
+
+#[test]
+fn edited_position_is_validated() {
+    let without_king = GameStateBuilder::from(&GameState::new()).without_piece(Position::new(5, 1));
+
+    assert!(without_king.build().is_err());
+}
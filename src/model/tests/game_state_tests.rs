@@ -1,7 +1,11 @@
 #[cfg(test)]
+use crate::error::PositionError;
+#[cfg(test)]
 use crate::model::game_state;
 #[cfg(test)]
 use crate::model::game_state::{Color, Piece, Position};
+#[cfg(test)]
+use crate::model::move_generator::MoveGenerator;
 
 #[test]
 fn get_pieces() {
@@ -72,3 +76,106 @@ fn collide() {
     assert_eq!(Option::None, collision_b5);
     assert_eq!(Option::Some(Color::BLACK), collision_g7);
 }
+
+#[test]
+fn non_pawn_material() {
+    let mut board = game_state::GameState::new_empty();
+    board.set_piece(Piece::KING, Color::WHITE, Position::new(5, 1));
+    board.set_piece(Piece::PAWN, Color::WHITE, Position::new(5, 2));
+    board.set_piece(Piece::KING, Color::BLACK, Position::new(5, 8));
+    board.set_piece(Piece::KNIGHT, Color::BLACK, Position::new(2, 8));
+
+    assert!(!board.has_non_pawn_material(Color::WHITE));
+    assert!(board.has_non_pawn_material(Color::BLACK));
+    assert!(game_state::GameState::new().has_non_pawn_material(Color::WHITE));
+}
+
+#[test]
+fn undo_stack_restores_a_sequence_of_moves() {
+    let move_generator = MoveGenerator::new();
+    let initial = game_state::GameState::new();
+    let mut board = initial;
+    let mut played = vec![];
+
+    // plays the first legal move until the game ends or the undo stack has wrapped around
+    for _ in 0..300 {
+        match move_generator.generate_moves(&board).moves.first() {
+            Some(m) => {
+                board.apply_move_mut(*m);
+                played.push((*m, board));
+            }
+            None => break,
+        }
+    }
+    let recent = played.len().saturating_sub(200);
+
+    for (m, after) in played[recent..].iter().rev() {
+        assert_eq!(*after, board);
+        board.unapply_move_mut(*m);
+    }
+    if recent == 0 {
+        assert_eq!(initial, board);
+    }
+}
+
+#[test]
+fn starting_position_is_valid() {
+    assert_eq!(Ok(()), game_state::GameState::new().validate());
+}
+
+#[test]
+fn validate_reports_every_error() {
+    let mut board = game_state::GameState::new_empty();
+    board.set_piece(Piece::KING, Color::WHITE, Position::new(5, 1));
+    board.set_piece(Piece::KING, Color::WHITE, Position::new(1, 1));
+    board.set_piece(Piece::PAWN, Color::BLACK, Position::new(4, 1));
+    board.set_piece(Piece::ROOK, Color::BLACK, Position::new(5, 1));
+    board.set_en_passant(Some(Position::new(3, 5)));
+
+    let errors = board.validate().unwrap_err();
+
+    assert_eq!(
+        vec![
+            PositionError::OverlappingPieces(Position::new(5, 1)),
+            PositionError::MultipleKings(Color::WHITE),
+            PositionError::MissingKing(Color::BLACK),
+            PositionError::PawnOnBackRank(Position::new(4, 1)),
+            PositionError::InvalidCastlingRights {
+                color: Color::WHITE,
+                king_side: true
+            },
+            PositionError::InvalidCastlingRights {
+                color: Color::WHITE,
+                king_side: false
+            },
+            PositionError::InvalidCastlingRights {
+                color: Color::BLACK,
+                king_side: true
+            },
+            PositionError::InvalidCastlingRights {
+                color: Color::BLACK,
+                king_side: false
+            },
+            PositionError::InvalidEnPassant(Position::new(3, 5)),
+        ],
+        errors
+    );
+}
+
+#[test]
+fn validate_reports_check_of_the_side_not_to_move() {
+    let mut board = game_state::GameState::new_empty();
+    board.castling_rights.white_king_side = false;
+    board.castling_rights.white_queen_side = false;
+    board.castling_rights.black_king_side = false;
+    board.castling_rights.black_queen_side = false;
+    board.set_piece(Piece::KING, Color::WHITE, Position::new(5, 1));
+    board.set_piece(Piece::KING, Color::BLACK, Position::new(5, 8));
+    board.set_piece(Piece::ROOK, Color::WHITE, Position::new(5, 4));
+
+    board.to_move = Color::BLACK;
+    assert_eq!(Ok(()), board.validate());
+
+    board.to_move = Color::WHITE;
+    assert_eq!(Err(vec![PositionError::OpponentInCheck]), board.validate());
+}
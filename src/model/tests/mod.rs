@@ -1,6 +1,16 @@
 mod attack_trace_tests;
+mod chess960_tests;
+mod endgame_tests;
+mod game_state_builder_tests;
 mod game_state_tests;
 mod move_generator_tests;
 mod move_sequence_tests;
+#[cfg(feature = "nnue")]
+mod nnue_tests;
+mod packed_move_tests;
+mod perft_tests;
 mod position_tests;
+#[cfg(feature = "syzygy")]
+mod syzygy_tests;
+mod variant_tests;
 mod zobrist_hash_tests;
@@ -1,9 +1,11 @@
 #[cfg(test)]
-use crate::model::game_state::{CastlingRights, GameState, Move, MoveType, Piece, Position};
+use crate::model::game_state::{Color, GameState, Move, MoveType, Piece, Position};
 #[cfg(test)]
 use crate::model::move_generator::MoveGenerator;
 #[cfg(test)]
 use crate::search::test_utils;
+#[cfg(test)]
+use crate::uci::uci_utils::parse_move;
 
 #[test]
 fn test_moves_for_scandinavian_opening_sequence() {
@@ -13,8 +15,6 @@ fn test_moves_for_scandinavian_opening_sequence() {
         move_type: MoveType::Step,
         moving_piece: Piece::PAWN,
         promotes_to: None,
-        last_en_passant: None,
-        last_castling_rights: CastlingRights::initial(),
     };
     let d5 = Move {
         from: Position::new(4, 7),
@@ -22,8 +22,6 @@ fn test_moves_for_scandinavian_opening_sequence() {
         move_type: MoveType::Step,
         moving_piece: Piece::PAWN,
         promotes_to: None,
-        last_en_passant: Some(Position::new(5, 4)),
-        last_castling_rights: CastlingRights::initial(),
     };
 
     let exd5 = Move {
@@ -32,8 +30,6 @@ fn test_moves_for_scandinavian_opening_sequence() {
         move_type: MoveType::Capture(Piece::PAWN),
         moving_piece: Piece::PAWN,
         promotes_to: None,
-        last_en_passant: Some(Position::new(4, 5)),
-        last_castling_rights: CastlingRights::initial(),
     };
 
     let qxd5 = Move {
@@ -42,8 +38,6 @@ fn test_moves_for_scandinavian_opening_sequence() {
         move_type: MoveType::Capture(Piece::PAWN),
         moving_piece: Piece::QUEEN,
         promotes_to: None,
-        last_en_passant: None,
-        last_castling_rights: CastlingRights::initial(),
     };
 
     let mut game_state = GameState::new();
@@ -79,8 +73,6 @@ fn test_opening_sequence_with_en_passant() {
         move_type: MoveType::Step,
         moving_piece: Piece::PAWN,
         promotes_to: None,
-        last_en_passant: None,
-        last_castling_rights: CastlingRights::initial(),
     };
 
     let nf6 = Move {
@@ -89,8 +81,6 @@ fn test_opening_sequence_with_en_passant() {
         move_type: MoveType::Step,
         moving_piece: Piece::KNIGHT,
         promotes_to: None,
-        last_en_passant: Some(Position::new(5, 4)),
-        last_castling_rights: CastlingRights::initial(),
     };
 
     let e5 = Move {
@@ -99,8 +89,6 @@ fn test_opening_sequence_with_en_passant() {
         move_type: MoveType::Step,
         moving_piece: Piece::PAWN,
         promotes_to: None,
-        last_en_passant: None,
-        last_castling_rights: CastlingRights::initial(),
     };
 
     let d5 = Move {
@@ -109,8 +97,6 @@ fn test_opening_sequence_with_en_passant() {
         move_type: MoveType::Step,
         moving_piece: Piece::PAWN,
         promotes_to: None,
-        last_en_passant: None,
-        last_castling_rights: CastlingRights::initial(),
     };
 
     let exd5 = Move {
@@ -119,8 +105,6 @@ fn test_opening_sequence_with_en_passant() {
         move_type: MoveType::EnPassant,
         moving_piece: Piece::PAWN,
         promotes_to: None,
-        last_en_passant: Some(Position::new(4, 5)),
-        last_castling_rights: CastlingRights::initial(),
     };
 
     let mut game_state = GameState::new();
@@ -207,8 +191,9 @@ fn test_castling_move_generation() {
         .into_iter()
         .find(|m| matches!(m.move_type, MoveType::Castling))
         .unwrap();
-    assert_eq!(Position::new(7, 1), white_castling_king_side.to);
+    assert_eq!(Position::new(8, 1), white_castling_king_side.to);
     assert_eq!(Position::new(5, 1), white_castling_king_side.from);
+    assert_eq!(Position::new(7, 1), white_castling_king_side.destination());
 
     game_state.apply_move_mut(
         move_generator
@@ -222,8 +207,9 @@ fn test_castling_move_generation() {
         .into_iter()
         .find(|m| matches!(m.move_type, MoveType::Castling))
         .unwrap();
-    assert_eq!(Position::new(7, 8), black_castling_king_side.to);
+    assert_eq!(Position::new(8, 8), black_castling_king_side.to);
     assert_eq!(Position::new(5, 8), black_castling_king_side.from);
+    assert_eq!(Position::new(7, 8), black_castling_king_side.destination());
 }
 
 #[test]
@@ -257,3 +243,43 @@ fn test_checkmate_detection_black() {
     let generated_moves = move_generator.generate_moves(&game_state);
     assert!(generated_moves.is_checkmate());
 }
+
+#[test]
+fn find_move_matches_the_promotion_piece() {
+    let mut game_state = GameState::new_empty();
+    game_state.set_piece(Piece::KING, Color::WHITE, Position::new(5, 1));
+    game_state.set_piece(Piece::KING, Color::BLACK, Position::new(5, 8));
+    game_state.set_piece(Piece::PAWN, Color::WHITE, Position::new(1, 7));
+    let move_generator = MoveGenerator::new();
+
+    for (uci_move, piece) in [
+        ("a7a8q", Piece::QUEEN),
+        ("a7a8r", Piece::ROOK),
+        ("a7a8b", Piece::BISHOP),
+        ("a7a8n", Piece::KNIGHT),
+    ]
+    .iter()
+    {
+        let found = move_generator
+            .find_move(&game_state, &parse_move(uci_move).unwrap())
+            .unwrap();
+        assert_eq!(Some(*piece), found.promotes_to);
+    }
+
+    assert_eq!(
+        None,
+        move_generator.find_move(&game_state, &parse_move("a7a8").unwrap())
+    );
+}
+
+#[test]
+fn positions_without_king_do_not_panic() {
+    let mut game_state = GameState::new_empty();
+    game_state.set_piece(Piece::ROOK, Color::WHITE, Position::new(1, 1));
+    let move_generator = MoveGenerator::new();
+
+    let generated = move_generator.generate_moves(&game_state);
+
+    assert_eq!(14, generated.moves.len());
+    assert!(!generated.is_check);
+}
@@ -8,7 +8,7 @@ fn all_moves_from_starting_position_are_revertible() {
 
     let valid_moves = move_generator.generate_moves(&initial_state);
 
-    let mut initial_state_clone = initial_state.clone();
+    let mut initial_state_clone = initial_state;
 
     for next_move in valid_moves.moves {
         assert_eq!(initial_state, initial_state_clone);
@@ -0,0 +1,143 @@
+#[cfg(test)]
+use crate::model::game_state::{Color, GameState, Position};
+#[cfg(test)]
+use crate::model::move_generator::MoveGenerator;
+#[cfg(test)]
+use crate::model::nnue::{Network, NnueEvaluator};
+#[cfg(test)]
+use crate::search::minimax_search::negamax_alpha_beta_with_trasposition_table_and_evaluator;
+#[cfg(test)]
+use crate::search::transposition_table::TranspositionTable;
+#[cfg(test)]
+use crate::uci::uci_utils::parse_move;
+#[cfg(test)]
+use std::sync::Arc;
+
+#[cfg(test)]
+const TINY_NETWORK: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/model/tests/data/tiny.nnue"
+);
+
+#[test]
+fn load_bundled_network() {
+    let network = Network::from_file(TINY_NETWORK).unwrap();
+    assert_eq!(32, network.hidden_size());
+}
+
+#[test]
+fn reject_invalid_network() {
+    let bytes = std::fs::read(TINY_NETWORK).unwrap();
+
+    assert!(Network::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(Network::from_bytes(b"not a network file").is_err());
+    assert!(Network::from_file("does/not/exist.nnue").is_err());
+}
+
+#[test]
+fn reject_invalid_output_scale() {
+    let mut bytes = std::fs::read(TINY_NETWORK).unwrap();
+
+    for scale in [0, -1, i32::MAX].iter() {
+        bytes[12..16].copy_from_slice(&scale.to_le_bytes());
+        assert!(Network::from_bytes(&bytes).is_err());
+    }
+}
+
+#[test]
+fn large_output_bias_does_not_overflow() {
+    let mut bytes = std::fs::read(TINY_NETWORK).unwrap();
+    let bias_offset = bytes.len() - 4;
+    bytes[12..16].copy_from_slice(&(1i32 << 20).to_le_bytes());
+    bytes[bias_offset..].copy_from_slice(&i32::MAX.to_le_bytes());
+
+    let network = Arc::new(Network::from_bytes(&bytes).unwrap());
+    let game_state = GameState::new();
+    let evaluator = NnueEvaluator::new(network, &game_state);
+
+    assert!(evaluator.evaluate(Color::WHITE) > 0);
+}
+
+#[test]
+fn starting_position_is_balanced() {
+    let network = Arc::new(Network::from_file(TINY_NETWORK).unwrap());
+    let game_state = GameState::new();
+    let evaluator = NnueEvaluator::new(network, &game_state);
+
+    assert_eq!(0, evaluator.evaluate(Color::WHITE));
+    assert_eq!(0, evaluator.evaluate(Color::BLACK));
+}
+
+#[test]
+fn incremental_update_matches_refresh() {
+    let network = Arc::new(Network::from_file(TINY_NETWORK).unwrap());
+    let move_generator = MoveGenerator::new();
+    let mut game_state = GameState::new();
+    let mut evaluator = NnueEvaluator::new(network.clone(), &game_state);
+
+    // covers castling, captures, en passant and an underpromotion with capture
+    let moves = [
+        "e2e4", "d7d5", "e4e5", "f7f5", "e5f6", "g8h6", "f6g7", "e8f7", "g7h8n", "f7e6", "g1f3",
+        "b8c6", "f1e2", "d8d6", "e1g1", "d5d4", "b1c3", "d4c3", "d2d3", "c3b2", "c1b2",
+    ];
+
+    for m in moves.iter() {
+        let uci_move = parse_move(m).unwrap();
+        let to_apply = move_generator.find_move(&game_state, &uci_move).unwrap();
+
+        evaluator.apply_move_mut(game_state.to_move(), to_apply);
+        game_state.apply_move_mut(to_apply);
+
+        let refreshed = NnueEvaluator::new(network.clone(), &game_state);
+        assert_eq!(
+            refreshed.evaluate(game_state.to_move()),
+            evaluator.evaluate(game_state.to_move())
+        );
+    }
+}
+
+#[test]
+fn apply_and_unapply_restores_evaluation() {
+    let network = Arc::new(Network::from_file(TINY_NETWORK).unwrap());
+    let move_generator = MoveGenerator::new();
+    let mut game_state = GameState::new();
+    let mut evaluator = NnueEvaluator::new(network, &game_state);
+    let initial_eval = evaluator.evaluate(Color::WHITE);
+
+    for m in move_generator.generate_moves(&game_state).moves {
+        evaluator.apply_move_mut(game_state.to_move(), m);
+        game_state.apply_move_mut(m);
+        game_state.unapply_move_mut(m);
+        evaluator.unapply_move_mut();
+
+        assert_eq!(initial_eval, evaluator.evaluate(Color::WHITE));
+    }
+}
+
+#[test]
+fn search_with_network_finds_fools_mate() {
+    let network = Arc::new(Network::from_file(TINY_NETWORK).unwrap());
+    let move_generator = MoveGenerator::new();
+    let mut game_state = GameState::new();
+    let mut transposition_table = TranspositionTable::with_capacity(10_000);
+
+    for m in ["f2f3", "e7e6", "g2g4"].iter() {
+        let uci_move = parse_move(m).unwrap();
+        let to_apply = move_generator
+            .get_move(&game_state, uci_move.0, uci_move.1)
+            .unwrap();
+        game_state.apply_move_mut(to_apply);
+    }
+
+    let mut evaluator = NnueEvaluator::new(network, &game_state);
+
+    let (best_move, _, _) = negamax_alpha_beta_with_trasposition_table_and_evaluator(
+        &mut game_state,
+        &move_generator,
+        &mut transposition_table,
+        &mut evaluator,
+        3,
+    );
+
+    assert_eq!(Position::new(8, 4), best_move.unwrap().to);
+}
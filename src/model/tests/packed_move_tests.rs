@@ -0,0 +1,73 @@
+#[cfg(test)]
+use crate::model::game_state::{GameState, PackedMove, Piece, Position};
+#[cfg(test)]
+use crate::model::move_generator::MoveGenerator;
+#[cfg(test)]
+use crate::notation::fen::parse_fen;
+
+#[cfg(test)]
+fn positions() -> Vec<GameState> {
+    [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/pppq1ppp/2n2n2/3pp3/1b1PP3/2N2N2/PPPQ1PPP/R3K2R w KQkq - 0 1",
+        "4k3/1P6/8/2pP4/8/8/6p1/4K2R w K c6 0 1",
+        "4k3/1P6/8/8/8/8/6p1/4K2R b K - 0 1",
+    ]
+    .iter()
+    .map(|fen| parse_fen(fen).unwrap())
+    .collect()
+}
+
+#[cfg(test)]
+fn packed(from: &str, to: &str) -> PackedMove {
+    PackedMove::new(from.parse().unwrap(), to.parse().unwrap(), None)
+}
+
+#[test]
+fn legal_moves_survive_packing() {
+    let move_generator = MoveGenerator::new();
+
+    for game_state in positions().iter() {
+        for m in move_generator.generate_moves(game_state).moves {
+            assert_eq!(Some(m), m.pack().unpack(game_state), "{}", m);
+            assert_eq!(m.pack(), PackedMove::from_bits(m.pack().bits()));
+        }
+    }
+}
+
+#[test]
+fn packed_moves_print_as_uci() {
+    let promotion = PackedMove::new(
+        Position::new(2, 7),
+        Position::new(2, 8),
+        Some(Piece::KNIGHT),
+    );
+
+    assert_eq!(promotion.to_string(), "b7b8n");
+    assert_eq!(promotion.promotes_to(), Some(Piece::KNIGHT));
+    assert_eq!(packed("e2", "e4").to_string(), "e2e4");
+}
+
+#[test]
+fn moves_display_in_uci_form() {
+    let game_state = parse_fen("4k3/1P6/8/8/8/8/p7/4K2R w K - 0 1").unwrap();
+    let printed: Vec<String> = MoveGenerator::new()
+        .generate_moves(&game_state)
+        .moves
+        .iter()
+        .map(|m| m.to_string())
+        .collect();
+
+    assert!(printed.contains(&"b7b8q".to_string()));
+    assert!(printed.contains(&"e1g1".to_string()));
+    assert!(printed.contains(&"h1h8".to_string()));
+}
+
+#[test]
+fn unpacking_requires_a_piece_of_the_side_to_move() {
+    let game_state = GameState::new();
+
+    assert_eq!(None, packed("e7", "e5").unpack(&game_state));
+    assert_eq!(None, packed("e4", "e5").unpack(&game_state));
+    assert_eq!(None, packed("d1", "e1").unpack(&game_state));
+}
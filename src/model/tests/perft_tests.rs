@@ -0,0 +1,100 @@
+#[cfg(test)]
+use crate::model::game_state::GameState;
+#[cfg(test)]
+use crate::model::move_generator::MoveGenerator;
+#[cfg(test)]
+use crate::model::zobrist_hash;
+#[cfg(test)]
+use crate::notation::fen::parse_fen;
+
+// counts the leaf nodes of the move tree, checking that every move is unapplied exactly
+#[cfg(test)]
+fn perft(move_generator: &MoveGenerator, game_state: &mut GameState, depth: u32) -> u64 {
+    let moves = move_generator.generate_moves(game_state).moves;
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    let mut nodes = 0;
+    for m in moves {
+        let before = *game_state;
+        game_state.apply_move_mut(m);
+        nodes += perft(move_generator, game_state, depth - 1);
+        game_state.unapply_move_mut(m);
+        assert_eq!(before, *game_state, "{}", m);
+    }
+    nodes
+}
+
+#[cfg(test)]
+fn assert_perft(fen: &str, expected: &[u64]) {
+    let move_generator = MoveGenerator::new();
+    let mut game_state = parse_fen(fen).unwrap();
+
+    for (depth, nodes) in expected.iter().enumerate() {
+        assert_eq!(
+            *nodes,
+            perft(&move_generator, &mut game_state, depth as u32 + 1),
+            "{} depth {}",
+            fen,
+            depth + 1
+        );
+    }
+}
+
+#[test]
+fn perft_with_castling_and_en_passant() {
+    assert_perft(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        &[48, 2039],
+    );
+    assert_perft(
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        &[14, 191, 2812],
+    );
+}
+
+#[test]
+fn perft_chess960() {
+    assert_perft(
+        "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        &[21, 528, 12189],
+    );
+    assert_perft(
+        "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9",
+        &[21, 807],
+    );
+    assert_perft(
+        "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9",
+        &[20, 479, 10471],
+    );
+}
+
+#[test]
+fn castling_keeps_the_hash_up_to_date() {
+    let move_generator = MoveGenerator::new();
+
+    for fen in [
+        "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+        "rk4r1/8/8/8/8/8/8/RK4R1 w GAga - 0 1",
+    ]
+    .iter()
+    {
+        let game_state = parse_fen(fen).unwrap();
+        for m in move_generator.generate_moves(&game_state).moves {
+            let next = game_state.apply_move(m);
+            assert_eq!(zobrist_hash::hash(&next), next.zobrist_hash, "{}", m);
+
+            for reply in move_generator.generate_moves(&next).moves {
+                let after_reply = next.apply_move(reply);
+                assert_eq!(
+                    zobrist_hash::hash(&after_reply),
+                    after_reply.zobrist_hash,
+                    "{} {}",
+                    m,
+                    reply
+                );
+            }
+        }
+    }
+}
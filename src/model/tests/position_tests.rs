@@ -1,4 +1,6 @@
 #[cfg(test)]
+use crate::error::ChessError;
+#[cfg(test)]
 use crate::model::game_state::Position;
 
 #[test]
@@ -87,3 +89,21 @@ fn delta() {
     assert_eq!(Option::None, a1.delta(2, -1));
     assert_eq!(Option::Some(Position::new(2, 3)), a1.delta(1, 2));
 }
+
+#[test]
+fn try_new_rejects_squares_outside_the_board() {
+    assert_eq!(Ok(Position::new(8, 8)), Position::try_new(8, 8));
+    assert!(Position::try_new(0, 1).is_err());
+    assert!(Position::try_new(1, 9).is_err());
+}
+
+#[test]
+fn from_str() {
+    assert_eq!(Ok(Position::new(5, 4)), "e4".parse());
+    for invalid in ["", "e", "e9", "i1", "E4", "e44"].iter() {
+        assert_eq!(
+            Err(ChessError::InvalidSquare(invalid.to_string())),
+            invalid.parse::<Position>()
+        );
+    }
+}
@@ -0,0 +1,162 @@
+#[cfg(test)]
+use crate::model::move_generator::MoveGenerator;
+#[cfg(test)]
+use crate::model::syzygy::{Tablebase, Wdl};
+#[cfg(test)]
+use crate::notation::fen::parse_fen;
+#[cfg(test)]
+use crate::search::score::{Score, TABLEBASE_BOUND};
+#[cfg(test)]
+use crate::search::searcher::{SearchLimits, Searcher};
+#[cfg(test)]
+use std::sync::Arc;
+
+#[cfg(test)]
+const TABLE_DIRECTORY: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/model/tests/data/syzygy");
+
+// The 3 and 4 piece tables of the test data, or those of the SYZYGY_PATH environment variable.
+// The test data holds KvK, KNvK, KBvK, KRvK, KQvK, KPvK and KRvKR.
+#[cfg(test)]
+fn tablebase() -> Tablebase {
+    let paths = std::env::var("SYZYGY_PATH").unwrap_or_else(|_| TABLE_DIRECTORY.to_string());
+    let tablebase = Tablebase::open(&paths).unwrap();
+    assert!(tablebase.table_count() > 0, "No Syzygy tables in {}", paths);
+    tablebase
+}
+
+#[cfg(test)]
+fn wdl(tablebase: &Tablebase, fen: &str) -> Option<Wdl> {
+    tablebase.probe_wdl(&parse_fen(fen).unwrap())
+}
+
+#[test]
+fn missing_directory_is_reported() {
+    assert!(Tablebase::open("does/not/exist").is_err());
+}
+
+#[test]
+fn nothing_is_probed_without_tables() {
+    let tablebase =
+        Tablebase::open(concat!(env!("CARGO_MANIFEST_DIR"), "/src/model/tests/data")).unwrap();
+    let game_state = parse_fen("8/8/8/4k3/8/8/8/KQ6 w - - 0 1").unwrap();
+
+    assert_eq!(0, tablebase.table_count());
+    assert_eq!(0, tablebase.max_pieces());
+    assert_eq!(None, tablebase.probe_wdl(&game_state));
+    assert_eq!(None, tablebase.probe_dtz(&game_state));
+    assert_eq!(None, tablebase.best_root_moves(&game_state));
+}
+
+#[test]
+fn probe_wdl() {
+    let tablebase = tablebase();
+
+    assert_eq!(
+        Some(Wdl::Draw),
+        wdl(&tablebase, "8/8/8/4k3/8/8/8/K7 w - - 0 1")
+    );
+    assert_eq!(
+        Some(Wdl::Win),
+        wdl(&tablebase, "8/8/8/4k3/8/8/8/KQ6 w - - 0 1")
+    );
+    assert_eq!(
+        Some(Wdl::Loss),
+        wdl(&tablebase, "8/8/8/4k3/8/8/8/KQ6 b - - 0 1")
+    );
+    assert_eq!(
+        Some(Wdl::Win),
+        wdl(&tablebase, "8/8/8/8/8/8/4P3/4K2k w - - 0 1")
+    );
+    assert_eq!(
+        Some(Wdl::Draw),
+        wdl(&tablebase, "k7/8/8/8/8/8/P7/7K w - - 0 1")
+    );
+    assert_eq!(
+        Some(Wdl::Draw),
+        wdl(&tablebase, "8/8/4k3/3r4/8/3R4/4K3/8 w - - 0 1")
+    );
+    assert_eq!(
+        Some(Wdl::Draw),
+        wdl(&tablebase, "8/8/4k3/3r4/8/3R4/4K3/8 b - - 0 1")
+    );
+    assert_eq!(
+        Some(Wdl::Win),
+        wdl(&tablebase, "4k3/8/8/8/8/8/4K3/r6R w - - 0 1")
+    );
+}
+
+#[test]
+fn probe_dtz() {
+    let tablebase = tablebase();
+    let dtz = |fen: &str| tablebase.probe_dtz(&parse_fen(fen).unwrap());
+
+    assert_eq!(Some(0), dtz("8/8/8/4k3/8/8/8/K7 w - - 0 1"));
+    assert_eq!(Some(29), dtz("8/8/8/4k3/8/8/8/KR6 w - - 0 1"));
+    assert_eq!(Some(-30), dtz("8/8/8/4k3/8/8/8/KR6 b - - 0 1"));
+    assert_eq!(Some(29), dtz("8/8/8/4K3/8/8/8/kr6 b - - 0 1"));
+    assert_eq!(Some(-1), dtz("k7/1Q6/1K6/8/8/8/8/8 b - - 0 1"));
+    assert_eq!(Some(1), dtz("8/8/8/8/8/8/4P3/4K2k w - - 0 1"));
+    assert_eq!(Some(1), dtz("4k3/8/8/8/8/8/4K3/r6R b - - 0 1"));
+}
+
+#[test]
+fn probe_wdl_with_black_as_the_stronger_side() {
+    let tablebase = tablebase();
+
+    assert_eq!(
+        Some(Wdl::Loss),
+        wdl(&tablebase, "8/8/8/4K3/8/8/8/kq6 w - - 0 1")
+    );
+    assert_eq!(
+        Some(Wdl::Win),
+        wdl(&tablebase, "4k2K/4p3/8/8/8/8/8/8 b - - 0 1")
+    );
+    assert_eq!(
+        Some(Wdl::Draw),
+        wdl(&tablebase, "7k/p7/8/8/8/8/8/K7 b - - 0 1")
+    );
+}
+
+#[test]
+fn positions_with_castling_rights_are_not_probed() {
+    let tablebase = tablebase();
+
+    assert_eq!(None, wdl(&tablebase, "r3k3/8/8/8/8/8/8/4K3 b q - 0 1"));
+}
+
+#[test]
+fn mate_in_one_is_the_best_root_move() {
+    let tablebase = tablebase();
+    let move_generator = MoveGenerator::new();
+    let game_state = parse_fen("k7/7Q/1K6/8/8/8/8/8 w - - 0 1").unwrap();
+
+    assert_eq!(Some(1), tablebase.probe_dtz(&game_state));
+
+    let best_moves = tablebase.best_root_moves(&game_state).unwrap();
+    assert!(!best_moves.is_empty());
+    for m in best_moves {
+        let next = game_state.apply_move(m);
+        assert!(move_generator.generate_moves(&next).is_checkmate());
+    }
+}
+
+#[test]
+fn searcher_keeps_the_tablebase_result() {
+    let tablebase = Arc::new(tablebase());
+    let mut game_state = parse_fen("8/8/8/4k3/8/8/8/KR6 w - - 0 1").unwrap();
+    let best_moves = tablebase.best_root_moves(&game_state).unwrap();
+
+    let outcome = Searcher::new()
+        .with_tablebase(Some(tablebase))
+        .with_limits(SearchLimits {
+            depth: Some(3),
+            ..SearchLimits::default()
+        })
+        .search(&mut game_state);
+
+    assert!(best_moves.contains(&outcome.best_move.unwrap()));
+    assert!(matches!(
+        outcome.score,
+        Score::Evaluation(eval) if eval >= TABLEBASE_BOUND
+    ));
+}
@@ -0,0 +1,169 @@
+#[cfg(test)]
+use crate::error::ChessError;
+#[cfg(test)]
+use crate::model::evaluator;
+#[cfg(test)]
+use crate::model::game_state::{Color, GameState};
+#[cfg(test)]
+use crate::model::game_state_builder::GameStateBuilder;
+#[cfg(test)]
+use crate::model::move_generator::MoveGenerator;
+#[cfg(test)]
+use crate::model::variant::Variant;
+#[cfg(test)]
+use crate::model::zobrist_hash;
+#[cfg(test)]
+use crate::notation::fen::parse_fen;
+#[cfg(test)]
+use crate::search::score::Score;
+#[cfg(test)]
+use crate::search::searcher::{SearchLimits, Searcher};
+
+#[cfg(test)]
+fn variant_position(fen: &str, variant: Variant) -> GameStateBuilder {
+    GameStateBuilder::from(&parse_fen(fen).unwrap()).with_variant(variant)
+}
+
+#[cfg(test)]
+fn search(game_state: &mut GameState, depth: u16) -> (String, Score) {
+    let outcome = Searcher::new()
+        .with_limits(SearchLimits {
+            depth: Some(depth),
+            ..SearchLimits::default()
+        })
+        .search(game_state);
+    (outcome.best_move.unwrap().to_string(), outcome.score)
+}
+
+#[test]
+fn variants_are_named_like_uci_variant() {
+    for variant in Variant::ALL.iter() {
+        assert_eq!(Ok(*variant), variant.name().parse());
+    }
+    assert_eq!(Ok(Variant::ThreeCheck), "3Check".parse());
+    assert_eq!(
+        Err(ChessError::UnknownVariant("atomic".to_string())),
+        "atomic".parse::<Variant>()
+    );
+}
+
+#[test]
+fn king_on_the_hill_wins() {
+    let move_generator = MoveGenerator::new();
+    let game_state = variant_position("4k3/8/8/8/8/4K3/8/8 w - - 0 1", Variant::KingOfTheHill)
+        .build()
+        .unwrap();
+    assert_eq!(None, game_state.variant().winner(&game_state));
+    assert!(!game_state.is_insufficient_material());
+
+    let on_the_hill = game_state.apply_move(
+        move_generator
+            .find_move(&game_state, &"e3e4".parse().unwrap())
+            .unwrap(),
+    );
+
+    assert_eq!(
+        Some(Color::WHITE),
+        on_the_hill.variant().winner(&on_the_hill)
+    );
+    assert!(move_generator.generate_moves(&on_the_hill).moves.is_empty());
+}
+
+#[test]
+fn search_walks_the_king_to_the_hill() {
+    let mut game_state = variant_position("4k3/8/8/8/8/4K3/8/8 w - - 0 1", Variant::KingOfTheHill)
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        ("e3e4".to_string(), Score::MateIn(1)),
+        search(&mut game_state, 2)
+    );
+}
+
+#[test]
+fn king_of_the_hill_rewards_central_kings() {
+    let central = variant_position("4k3/8/8/8/8/4K3/8/8 w - - 0 1", Variant::KingOfTheHill)
+        .build()
+        .unwrap();
+    let standard = parse_fen("4k3/8/8/8/8/4K3/8/8 w - - 0 1").unwrap();
+
+    assert!(evaluator::evaluate(&central) > evaluator::evaluate(&standard));
+}
+
+#[test]
+fn checks_are_counted_and_hashed() {
+    let move_generator = MoveGenerator::new();
+    let mut game_state = variant_position("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", Variant::ThreeCheck)
+        .build()
+        .unwrap();
+    let before = game_state;
+
+    let check = move_generator
+        .find_move(&game_state, &"a1a8".parse().unwrap())
+        .unwrap();
+    game_state.apply_move_mut(check);
+
+    assert_eq!(1, game_state.checks_given(Color::WHITE));
+    assert_eq!(0, game_state.checks_given(Color::BLACK));
+    assert_eq!(zobrist_hash::hash(&game_state), game_state.zobrist_hash);
+    assert_ne!(
+        game_state.zobrist_hash,
+        GameStateBuilder::from(&game_state)
+            .with_checks_given(Color::WHITE, 0)
+            .build()
+            .unwrap()
+            .zobrist_hash
+    );
+
+    game_state.unapply_move_mut(check);
+    assert_eq!(before, game_state);
+    assert_eq!(0, game_state.checks_given(Color::WHITE));
+}
+
+#[test]
+fn third_check_wins() {
+    let mut game_state = variant_position("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", Variant::ThreeCheck)
+        .with_checks_given(Color::WHITE, 2)
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        ("a1a8".to_string(), Score::MateIn(1)),
+        search(&mut game_state, 2)
+    );
+
+    let next = game_state.apply_move(
+        MoveGenerator::new()
+            .find_move(&game_state, &"a1a8".parse().unwrap())
+            .unwrap(),
+    );
+    assert_eq!(Some(Color::WHITE), next.variant().winner(&next));
+}
+
+#[test]
+fn standard_chess_ignores_checks() {
+    let move_generator = MoveGenerator::new();
+    let game_state = parse_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+
+    let next = game_state.apply_move(
+        move_generator
+            .find_move(&game_state, &"a1a8".parse().unwrap())
+            .unwrap(),
+    );
+
+    assert_eq!(0, next.checks_given(Color::WHITE));
+    assert_eq!(None, next.variant().winner(&next));
+}
+
+// bare kings would be scaled down as a draw in standard chess, the kings themselves evaluate equal
+#[test]
+fn king_of_the_hill_bonus_is_not_scaled_with_bare_kings() {
+    let game_state = variant_position("4k3/8/8/8/8/4K3/8/8 w - - 0 1", Variant::KingOfTheHill)
+        .build()
+        .unwrap();
+    let hill_bonus = Variant::KingOfTheHill.evaluate(&game_state);
+
+    assert!(hill_bonus > 0);
+    assert_eq!(hill_bonus, evaluator::evaluate(&game_state));
+}
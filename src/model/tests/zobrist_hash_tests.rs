@@ -1,7 +1,7 @@
 #[cfg(test)]
-use crate::model::game_state::{CastlingRights, Move, MoveType, Piece, Position};
+use crate::model::game_state::{Move, MoveType, Piece, Position};
 #[cfg(test)]
-use crate::model::{game_state, move_generator};
+use crate::model::{game_state, move_generator, zobrist_hash};
 
 #[test]
 fn zobrish_hash_is_reversible_from_starting_position() {
@@ -10,7 +10,7 @@ fn zobrish_hash_is_reversible_from_starting_position() {
 
     let valid_moves = move_generator.generate_moves(&initial_state);
 
-    let mut initial_state_clone = initial_state.clone();
+    let mut initial_state_clone = initial_state;
 
     for next_move in valid_moves.moves {
         assert_eq!(initial_state.zobrist_hash, initial_state_clone.zobrist_hash);
@@ -30,8 +30,6 @@ fn zobrist_hash_is_reversible_in_scandinavian_starting_sequence() {
             move_type: MoveType::Step,
             moving_piece: Piece::PAWN,
             promotes_to: None,
-            last_en_passant: None,
-            last_castling_rights: CastlingRights::initial(),
         },
         Move {
             from: Position::new(4, 7),
@@ -39,8 +37,6 @@ fn zobrist_hash_is_reversible_in_scandinavian_starting_sequence() {
             move_type: MoveType::Step,
             moving_piece: Piece::PAWN,
             promotes_to: None,
-            last_en_passant: Some(Position::new(5, 4)),
-            last_castling_rights: CastlingRights::initial(),
         },
         Move {
             from: Position::new(5, 4),
@@ -48,8 +44,6 @@ fn zobrist_hash_is_reversible_in_scandinavian_starting_sequence() {
             move_type: MoveType::Capture(Piece::PAWN),
             moving_piece: Piece::PAWN,
             promotes_to: None,
-            last_en_passant: Some(Position::new(4, 5)),
-            last_castling_rights: CastlingRights::initial(),
         },
         Move {
             from: Position::new(4, 8),
@@ -57,8 +51,6 @@ fn zobrist_hash_is_reversible_in_scandinavian_starting_sequence() {
             move_type: MoveType::Capture(Piece::PAWN),
             moving_piece: Piece::QUEEN,
             promotes_to: None,
-            last_en_passant: None,
-            last_castling_rights: CastlingRights::initial(),
         },
     ];
 
@@ -77,3 +69,25 @@ fn zobrist_hash_is_reversible_in_scandinavian_starting_sequence() {
         state.apply_move_mut(*m);
     }
 }
+
+#[test]
+fn null_move_clears_en_passant_and_is_reversible() {
+    let move_generator = move_generator::MoveGenerator::new();
+    let mut state = game_state::GameState::new();
+    let double_step = move_generator
+        .get_move(&state, Position::new(5, 2), Position::new(5, 4))
+        .unwrap();
+    state.apply_move_mut(double_step);
+    let before = state;
+
+    state.apply_null_move_mut();
+
+    assert_eq!(Some(Position::new(5, 4)), before.en_passant());
+    assert_eq!(None, state.en_passant());
+    assert_eq!(before.to_move().opposite(), state.to_move());
+    assert_eq!(zobrist_hash::hash(&state), state.zobrist_hash);
+
+    state.unapply_null_move_mut();
+
+    assert_eq!(before, state);
+}
@@ -0,0 +1,114 @@
+use super::game_state::{bit_mask_to_positions, Color, GameState, Piece};
+use crate::error::ChessError;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+// d4, e4, d5 and e5
+const HILL: u64 = 0x0000_0018_1800_0000;
+
+// evaluation of a king by its distance to the hill, it wins on the hill itself
+const HILL_DISTANCE_VALUE: [i32; 4] = [0, 600, 250, 75];
+
+const CHECKS_TO_WIN: u8 = 3;
+
+// evaluation of the checks given so far, the third one wins
+const CHECKS_GIVEN_VALUE: [i32; 3] = [0, 800, 2500];
+
+// Rules on top of standard chess. Every variant uses the standard move generation, but may end
+// the game before checkmate and adds its own terms to the evaluation.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Variant {
+    Standard,
+    // moving the king onto one of the four center squares wins
+    KingOfTheHill,
+    // giving check for the third time wins
+    ThreeCheck,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 3] = [
+        Variant::Standard,
+        Variant::KingOfTheHill,
+        Variant::ThreeCheck,
+    ];
+
+    // the name used by UCI_Variant
+    pub fn name(&self) -> &'static str {
+        match self {
+            Variant::Standard => "chess",
+            Variant::KingOfTheHill => "kingofthehill",
+            Variant::ThreeCheck => "3check",
+        }
+    }
+
+    // the side that has won by the rules of the variant, checkmate is left to the move generator
+    pub fn winner(&self, game_state: &GameState) -> Option<Color> {
+        let colors = [Color::WHITE, Color::BLACK];
+        match self {
+            Variant::Standard => None,
+            Variant::KingOfTheHill => colors
+                .iter()
+                .copied()
+                .find(|color| game_state.get_piece_mask(Piece::KING, *color) & HILL != 0),
+            Variant::ThreeCheck => colors
+                .iter()
+                .copied()
+                .find(|color| game_state.checks_given(*color) >= CHECKS_TO_WIN),
+        }
+    }
+
+    // whether giving check counts towards winning the game
+    pub fn counts_checks(&self) -> bool {
+        *self == Variant::ThreeCheck
+    }
+
+    // evaluation of the variant's own goals from the point of view of white
+    pub fn evaluate(&self, game_state: &GameState) -> i32 {
+        let value = |color: Color| match self {
+            Variant::Standard => 0,
+            Variant::KingOfTheHill => {
+                bit_mask_to_positions(*game_state.get_piece_mask(Piece::KING, color))
+                    .iter()
+                    .map(|king| {
+                        let file_distance =
+                            i32::max(4 - i32::from(king.file()), i32::from(king.file()) - 5);
+                        let rank_distance =
+                            i32::max(4 - i32::from(king.rank()), i32::from(king.rank()) - 5);
+                        let distance = i32::max(0, i32::max(file_distance, rank_distance));
+                        HILL_DISTANCE_VALUE[distance as usize]
+                    })
+                    .sum()
+            }
+            Variant::ThreeCheck => {
+                let checks = usize::from(game_state.checks_given(color));
+                CHECKS_GIVEN_VALUE[usize::min(checks, CHECKS_GIVEN_VALUE.len() - 1)]
+            }
+        };
+
+        value(Color::WHITE) - value(Color::BLACK)
+    }
+}
+
+impl Default for Variant {
+    fn default() -> Self {
+        Variant::Standard
+    }
+}
+
+impl Display for Variant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Variant {
+    type Err = ChessError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Variant::ALL
+            .iter()
+            .copied()
+            .find(|variant| variant.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| ChessError::UnknownVariant(name.to_string()))
+    }
+}
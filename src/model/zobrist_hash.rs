@@ -10,6 +10,9 @@ const WHITE_QUEEN_SIDE: usize = 1;
 const BLACK_KING_SIDE: usize = 2;
 const BLACK_QUEEN_SIDE: usize = 3;
 
+// enough for Three-check, which ends with the third check
+const CHECKS_GIVEN_KEYS: usize = 4;
+
 lazy_static! {
     static ref ZOBIRST: ZobristHasher = ZobristHasher::new(ZOBRIST_SEED);
 }
@@ -18,24 +21,46 @@ pub fn hash(game_state: &GameState) -> u64 {
     ZOBIRST.hash(game_state)
 }
 
+// castling rights and en passant square of the position before the move
 pub fn apply_move(
     current_hash: u64,
     castling_rights: &CastlingRights,
+    en_passant: Option<Position>,
     to_apply: Move,
     to_move: Color,
 ) -> u64 {
-    let next_hash = ZOBIRST.apply_move(current_hash, to_apply, to_move);
-    ZOBIRST.apply_castling_rights(next_hash, *castling_rights, to_apply, to_move)
+    let next_hash = ZOBIRST.apply_move(current_hash, en_passant, to_apply, to_move);
+    let next_castling_rights = castling_rights.after_move(&to_apply, to_move);
+    ZOBIRST.change_castling_rights(next_hash, *castling_rights, next_castling_rights)
 }
 
+// castling rights of the position after the move, and those and the en passant square restored
+// by unapplying it
 pub fn unapply_move(
     current_hash: u64,
     castling_rights: &CastlingRights,
+    last_castling_rights: &CastlingRights,
+    last_en_passant: Option<Position>,
     to_unapply: Move,
     to_move: Color,
 ) -> u64 {
-    let next_hash = ZOBIRST.unapply_move(current_hash, to_unapply, to_move);
-    ZOBIRST.unapply_castling_rights(next_hash, *castling_rights, to_unapply, to_move)
+    let next_hash = ZOBIRST.unapply_move(current_hash, last_en_passant, to_unapply, to_move);
+    ZOBIRST.change_castling_rights(next_hash, *castling_rights, *last_castling_rights)
+}
+
+// the checks given by white and black before and after a move
+pub fn change_checks_given(
+    current_hash: u64,
+    checks_given: [u8; 2],
+    next_checks_given: [u8; 2],
+) -> u64 {
+    ZOBIRST.change_checks_given(current_hash, checks_given, next_checks_given)
+}
+
+// passing the turn only changes the side to move and clears the en passant square, so the same
+// update both applies and unapplies a null move
+pub fn null_move(current_hash: u64, en_passant: Option<Position>) -> u64 {
+    ZOBIRST.null_move(current_hash, en_passant)
 }
 
 struct ZobristHasher {
@@ -43,6 +68,8 @@ struct ZobristHasher {
     en_passant: [u64; 64],
     to_move_white: u64,
     castling_rights: [u64; 4],
+    // by color and number of checks given, only the current number is hashed
+    checks_given: [[u64; CHECKS_GIVEN_KEYS]; 2],
 }
 
 impl ZobristHasher {
@@ -53,9 +80,9 @@ impl ZobristHasher {
             pieces: {
                 let mut table = [[0; 12]; 64];
 
-                for i in 0..table.len() {
-                    for j in 0..table[i].len() {
-                        table[i][j] = rng.next_u64();
+                for row in table.iter_mut() {
+                    for entry in row.iter_mut() {
+                        *entry = rng.next_u64();
                     }
                 }
 
@@ -65,8 +92,8 @@ impl ZobristHasher {
             en_passant: {
                 let mut table = [0; 64];
 
-                for i in 0..table.len() {
-                    table[i] = rng.next_u64();
+                for entry in table.iter_mut() {
+                    *entry = rng.next_u64();
                 }
 
                 table
@@ -75,14 +102,27 @@ impl ZobristHasher {
             castling_rights: {
                 let mut table = [0; 4];
 
-                for i in 0..table.len() {
-                    table[i] = rng.next_u64();
+                for entry in table.iter_mut() {
+                    *entry = rng.next_u64();
                 }
 
                 table
             },
 
             to_move_white: rng.next_u64(),
+
+            // no checks given leaves the hash as in standard chess
+            checks_given: {
+                let mut table = [[0; CHECKS_GIVEN_KEYS]; 2];
+
+                for row in table.iter_mut() {
+                    for entry in row.iter_mut().skip(1) {
+                        *entry = rng.next_u64();
+                    }
+                }
+
+                table
+            },
         }
     }
 
@@ -95,34 +135,47 @@ impl ZobristHasher {
                 .map(|piece| zobrist_index_for_piece(piece.0, piece.1));
 
             if let Some(piece_index) = maybe_piece_index {
-                hash = hash ^ self.pieces[usize::from(i)][piece_index];
+                hash ^= self.pieces[usize::from(i)][piece_index];
             }
         }
 
         if let Some(en_passant_square) = game_state.en_passant() {
-            hash = hash ^ self.en_passant[usize::from(en_passant_square.to_numeric())];
+            hash ^= self.en_passant[usize::from(en_passant_square.to_numeric())];
         }
 
         if game_state.to_move() == Color::WHITE {
-            hash = hash ^ self.to_move_white;
+            hash ^= self.to_move_white;
         }
 
-        // TODO castling rights...
+        hash =
+            self.change_castling_rights(hash, CastlingRights::none(), game_state.castling_rights);
 
-        hash
+        let checks_given = [
+            game_state.checks_given(Color::WHITE),
+            game_state.checks_given(Color::BLACK),
+        ];
+        self.change_checks_given(hash, [0; 2], checks_given)
     }
 
-    pub fn apply_move(&self, current_hash: u64, to_apply: Move, to_move: Color) -> u64 {
+    pub fn apply_move(
+        &self,
+        current_hash: u64,
+        last_en_passant: Option<Position>,
+        to_apply: Move,
+        to_move: Color,
+    ) -> u64 {
         let moving_piece = to_apply.moving_piece;
         let moving_piece_index = zobrist_index_for_piece(moving_piece, to_move);
         let from_square_index = usize::from(to_apply.from.to_numeric());
         let to_square_index = usize::from(to_apply.to.to_numeric());
+        let destination_index = usize::from(to_apply.destination().to_numeric());
 
         let mut new_hash = current_hash;
 
         match to_apply.move_type {
             MoveType::Capture(captured_piece) => {
-                let captured_piece_index = zobrist_index_for_piece(captured_piece, to_move);
+                let captured_piece_index =
+                    zobrist_index_for_piece(captured_piece, to_move.opposite());
                 new_hash ^= self.pieces[to_square_index][captured_piece_index];
             }
             MoveType::EnPassant => {
@@ -135,19 +188,12 @@ impl ZobristHasher {
                         .to_numeric(),
                 );
                 let captured_piece_index = zobrist_index_for_piece(Piece::PAWN, to_move.opposite());
-                new_hash = new_hash ^ self.pieces[captured_square_index][captured_piece_index];
+                new_hash ^= self.pieces[captured_square_index][captured_piece_index];
             }
             MoveType::Castling => {
-                let rank = to_apply.from.rank();
-                let (old_rook_file, new_rook_file) = if to_apply.to.file() < 5 {
-                    (1, 4)
-                } else {
-                    (8, 6)
-                };
-                let old_rook_position_index =
-                    usize::from(Position::new(old_rook_file, rank).to_numeric());
+                let old_rook_position_index = to_square_index;
                 let new_rook_position_index =
-                    usize::from(Position::new(new_rook_file, rank).to_numeric());
+                    usize::from(to_apply.castling_destinations().1.to_numeric());
                 let rook_piece_index = zobrist_index_for_piece(Piece::ROOK, to_move);
                 new_hash ^= self.pieces[old_rook_position_index][rook_piece_index];
                 new_hash ^= self.pieces[new_rook_position_index][rook_piece_index];
@@ -159,15 +205,15 @@ impl ZobristHasher {
 
         match to_apply.promotes_to {
             None => {
-                new_hash ^= self.pieces[to_square_index][moving_piece_index];
+                new_hash ^= self.pieces[destination_index][moving_piece_index];
             }
             Some(piece) => {
                 new_hash ^= self.pieces[to_square_index][zobrist_index_for_piece(piece, to_move)];
             }
         }
 
-        if let Some(en_passant) = to_apply.last_en_passant {
-            new_hash = new_hash ^ self.en_passant[usize::from(en_passant.to_numeric())];
+        if let Some(en_passant) = last_en_passant {
+            new_hash ^= self.en_passant[usize::from(en_passant.to_numeric())];
         }
 
         // set en passant bit mask
@@ -176,26 +222,33 @@ impl ZobristHasher {
                 i16::from(to_apply.from.to_numeric()) - i16::from(to_apply.to.to_numeric()),
             ) == 16;
             if is_two_steps_move {
-                new_hash = new_hash ^ self.en_passant[usize::from(to_apply.to.to_numeric())];
+                new_hash ^= self.en_passant[usize::from(to_apply.to.to_numeric())];
             }
         }
 
-        new_hash = new_hash ^ self.to_move_white;
+        new_hash ^= self.to_move_white;
 
         new_hash
     }
 
-    pub fn unapply_move(&self, current_hash: u64, to_unapply: Move, to_move: Color) -> u64 {
+    pub fn unapply_move(
+        &self,
+        current_hash: u64,
+        last_en_passant: Option<Position>,
+        to_unapply: Move,
+        to_move: Color,
+    ) -> u64 {
         let moving_piece = to_unapply.moving_piece;
         let moving_piece_index = zobrist_index_for_piece(moving_piece, to_move.opposite());
         let from_square_index = usize::from(to_unapply.from.to_numeric());
         let to_square_index = usize::from(to_unapply.to.to_numeric());
+        let destination_index = usize::from(to_unapply.destination().to_numeric());
 
         let mut new_hash = current_hash;
 
         match to_unapply.move_type {
             MoveType::Capture(captured_piece) => {
-                let piece_index = zobrist_index_for_piece(captured_piece, to_move.opposite());
+                let piece_index = zobrist_index_for_piece(captured_piece, to_move);
                 new_hash ^= self.pieces[to_square_index][piece_index];
             }
             MoveType::EnPassant => {
@@ -215,16 +268,9 @@ impl ZobristHasher {
                 new_hash ^= self.pieces[captured_square_index][captured_piece_index];
             }
             MoveType::Castling => {
-                let rank = to_unapply.from.rank();
-                let (old_rook_file, new_rook_file) = if to_unapply.to.file() < 5 {
-                    (1, 4)
-                } else {
-                    (8, 6)
-                };
-                let old_rook_position_index =
-                    usize::from(Position::new(old_rook_file, rank).to_numeric());
+                let old_rook_position_index = to_square_index;
                 let new_rook_position_index =
-                    usize::from(Position::new(new_rook_file, rank).to_numeric());
+                    usize::from(to_unapply.castling_destinations().1.to_numeric());
                 let rook_piece_index = zobrist_index_for_piece(Piece::ROOK, to_move.opposite());
                 new_hash ^= self.pieces[old_rook_position_index][rook_piece_index];
                 new_hash ^= self.pieces[new_rook_position_index][rook_piece_index];
@@ -236,7 +282,7 @@ impl ZobristHasher {
 
         match to_unapply.promotes_to {
             None => {
-                new_hash ^= self.pieces[to_square_index][moving_piece_index];
+                new_hash ^= self.pieces[destination_index][moving_piece_index];
             }
             Some(piece) => {
                 new_hash ^= self.pieces[to_square_index]
@@ -244,7 +290,7 @@ impl ZobristHasher {
             }
         }
 
-        if let Some(en_passant) = to_unapply.last_en_passant {
+        if let Some(en_passant) = last_en_passant {
             new_hash ^= self.en_passant[usize::from(en_passant.to_numeric())];
         }
 
@@ -257,117 +303,62 @@ impl ZobristHasher {
             }
         }
 
-        new_hash = new_hash ^ self.to_move_white;
+        new_hash ^= self.to_move_white;
 
         new_hash
     }
 
-    fn apply_castling_rights(
-        &self,
-        current_hash: u64,
-        castling_rights: CastlingRights,
-        m: Move,
-        to_move: Color,
-    ) -> u64 {
-        let mut new_hash = current_hash;
+    pub fn null_move(&self, current_hash: u64, en_passant: Option<Position>) -> u64 {
+        let mut new_hash = current_hash ^ self.to_move_white;
 
-        let moving_piece = m.moving_piece;
-        // lose all castling rights when king moves
-        if moving_piece == Piece::KING {
-            if to_move == Color::WHITE {
-                if castling_rights.white_king_side {
-                    new_hash ^= self.castling_rights[WHITE_KING_SIDE];
-                }
-                if castling_rights.white_queen_side {
-                    new_hash ^= self.castling_rights[WHITE_QUEEN_SIDE];
-                }
-            } else {
-                if castling_rights.black_king_side {
-                    new_hash ^= self.castling_rights[BLACK_KING_SIDE];
-                }
-                if castling_rights.black_queen_side {
-                    new_hash ^= self.castling_rights[BLACK_QUEEN_SIDE];
-                }
-            }
+        if let Some(en_passant) = en_passant {
+            new_hash ^= self.en_passant[usize::from(en_passant.to_numeric())];
         }
 
-        // lose queen side castling rights when queen side rook moves
-        if moving_piece == Piece::ROOK && m.from.file() == 1 {
-            if to_move == Color::WHITE {
-                if castling_rights.white_queen_side {
-                    new_hash ^= self.castling_rights[WHITE_QUEEN_SIDE];
-                }
-            } else {
-                if castling_rights.black_queen_side {
-                    new_hash ^= self.castling_rights[BLACK_QUEEN_SIDE];
-                }
-            }
-        }
+        new_hash
+    }
 
-        // lose king side castling rights when king side rook moves
-        if moving_piece == Piece::ROOK && m.from.file() == 8 {
-            if to_move == Color::WHITE {
-                if castling_rights.white_king_side {
-                    new_hash ^= self.castling_rights[WHITE_KING_SIDE];
-                }
-            } else {
-                if castling_rights.black_king_side {
-                    new_hash ^= self.castling_rights[BLACK_KING_SIDE];
-                }
-            }
-        }
+    fn change_checks_given(
+        &self,
+        current_hash: u64,
+        checks_given: [u8; 2],
+        next_checks_given: [u8; 2],
+    ) -> u64 {
+        let mut new_hash = current_hash;
 
-        // lose castling rights when rook is captured
-        match m.move_type {
-            MoveType::Capture(Piece::ROOK) => {
-                if m.to.file() == 1 {
-                    if to_move == Color::WHITE {
-                        if castling_rights.black_queen_side {
-                            new_hash ^= self.castling_rights[BLACK_QUEEN_SIDE];
-                        }
-                    } else {
-                        if castling_rights.white_queen_side {
-                            new_hash ^= self.castling_rights[WHITE_QUEEN_SIDE];
-                        }
-                    }
-                } else if m.to.file() == 8 {
-                    if to_move == Color::WHITE {
-                        if castling_rights.black_king_side {
-                            new_hash ^= self.castling_rights[BLACK_KING_SIDE];
-                        }
-                    } else {
-                        if castling_rights.white_king_side {
-                            new_hash ^= self.castling_rights[WHITE_KING_SIDE];
-                        }
-                    }
-                }
+        for (keys, (checks, next_checks)) in self
+            .checks_given
+            .iter()
+            .zip(checks_given.iter().zip(next_checks_given.iter()))
+        {
+            if checks != next_checks {
+                new_hash ^= keys[usize::from(*checks).min(CHECKS_GIVEN_KEYS - 1)];
+                new_hash ^= keys[usize::from(*next_checks).min(CHECKS_GIVEN_KEYS - 1)];
             }
-            _ => (),
         }
 
         new_hash
     }
 
-    fn unapply_castling_rights(
+    // toggles the keys of the rights that differ between the two
+    fn change_castling_rights(
         &self,
         current_hash: u64,
         castling_rights: CastlingRights,
-        m: Move,
-        _to_move: Color,
+        next_castling_rights: CastlingRights,
     ) -> u64 {
         let mut new_hash = current_hash;
-        let last_castling_rights = m.last_castling_rights;
 
-        if castling_rights.white_king_side != last_castling_rights.white_king_side {
+        if castling_rights.white_king_side != next_castling_rights.white_king_side {
             new_hash ^= self.castling_rights[WHITE_KING_SIDE];
         }
-        if castling_rights.white_queen_side != last_castling_rights.white_queen_side {
+        if castling_rights.white_queen_side != next_castling_rights.white_queen_side {
             new_hash ^= self.castling_rights[WHITE_QUEEN_SIDE];
         }
-        if castling_rights.black_king_side != last_castling_rights.black_king_side {
+        if castling_rights.black_king_side != next_castling_rights.black_king_side {
             new_hash ^= self.castling_rights[BLACK_KING_SIDE];
         }
-        if castling_rights.black_queen_side != last_castling_rights.black_queen_side {
+        if castling_rights.black_queen_side != next_castling_rights.black_queen_side {
             new_hash ^= self.castling_rights[BLACK_QUEEN_SIDE];
         }
 
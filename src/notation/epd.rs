@@ -0,0 +1,129 @@
+use crate::model::game_state::{GameState, Move};
+use crate::notation::fen::parse_fen;
+use crate::notation::san::parse_san;
+
+// A test position with the operations of its EPD record. Best and avoid moves are resolved from
+// their SAN operands, and a direct mate is the number of full moves to mate.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EpdPosition {
+    pub game_state: GameState,
+    pub operations: Vec<(String, Vec<String>)>,
+    pub id: Option<String>,
+    pub best_moves: Vec<Move>,
+    pub avoid_moves: Vec<Move>,
+    pub direct_mate: Option<i32>,
+}
+
+impl EpdPosition {
+    pub fn operation(&self, opcode: &str) -> Option<&[String]> {
+        self.operations
+            .iter()
+            .find(|(name, _)| name == opcode)
+            .map(|(_, operands)| operands.as_slice())
+    }
+}
+
+// parses every record of an EPD file, skipping empty lines and lines starting with '#'
+pub fn parse_epd_file(contents: &str) -> Result<Vec<EpdPosition>, String> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| parse_epd(line).map_err(|msg| format!("Line {}: {}", index + 1, msg)))
+        .collect()
+}
+
+pub fn parse_epd(record: &str) -> Result<EpdPosition, String> {
+    let mut rest = record.trim();
+    let mut fields = vec![];
+    for _ in 0..4 {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        fields.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+
+    let game_state = parse_fen(&fields.join(" "))?;
+    let operations = parse_operations(rest)?;
+
+    let mut position = EpdPosition {
+        game_state,
+        operations,
+        id: None,
+        best_moves: vec![],
+        avoid_moves: vec![],
+        direct_mate: None,
+    };
+
+    for (opcode, operands) in position.operations.iter() {
+        match opcode.as_str() {
+            "id" => position.id = operands.first().cloned(),
+            "bm" => position.best_moves = parse_moves(&game_state, operands)?,
+            "am" => position.avoid_moves = parse_moves(&game_state, operands)?,
+            "dm" => {
+                let moves = operands.first().map(String::as_str).unwrap_or("");
+                position.direct_mate = Some(
+                    moves
+                        .parse()
+                        .map_err(|_| format!("Invalid direct mate: {}", moves))?,
+                );
+            }
+            _ => (),
+        }
+    }
+
+    Ok(position)
+}
+
+fn parse_moves(game_state: &GameState, operands: &[String]) -> Result<Vec<Move>, String> {
+    operands
+        .iter()
+        .map(|operand| parse_san(game_state, operand))
+        .collect()
+}
+
+// Operations are an opcode followed by operands and terminated by ';'. Quoted operands may
+// contain whitespace and semicolons.
+fn parse_operations(operations: &str) -> Result<Vec<(String, Vec<String>)>, String> {
+    let mut parsed = vec![];
+    let mut tokens: Vec<String> = vec![];
+    let mut chars = operations.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => {
+                if !tokens.is_empty() {
+                    let opcode = tokens.remove(0);
+                    parsed.push((opcode, std::mem::take(&mut tokens)));
+                }
+            }
+            '"' => {
+                let mut token = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => token.push(c),
+                        None => return Err("Unterminated string operand".to_string()),
+                    }
+                }
+                tokens.push(token);
+            }
+            c if c.is_whitespace() => (),
+            c => {
+                let mut token = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != ';') {
+                    token.push(c);
+                }
+                tokens.push(token);
+            }
+        }
+    }
+
+    if !tokens.is_empty() {
+        return Err(format!(
+            "Operation without terminating ';': {}",
+            tokens.join(" ")
+        ));
+    }
+
+    Ok(parsed)
+}
@@ -0,0 +1,271 @@
+use crate::model::game_state::{CastlingRights, Color, GameState, Piece, Position};
+use crate::model::game_state_builder::GameStateBuilder;
+
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+// The halfmove clock and fullmove number are optional, since the game state does not track them
+pub fn parse_fen(fen: &str) -> Result<GameState, String> {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    if fields.len() < 4 || fields.len() > 6 {
+        return Err(format!("FEN must have 4 to 6 fields: {}", fen));
+    }
+
+    let builder = parse_placement(fields[0])?;
+
+    let to_move = match fields[1] {
+        "w" => Color::WHITE,
+        "b" => Color::BLACK,
+        other => return Err(format!("Invalid side to move: {}", other)),
+    };
+    let castling_rights = parse_castling_rights(fields[2], &builder)?;
+    let en_passant = parse_en_passant(fields[3], to_move)?;
+
+    for counter in fields[4..].iter() {
+        counter
+            .parse::<u32>()
+            .map_err(|_| format!("Invalid move counter: {}", counter))?;
+    }
+
+    let game_state = builder
+        .with_to_move(to_move)
+        .with_castling_rights(castling_rights)
+        .with_en_passant(en_passant)
+        .build()?;
+    Ok(game_state)
+}
+
+pub fn to_fen(game_state: &GameState) -> String {
+    let mut ranks = vec![];
+    for rank in (1..9).rev() {
+        let mut repr = String::new();
+        let mut empty = 0;
+        for file in 1..9 {
+            match game_state.get_piece(Position::new(file, rank)) {
+                Some((piece, color)) => {
+                    if empty > 0 {
+                        repr.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+                    repr.push(piece_to_char(piece, color));
+                }
+                None => empty += 1,
+            }
+        }
+        if empty > 0 {
+            repr.push_str(&empty.to_string());
+        }
+        ranks.push(repr);
+    }
+
+    let to_move = if game_state.to_move() == Color::WHITE {
+        "w"
+    } else {
+        "b"
+    };
+
+    let rights = game_state.castling_rights;
+    let mut castling = String::new();
+    for color in [Color::WHITE, Color::BLACK].iter() {
+        for king_side in [true, false].iter() {
+            if rights.is_allowed(*color, *king_side) {
+                castling.push(castling_char(game_state, *color, *king_side));
+            }
+        }
+    }
+    if castling.is_empty() {
+        castling.push('-');
+    }
+
+    // the game state stores the pawn that moved two squares, FEN the square behind it
+    let en_passant = game_state
+        .en_passant()
+        .and_then(|pawn| pawn.delta(0, if pawn.rank() == 4 { -1 } else { 1 }))
+        .map(|square| square.to_lowercase())
+        .unwrap_or_else(|| "-".to_string());
+
+    format!(
+        "{} {} {} {} 0 1",
+        ranks.join("/"),
+        to_move,
+        castling,
+        en_passant
+    )
+}
+
+fn parse_placement(placement: &str) -> Result<GameStateBuilder, String> {
+    let mut builder = GameStateBuilder::new();
+    let ranks: Vec<&str> = placement.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(format!("FEN placement must have 8 ranks: {}", placement));
+    }
+
+    for (index, rank_repr) in ranks.iter().enumerate() {
+        let rank = 8 - index as u8;
+        let mut file = 1;
+        for c in rank_repr.chars() {
+            if let Some(empty) = c.to_digit(10).filter(|digit| (1..=8).contains(digit)) {
+                file += empty as u8;
+            } else {
+                let (piece, color) =
+                    char_to_piece(c).ok_or_else(|| format!("Invalid piece: {}", c))?;
+                if file > 8 {
+                    return Err(format!("FEN rank must have 8 squares: {}", rank_repr));
+                }
+                builder = builder.with_piece(piece, color, Position::new(file, rank));
+                file += 1;
+            }
+        }
+
+        if file != 9 {
+            return Err(format!("FEN rank must have 8 squares: {}", rank_repr));
+        }
+    }
+
+    Ok(builder)
+}
+
+// Accepts KQkq for the outermost rook on each side of the king as well as the rook files of
+// Shredder-FEN and X-FEN, like HAha for the standard starting position.
+fn parse_castling_rights(
+    castling: &str,
+    builder: &GameStateBuilder,
+) -> Result<CastlingRights, String> {
+    let mut rights = CastlingRights::none();
+    if castling == "-" {
+        return Ok(rights);
+    }
+
+    let mut rook_files: Vec<(bool, u8)> = vec![];
+    for c in castling.chars() {
+        let color = if c.is_ascii_uppercase() {
+            Color::WHITE
+        } else {
+            Color::BLACK
+        };
+        let rank = color.back_rank();
+        let is_piece = |piece: Piece, file: &u8| {
+            builder.piece(Position::new(*file, rank)) == Some((piece, color))
+        };
+        let king_file = (1..9)
+            .find(|file| is_piece(Piece::KING, file))
+            .ok_or_else(|| format!("Castling rights without king: {}", castling))?;
+
+        let rook_file = match c.to_ascii_lowercase() {
+            'k' => (king_file + 1..9)
+                .rev()
+                .find(|file| is_piece(Piece::ROOK, file))
+                .unwrap_or(8),
+            'q' => (1..king_file)
+                .find(|file| is_piece(Piece::ROOK, file))
+                .unwrap_or(1),
+            file @ 'a'..='h' if file as u8 - b'a' + 1 != king_file => file as u8 - b'a' + 1,
+            _ => return Err(format!("Invalid castling rights: {}", castling)),
+        };
+
+        let king_side = rook_file > king_file;
+        if king_side {
+            *rights.get_king_side_mut(color) = true;
+            rights.king_side_rook_file = rook_file;
+        } else {
+            *rights.get_queen_side_mut(color) = true;
+            rights.queen_side_rook_file = rook_file;
+        }
+        rook_files.push((king_side, rook_file));
+    }
+
+    if rook_files.iter().any(|(king_side, file)| {
+        *file
+            != if *king_side {
+                rights.king_side_rook_file
+            } else {
+                rights.queen_side_rook_file
+            }
+    }) {
+        return Err(format!(
+            "Castling rooks must be on the same files for both sides: {}",
+            castling
+        ));
+    }
+
+    Ok(rights)
+}
+
+// X-FEN: the file of the rook when another rook stands between it and the corner, else K or Q
+fn castling_char(game_state: &GameState, color: Color, king_side: bool) -> char {
+    let rook = game_state.castling_rights.rook_square(color, king_side);
+    let outer_files = if king_side {
+        rook.file() + 1..9
+    } else {
+        1..rook.file()
+    };
+    let is_outermost = outer_files.into_iter().all(|file| {
+        game_state.get_piece(Position::new(file, rook.rank())) != Some((Piece::ROOK, color))
+    });
+
+    let c = match (is_outermost, king_side) {
+        (true, true) => 'k',
+        (true, false) => 'q',
+        (false, _) => (b'a' + rook.file() - 1) as char,
+    };
+    if color == Color::WHITE {
+        c.to_ascii_uppercase()
+    } else {
+        c
+    }
+}
+
+fn parse_en_passant(en_passant: &str, to_move: Color) -> Result<Option<Position>, String> {
+    if en_passant == "-" {
+        return Ok(None);
+    }
+
+    let chars: Vec<char> = en_passant.chars().collect();
+    let (expected_rank, pawn_rank) = if to_move == Color::WHITE {
+        ('6', 5)
+    } else {
+        ('3', 4)
+    };
+
+    match chars.as_slice() {
+        [file @ 'a'..='h', rank] if *rank == expected_rank => {
+            Ok(Some(Position::new(*file as u8 - b'a' + 1, pawn_rank)))
+        }
+        _ => Err(format!("Invalid en passant square: {}", en_passant)),
+    }
+}
+
+fn piece_to_char(piece: Piece, color: Color) -> char {
+    let c = match piece {
+        Piece::PAWN => 'p',
+        Piece::KNIGHT => 'n',
+        Piece::BISHOP => 'b',
+        Piece::ROOK => 'r',
+        Piece::QUEEN => 'q',
+        Piece::KING => 'k',
+    };
+
+    if color == Color::WHITE {
+        c.to_ascii_uppercase()
+    } else {
+        c
+    }
+}
+
+fn char_to_piece(c: char) -> Option<(Piece, Color)> {
+    let piece = match c.to_ascii_lowercase() {
+        'p' => Piece::PAWN,
+        'n' => Piece::KNIGHT,
+        'b' => Piece::BISHOP,
+        'r' => Piece::ROOK,
+        'q' => Piece::QUEEN,
+        'k' => Piece::KING,
+        _ => return None,
+    };
+    let color = if c.is_ascii_uppercase() {
+        Color::WHITE
+    } else {
+        Color::BLACK
+    };
+
+    Some((piece, color))
+}
@@ -0,0 +1,448 @@
+use crate::model::game_state::{Color, GameState, Move};
+use crate::notation::fen::{parse_fen, to_fen};
+use crate::notation::san::{move_to_san, parse_san};
+
+const MAX_LINE_LENGTH: usize = 80;
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+const SUFFIX_ANNOTATIONS: [(&str, u8); 6] = [
+    ("!", 1),
+    ("?", 2),
+    ("!!", 3),
+    ("??", 4),
+    ("!?", 5),
+    ("?!", 6),
+];
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub initial_position: GameState,
+    pub moves: Vec<PgnMove>,
+    pub result: String,
+}
+
+// A move of a line with its annotations and the variations that replace it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PgnMove {
+    pub chess_move: Move,
+    pub nags: Vec<u8>,
+    pub comment_before: Option<String>,
+    pub comment_after: Option<String>,
+    pub variations: Vec<Vec<PgnMove>>,
+}
+
+impl PgnMove {
+    pub fn new(chess_move: Move) -> Self {
+        PgnMove {
+            chess_move,
+            nags: vec![],
+            comment_before: None,
+            comment_after: None,
+            variations: vec![],
+        }
+    }
+}
+
+impl Default for PgnGame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PgnGame {
+    pub fn new() -> Self {
+        Self::from_position(GameState::new())
+    }
+
+    pub fn from_position(initial_position: GameState) -> Self {
+        let tags = ["Event", "Site", "Date", "Round", "White", "Black"]
+            .iter()
+            .map(|name| (name.to_string(), "?".to_string()))
+            .chain(std::iter::once(("Result".to_string(), "*".to_string())))
+            .collect();
+
+        PgnGame {
+            tags,
+            initial_position,
+            moves: vec![],
+            result: "*".to_string(),
+        }
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag_name, _)| tag_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag_name, _)| tag_name == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn set_result(&mut self, result: &str) {
+        self.result = result.to_string();
+        self.set_tag("Result", result);
+    }
+
+    pub fn push_move(&mut self, chess_move: Move) {
+        self.moves.push(PgnMove::new(chess_move));
+    }
+
+    pub fn main_line(&self) -> Vec<Move> {
+        self.moves.iter().map(|m| m.chess_move).collect()
+    }
+
+    pub fn final_position(&self) -> GameState {
+        let mut game_state = self.initial_position;
+        for m in self.moves.iter() {
+            game_state.apply_move_mut(m.chess_move);
+        }
+        game_state
+    }
+
+    fn fullmove_number(&self) -> u32 {
+        self.tag("FEN")
+            .and_then(|fen| fen.split_whitespace().nth(5))
+            .and_then(|number| number.parse().ok())
+            .unwrap_or(1)
+    }
+}
+
+pub fn parse_pgn(pgn: &str) -> Result<Vec<PgnGame>, String> {
+    let mut parser = Parser {
+        chars: pgn.chars().collect(),
+        index: 0,
+    };
+    let mut games = vec![];
+
+    parser.skip_whitespace();
+    while !parser.is_at_end() {
+        games.push(parser.parse_game()?);
+        parser.skip_whitespace();
+    }
+
+    Ok(games)
+}
+
+pub fn write_pgn(game: &PgnGame) -> String {
+    let mut pgn = String::new();
+    let is_standard_start = game.initial_position == GameState::new();
+
+    for (name, value) in game.tags.iter() {
+        pgn.push_str(&format!("[{} \"{}\"]\n", name, escape(value)));
+    }
+    if !is_standard_start && game.tag("FEN").is_none() {
+        pgn.push_str("[SetUp \"1\"]\n");
+        pgn.push_str(&format!("[FEN \"{}\"]\n", to_fen(&game.initial_position)));
+    }
+    pgn.push('\n');
+
+    let mut tokens = vec![];
+    write_line(
+        &game.moves,
+        game.initial_position,
+        game.fullmove_number(),
+        &mut tokens,
+    );
+    tokens.push(game.result.clone());
+
+    // keep parentheses attached to the moves they enclose
+    let mut index = 1;
+    while index < tokens.len() {
+        if tokens[index] == ")" || tokens[index - 1] == "(" {
+            let token = tokens.remove(index);
+            tokens[index - 1].push_str(&token);
+        } else {
+            index += 1;
+        }
+    }
+
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && line.len() + 1 + token.len() > MAX_LINE_LENGTH {
+            pgn.push_str(&line);
+            pgn.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&token);
+    }
+    pgn.push_str(&line);
+    pgn.push('\n');
+
+    pgn
+}
+
+fn write_line(
+    moves: &[PgnMove],
+    mut game_state: GameState,
+    mut fullmove_number: u32,
+    tokens: &mut Vec<String>,
+) {
+    let mut needs_number = true;
+
+    for m in moves {
+        if let Some(comment) = &m.comment_before {
+            tokens.push(format!("{{{}}}", comment));
+            needs_number = true;
+        }
+
+        if game_state.to_move() == Color::WHITE {
+            tokens.push(format!("{}.", fullmove_number));
+        } else if needs_number {
+            tokens.push(format!("{}...", fullmove_number));
+        }
+        needs_number = false;
+
+        tokens.push(move_to_san(&game_state, &m.chess_move));
+        tokens.extend(m.nags.iter().map(|nag| format!("${}", nag)));
+
+        if let Some(comment) = &m.comment_after {
+            tokens.push(format!("{{{}}}", comment));
+            needs_number = true;
+        }
+
+        for variation in m.variations.iter() {
+            tokens.push("(".to_string());
+            write_line(variation, game_state, fullmove_number, tokens);
+            tokens.push(")".to_string());
+            needs_number = true;
+        }
+
+        if game_state.to_move() == Color::BLACK {
+            fullmove_number += 1;
+        }
+        game_state.apply_move_mut(m.chess_move);
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+}
+
+impl Parser {
+    fn parse_game(&mut self) -> Result<PgnGame, String> {
+        let mut tags = vec![];
+        self.skip_whitespace();
+        while self.peek() == Some('[') {
+            tags.push(self.parse_tag()?);
+            self.skip_whitespace();
+        }
+
+        let initial_position = match tags.iter().find(|(name, _)| name == "FEN") {
+            Some((_, fen)) => parse_fen(fen)?,
+            None => GameState::new(),
+        };
+
+        let mut game = PgnGame {
+            tags,
+            initial_position,
+            moves: vec![],
+            result: "*".to_string(),
+        };
+
+        let (moves, result) = self.parse_line(initial_position, false)?;
+        game.moves = moves;
+        game.result = result
+            .or_else(|| game.tag("Result").map(|result| result.to_string()))
+            .unwrap_or_else(|| "*".to_string());
+
+        Ok(game)
+    }
+
+    fn parse_tag(&mut self) -> Result<(String, String), String> {
+        self.index += 1;
+        self.skip_whitespace();
+        let name = self.take_while(|c| !c.is_whitespace() && c != '"' && c != ']');
+        self.skip_whitespace();
+
+        if self.next() != Some('"') {
+            return Err(format!("Expected tag value for {}", name));
+        }
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                Some('\\') => value.extend(self.next()),
+                Some('"') => break,
+                Some(c) => value.push(c),
+                None => return Err(format!("Unterminated tag value for {}", name)),
+            }
+        }
+
+        self.skip_whitespace();
+        if self.next() != Some(']') {
+            return Err(format!("Expected ] after tag {}", name));
+        }
+
+        Ok((name, value))
+    }
+
+    // Parses moves until the end of a variation or the game, returning the game result if found
+    fn parse_line(
+        &mut self,
+        mut game_state: GameState,
+        is_variation: bool,
+    ) -> Result<(Vec<PgnMove>, Option<String>), String> {
+        let mut moves: Vec<PgnMove> = vec![];
+        let mut previous_state = game_state;
+        let mut pending_comment: Option<String> = None;
+
+        loop {
+            self.skip_whitespace();
+            let c = match self.peek() {
+                Some(c) => c,
+                None if is_variation => return Err("Unterminated variation".to_string()),
+                None => return Ok((moves, None)),
+            };
+
+            match c {
+                '{' | ';' => {
+                    let comment = self.parse_comment()?;
+                    match moves.last_mut() {
+                        Some(last) => append_comment(&mut last.comment_after, comment),
+                        None => append_comment(&mut pending_comment, comment),
+                    }
+                }
+                '(' => {
+                    self.index += 1;
+                    let (variation, _) = self.parse_line(previous_state, true)?;
+                    moves
+                        .last_mut()
+                        .ok_or("Variation without a preceding move")?
+                        .variations
+                        .push(variation);
+                }
+                ')' if is_variation => {
+                    self.index += 1;
+                    return Ok((moves, None));
+                }
+                '[' if !is_variation => return Ok((moves, None)),
+                '%' if self.is_line_start() => {
+                    self.take_while(|c| c != '\n');
+                }
+                '$' => {
+                    self.index += 1;
+                    let nag = self.take_while(|c| c.is_ascii_digit());
+                    let nag = nag.parse().map_err(|_| format!("Invalid NAG: ${}", nag))?;
+                    moves
+                        .last_mut()
+                        .ok_or("NAG without a preceding move")?
+                        .nags
+                        .push(nag);
+                }
+                _ => {
+                    let token = self.take_while(|c| !c.is_whitespace() && !"{};()[$".contains(c));
+                    if token.is_empty() {
+                        return Err(format!("Unexpected character: {}", c));
+                    }
+                    if RESULTS.contains(&token.as_str()) {
+                        if is_variation {
+                            return Err(format!("Result inside a variation: {}", token));
+                        }
+                        return Ok((moves, Some(token)));
+                    }
+
+                    let san = strip_move_number(&token);
+                    if san.is_empty() {
+                        continue;
+                    }
+
+                    let (san, nag) = split_suffix_annotation(san);
+                    let chess_move = parse_san(&game_state, san)?;
+                    let mut pgn_move = PgnMove::new(chess_move);
+                    pgn_move.nags.extend(nag);
+                    pgn_move.comment_before = pending_comment.take();
+                    moves.push(pgn_move);
+
+                    previous_state = game_state;
+                    game_state.apply_move_mut(chess_move);
+                }
+            }
+        }
+    }
+
+    fn parse_comment(&mut self) -> Result<String, String> {
+        let comment = if self.next() == Some('{') {
+            let comment = self.take_while(|c| c != '}');
+            if self.next() != Some('}') {
+                return Err("Unterminated comment".to_string());
+            }
+            comment
+        } else {
+            self.take_while(|c| c != '\n')
+        };
+
+        Ok(comment.trim().to_string())
+    }
+
+    fn take_while<P: Fn(char) -> bool>(&mut self, predicate: P) -> String {
+        let mut taken = String::new();
+        while let Some(c) = self.peek().filter(|c| predicate(*c)) {
+            taken.push(c);
+            self.index += 1;
+        }
+        taken
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn is_line_start(&self) -> bool {
+        self.index == 0 || self.chars[self.index - 1] == '\n'
+    }
+
+    fn is_at_end(&self) -> bool {
+        self.index >= self.chars.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.index += 1;
+        c
+    }
+}
+
+fn strip_move_number(token: &str) -> &str {
+    let without_digits = token.trim_start_matches(|c: char| c.is_ascii_digit());
+    if without_digits.starts_with('.') {
+        without_digits.trim_start_matches('.')
+    } else {
+        token
+    }
+}
+
+fn split_suffix_annotation(san: &str) -> (&str, Option<u8>) {
+    let trimmed = san.trim_end_matches(['!', '?']);
+    let suffix = &san[trimmed.len()..];
+
+    SUFFIX_ANNOTATIONS
+        .iter()
+        .find(|(annotation, _)| *annotation == suffix)
+        .map_or((san, None), |(_, nag)| (trimmed, Some(*nag)))
+}
+
+fn append_comment(target: &mut Option<String>, comment: String) {
+    match target {
+        Some(existing) => {
+            existing.push(' ');
+            existing.push_str(&comment);
+        }
+        None => *target = Some(comment),
+    }
+}
@@ -0,0 +1,225 @@
+use crate::model::game_state::{GameState, Move, MoveType, Piece, Position};
+use crate::model::move_generator::MoveGenerator;
+
+lazy_static! {
+    static ref MOVE_GENERATOR: MoveGenerator = MoveGenerator::new();
+}
+
+pub fn move_to_san(game_state: &GameState, m: &Move) -> String {
+    let mut san = String::new();
+
+    if m.move_type == MoveType::Castling {
+        san.push_str(if m.to.file() > m.from.file() {
+            "O-O"
+        } else {
+            "O-O-O"
+        });
+    } else {
+        let is_capture = matches!(m.move_type, MoveType::Capture(_) | MoveType::EnPassant);
+
+        if m.moving_piece == Piece::PAWN {
+            if is_capture {
+                san.push(file_to_char(m.from.file()));
+            }
+        } else {
+            san.push(piece_to_char(m.moving_piece));
+            san.push_str(&disambiguation(game_state, m));
+        }
+
+        if is_capture {
+            san.push('x');
+        }
+        san.push_str(&m.to.to_lowercase());
+
+        if let Some(piece) = m.promotes_to {
+            san.push('=');
+            san.push(piece_to_char(piece));
+        }
+    }
+
+    let generated = MOVE_GENERATOR.generate_moves(&game_state.apply_move(*m));
+    if generated.is_checkmate() {
+        san.push('#');
+    } else if generated.is_check {
+        san.push('+');
+    }
+
+    san
+}
+
+// Accepts strict SAN as well as common variants: 0-0 castling, missing or extra
+// capture markers, promotion without '=', annotations and long algebraic moves.
+pub fn parse_san(game_state: &GameState, san: &str) -> Result<Move, String> {
+    let trimmed = san
+        .trim()
+        .trim_end_matches(|c| "+#!?".contains(c))
+        .trim_end_matches("e.p.")
+        .trim_end();
+    let legal_moves = MOVE_GENERATOR.generate_moves(game_state).moves;
+
+    let matching: Vec<Move> = match trimmed {
+        "O-O" | "0-0" => legal_moves
+            .into_iter()
+            .filter(|m| m.move_type == MoveType::Castling && m.to.file() > m.from.file())
+            .collect(),
+        "O-O-O" | "0-0-0" => legal_moves
+            .into_iter()
+            .filter(|m| m.move_type == MoveType::Castling && m.to.file() < m.from.file())
+            .collect(),
+        _ => {
+            let parsed =
+                parse_components(trimmed).ok_or_else(|| format!("Invalid SAN move: {}", san))?;
+            legal_moves
+                .into_iter()
+                .filter(|m| parsed.matches(m))
+                .collect()
+        }
+    };
+
+    match matching.as_slice() {
+        [m] => Ok(*m),
+        [] => Err(format!("Illegal move: {}", san)),
+        _ => Err(format!("Ambiguous move: {}", san)),
+    }
+}
+
+struct SanComponents {
+    piece: Piece,
+    from_file: Option<u8>,
+    from_rank: Option<u8>,
+    to: Position,
+    promotes_to: Option<Piece>,
+}
+
+impl SanComponents {
+    // castling is only written as O-O or O-O-O
+    fn matches(&self, m: &Move) -> bool {
+        m.move_type != MoveType::Castling
+            && m.moving_piece == self.piece
+            && m.to == self.to
+            && m.promotes_to == self.promotes_to
+            && self.from_file.map_or(true, |file| m.from.file() == file)
+            && self.from_rank.map_or(true, |rank| m.from.rank() == rank)
+    }
+}
+
+fn parse_components(san: &str) -> Option<SanComponents> {
+    let mut chars: Vec<char> = san
+        .chars()
+        .filter(|c| !matches!(c, 'x' | 'X' | ':' | '-'))
+        .collect();
+
+    let piece = match chars.first().copied() {
+        Some('P') => {
+            chars.remove(0);
+            Piece::PAWN
+        }
+        Some(c) => match char_to_piece(c) {
+            Some(piece) => {
+                chars.remove(0);
+                piece
+            }
+            None => Piece::PAWN,
+        },
+        None => return None,
+    };
+
+    let promotes_to = match chars.last().copied() {
+        Some(c) if piece == Piece::PAWN && c.is_ascii_alphabetic() => {
+            chars.pop();
+            if chars.last() == Some(&'=') {
+                chars.pop();
+            }
+            Some(char_to_piece(c.to_ascii_uppercase()).filter(|p| *p != Piece::KING)?)
+        }
+        _ => None,
+    };
+
+    if chars.len() < 2 || chars.len() > 4 {
+        return None;
+    }
+
+    let (disambiguation, destination) = chars.split_at(chars.len() - 2);
+    let to = Position::new(char_to_file(destination[0])?, char_to_rank(destination[1])?);
+
+    let (mut from_file, mut from_rank) = (None, None);
+    for c in disambiguation {
+        if let Some(file) = char_to_file(*c).filter(|_| from_file.is_none()) {
+            from_file = Some(file);
+        } else if let Some(rank) = char_to_rank(*c).filter(|_| from_rank.is_none()) {
+            from_rank = Some(rank);
+        } else {
+            return None;
+        }
+    }
+
+    Some(SanComponents {
+        piece,
+        from_file,
+        from_rank,
+        to,
+        promotes_to,
+    })
+}
+
+fn disambiguation(game_state: &GameState, m: &Move) -> String {
+    let others: Vec<Position> = MOVE_GENERATOR
+        .generate_moves(game_state)
+        .moves
+        .into_iter()
+        .filter(|other| {
+            other.moving_piece == m.moving_piece && other.to == m.to && other.from != m.from
+        })
+        .map(|other| other.from)
+        .collect();
+
+    if others.is_empty() {
+        String::new()
+    } else if others.iter().all(|from| from.file() != m.from.file()) {
+        file_to_char(m.from.file()).to_string()
+    } else if others.iter().all(|from| from.rank() != m.from.rank()) {
+        m.from.rank().to_string()
+    } else {
+        m.from.to_lowercase()
+    }
+}
+
+fn file_to_char(file: u8) -> char {
+    (b'a' + file - 1) as char
+}
+
+fn char_to_file(c: char) -> Option<u8> {
+    match c {
+        'a'..='h' => Some(c as u8 - b'a' + 1),
+        _ => None,
+    }
+}
+
+fn char_to_rank(c: char) -> Option<u8> {
+    match c {
+        '1'..='8' => Some(c as u8 - b'0'),
+        _ => None,
+    }
+}
+
+fn piece_to_char(piece: Piece) -> char {
+    match piece {
+        Piece::PAWN => 'P',
+        Piece::KNIGHT => 'N',
+        Piece::BISHOP => 'B',
+        Piece::ROOK => 'R',
+        Piece::QUEEN => 'Q',
+        Piece::KING => 'K',
+    }
+}
+
+fn char_to_piece(c: char) -> Option<Piece> {
+    match c {
+        'N' => Some(Piece::KNIGHT),
+        'B' => Some(Piece::BISHOP),
+        'R' => Some(Piece::ROOK),
+        'Q' => Some(Piece::QUEEN),
+        'K' => Some(Piece::KING),
+        _ => None,
+    }
+}
@@ -0,0 +1,61 @@
+#[cfg(test)]
+use crate::notation::epd::{parse_epd, parse_epd_file};
+#[cfg(test)]
+use crate::notation::fen::parse_fen;
+#[cfg(test)]
+use crate::notation::san::parse_san;
+
+#[test]
+fn operations_are_parsed() {
+    let position = parse_epd(
+        r#"2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001; mate";"#,
+    )
+    .unwrap();
+    let game_state =
+        parse_fen("2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1").unwrap();
+
+    assert_eq!(position.game_state, game_state);
+    assert_eq!(position.id.as_deref(), Some("WAC.001; mate"));
+    assert_eq!(
+        position.best_moves,
+        vec![parse_san(&game_state, "Qg6").unwrap()]
+    );
+    assert!(position.avoid_moves.is_empty());
+    assert_eq!(position.direct_mate, None);
+    assert_eq!(position.operation("bm"), Some(&["Qg6".to_string()][..]));
+}
+
+#[test]
+fn avoid_moves_and_direct_mates() {
+    let position = parse_epd("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - am Kf1 Kh1; dm 1; hmvc 0;").unwrap();
+
+    assert_eq!(position.avoid_moves.len(), 2);
+    assert_eq!(position.direct_mate, Some(1));
+    assert_eq!(position.operation("hmvc"), Some(&["0".to_string()][..]));
+    assert_eq!(position.id, None);
+}
+
+#[test]
+fn files_skip_comments_and_report_line_numbers() {
+    let contents = "# tactics\n\n6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra8#;\n\
+                    6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra9;\n";
+
+    assert!(parse_epd_file(contents)
+        .unwrap_err()
+        .starts_with("Line 4: "));
+    assert_eq!(
+        parse_epd_file(&contents[..contents.rfind("6k1").unwrap()])
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn invalid_records_are_rejected() {
+    assert!(parse_epd("6k1/5ppp/8/8/8/8/5PPP/R5K1 w -").is_err());
+    assert!(parse_epd("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Ra8#").is_err());
+    assert!(parse_epd("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - id \"open;").is_err());
+    assert!(parse_epd("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - bm Rb8 Ra7;").is_err());
+    assert!(parse_epd("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - dm x;").is_err());
+}
@@ -0,0 +1,75 @@
+#[cfg(test)]
+use crate::model::game_state::{Color, GameState, Piece, Position};
+#[cfg(test)]
+use crate::model::move_generator::MoveGenerator;
+#[cfg(test)]
+use crate::notation::fen::{parse_fen, to_fen, STARTING_FEN};
+#[cfg(test)]
+use crate::search::test_utils;
+
+#[test]
+fn starting_position_round_trips() {
+    assert_eq!(parse_fen(STARTING_FEN), Ok(GameState::new()));
+    assert_eq!(to_fen(&GameState::new()), STARTING_FEN);
+}
+
+#[test]
+fn position_after_moves_matches_applied_moves() {
+    let mut game_state = GameState::new();
+    test_utils::apply_position(
+        vec![
+            "e2e4".to_string(),
+            "c7c5".to_string(),
+            "g1f3".to_string(),
+            "d7d5".to_string(),
+        ],
+        &mut game_state,
+        &MoveGenerator::new(),
+    );
+
+    let fen = "rnbqkbnr/pp2pppp/8/2pp4/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq d6 0 3";
+    assert_eq!(parse_fen(fen), Ok(game_state));
+    assert_eq!(
+        to_fen(&game_state),
+        "rnbqkbnr/pp2pppp/8/2pp4/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq d6 0 1"
+    );
+}
+
+#[test]
+fn fields_are_parsed() {
+    let game_state = parse_fen("4k3/8/8/8/3pP3/8/8/R3K3 b Q e3").unwrap();
+
+    assert_eq!(game_state.to_move(), Color::BLACK);
+    assert_eq!(game_state.en_passant(), Some(Position::new(5, 4)));
+    assert!(game_state.castling_rights.white_queen_side);
+    assert!(!game_state.castling_rights.white_king_side);
+    assert!(!game_state.castling_rights.black_king_side);
+    assert_eq!(
+        game_state.get_piece(Position::new(1, 1)),
+        Some((Piece::ROOK, Color::WHITE))
+    );
+    assert_eq!(
+        game_state.get_piece(Position::new(4, 4)),
+        Some((Piece::PAWN, Color::BLACK))
+    );
+}
+
+#[test]
+fn invalid_fens_are_rejected() {
+    let invalid = [
+        "",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/ppppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQXBNR w KQkq - 0 1",
+        "rnbq1bnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQ - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkx - 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e3 0 1",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - zero 1",
+    ];
+
+    for fen in invalid.iter() {
+        assert!(parse_fen(fen).is_err(), "{}", fen);
+    }
+}
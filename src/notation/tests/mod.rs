@@ -0,0 +1,4 @@
+mod epd_tests;
+mod fen_tests;
+mod pgn_tests;
+mod san_tests;
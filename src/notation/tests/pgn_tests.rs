@@ -0,0 +1,138 @@
+#[cfg(test)]
+use crate::model::game_state::{GameState, Move};
+#[cfg(test)]
+use crate::notation::fen::parse_fen;
+#[cfg(test)]
+use crate::notation::pgn::{parse_pgn, write_pgn, PgnGame};
+#[cfg(test)]
+use crate::notation::san::{move_to_san, parse_san};
+
+#[cfg(test)]
+const ANNOTATED_GAME: &str = r#"[Event "Casual \"blitz\""]
+[Site "?"]
+[Date "2021.01.01"]
+[Round "1"]
+[White "Engine A"]
+[Black "Engine B"]
+[Result "1-0"]
+
+{Opening comment} 1. e4 e5 2. Nf3 $1 {Attacks e5} Nc6 (2... d6 3. d4 (3. Bc4 Be7)
+3... exd4) 3. Bb5!? a6 ; rest of line comment
+4. Ba4 Nf6 5. O-O 1-0
+"#;
+
+#[cfg(test)]
+fn san_line(initial_position: GameState, moves: &[Move]) -> Vec<String> {
+    let mut game_state = initial_position;
+    moves
+        .iter()
+        .map(|m| {
+            let san = move_to_san(&game_state, m);
+            game_state.apply_move_mut(*m);
+            san
+        })
+        .collect()
+}
+
+#[test]
+fn tags_moves_and_annotations_are_parsed() {
+    let games = parse_pgn(ANNOTATED_GAME).unwrap();
+    assert_eq!(games.len(), 1);
+
+    let game = &games[0];
+    assert_eq!(game.tag("Event"), Some("Casual \"blitz\""));
+    assert_eq!(game.tag("White"), Some("Engine A"));
+    assert_eq!(game.result, "1-0");
+    assert_eq!(
+        san_line(game.initial_position, &game.main_line()),
+        vec!["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4", "Nf6", "O-O"]
+    );
+
+    assert_eq!(
+        game.moves[0].comment_before.as_deref(),
+        Some("Opening comment")
+    );
+    assert_eq!(game.moves[2].nags, vec![1]);
+    assert_eq!(game.moves[2].comment_after.as_deref(), Some("Attacks e5"));
+    assert_eq!(game.moves[4].nags, vec![5]);
+    assert_eq!(
+        game.moves[5].comment_after.as_deref(),
+        Some("rest of line comment")
+    );
+}
+
+#[test]
+fn variations_replace_the_move_they_follow() {
+    let game = &parse_pgn(ANNOTATED_GAME).unwrap()[0];
+    let after_e5 = parse_pgn("1. e4 e5 *").unwrap()[0].final_position();
+
+    let variations = &game.moves[3].variations;
+    assert_eq!(variations.len(), 1);
+    assert_eq!(variations[0].len(), 3);
+    assert_eq!(
+        variations[0][0].chess_move,
+        parse_san(&after_e5.apply_move(game.moves[2].chess_move), "d6").unwrap()
+    );
+
+    let nested = &variations[0][1].variations;
+    assert_eq!(nested.len(), 1);
+    assert_eq!(nested[0].len(), 2);
+}
+
+#[test]
+fn written_games_parse_back_identically() {
+    let game = &parse_pgn(ANNOTATED_GAME).unwrap()[0];
+    let written = write_pgn(game);
+
+    assert!(written.contains("[Event \"Casual \\\"blitz\\\"\"]"));
+    assert!(written.replace('\n', " ").contains("{Opening comment} 1. e4 e5 2. Nf3 $1 {Attacks e5} 2... Nc6 (2... d6 3. d4 (3. Bc4 Be7) 3... exd4) 3. Bb5 $5"));
+    assert!(written.lines().all(|line| line.len() <= 80));
+    assert_eq!(&parse_pgn(&written).unwrap()[0], game);
+}
+
+#[test]
+fn non_standard_start_writes_fen_tag() {
+    let initial_position = parse_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1").unwrap();
+    let mut game = PgnGame::from_position(initial_position);
+    game.push_move(parse_san(&initial_position, "Kd7").unwrap());
+    game.set_result("1/2-1/2");
+
+    let written = write_pgn(&game);
+    assert!(written.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 1\"]"));
+    assert!(written.contains("[Result \"1/2-1/2\"]"));
+    assert!(written.ends_with("\n1... Kd7 1/2-1/2\n"));
+
+    let parsed = &parse_pgn(&written).unwrap()[0];
+    assert_eq!(parsed.initial_position, initial_position);
+    assert_eq!(parsed.main_line(), game.main_line());
+    assert_eq!(write_pgn(parsed), written);
+}
+
+#[test]
+fn multiple_games_and_fen_move_numbers() {
+    let pgn = r#"[Event "First"]
+
+1. d4 d5 *
+
+[Event "Second"]
+[FEN "r3k3/8/8/8/8/8/8/4K2R w Kq - 0 30"]
+
+30. O-O O-O-O 31. Rd1 0-1
+"#;
+    let games = parse_pgn(pgn).unwrap();
+
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].result, "*");
+    assert_eq!(games[1].moves.len(), 3);
+    assert_eq!(games[1].result, "0-1");
+    assert!(write_pgn(&games[1]).contains("30. O-O O-O-O 31. Rd1 0-1"));
+}
+
+#[test]
+fn illegal_and_malformed_movetext_is_rejected() {
+    assert!(parse_pgn("1. e4 e4 *").unwrap_err().contains("Illegal"));
+    assert!(parse_pgn("1. e4 (1. d4 *").is_err());
+    assert!(parse_pgn("1. e4 {unterminated").is_err());
+    assert!(parse_pgn("[Event \"x\"\n1. e4 *").is_err());
+    assert!(parse_pgn("[FEN \"8/8/8/8/8/8/8/8 w - - 0 1\"]\n*").is_err());
+}
@@ -0,0 +1,140 @@
+#[cfg(test)]
+use crate::model::game_state::{CastlingRights, Color, GameState, Piece, Position};
+#[cfg(test)]
+use crate::model::move_generator::MoveGenerator;
+#[cfg(test)]
+use crate::model::zobrist_hash;
+#[cfg(test)]
+use crate::notation::san::{move_to_san, parse_san};
+#[cfg(test)]
+use crate::search::test_utils;
+
+#[cfg(test)]
+fn position(moves: &[&str]) -> GameState {
+    let mut game_state = GameState::new();
+    test_utils::apply_position(
+        moves.iter().map(|s| s.to_string()),
+        &mut game_state,
+        &MoveGenerator::new(),
+    );
+    game_state
+}
+
+#[cfg(test)]
+fn position_with(pieces: &[(Piece, Color, Position)]) -> GameState {
+    let mut state = GameState::new_empty();
+    state.castling_rights = CastlingRights::none();
+
+    for (piece, color, position) in pieces.iter() {
+        state.set_piece(*piece, *color, *position);
+    }
+
+    state.zobrist_hash = zobrist_hash::hash(&state);
+    state
+}
+
+#[cfg(test)]
+fn san(game_state: &GameState, notation: &str) -> String {
+    move_to_san(game_state, &parse_san(game_state, notation).unwrap())
+}
+
+#[test]
+fn pawn_and_piece_moves() {
+    let game_state = GameState::new();
+
+    assert_eq!(san(&game_state, "e4"), "e4");
+    assert_eq!(san(&game_state, "Nf3"), "Nf3");
+    assert_eq!(san(&game_state, "e2e4"), "e4");
+    assert_eq!(san(&game_state, "Ng1-f3"), "Nf3");
+}
+
+#[test]
+fn check_and_mate_suffixes() {
+    assert_eq!(san(&position(&["e2e4", "d7d6"]), "Bb5"), "Bb5+");
+    assert_eq!(san(&position(&["f2f3", "e7e5", "g2g4"]), "Qh4"), "Qh4#");
+}
+
+#[test]
+fn castling() {
+    let game_state = position(&["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6"]);
+
+    assert_eq!(san(&game_state, "O-O"), "O-O");
+    assert_eq!(san(&game_state, "0-0"), "O-O");
+    assert!(parse_san(&game_state, "O-O-O").is_err());
+}
+
+#[test]
+fn captures_en_passant_and_promotion() {
+    let game_state = position(&["e2e4", "a7a6", "e4e5", "d7d5"]);
+    assert_eq!(san(&game_state, "exd6"), "exd6");
+    assert_eq!(san(&game_state, "exd6 e.p."), "exd6");
+
+    let game_state = position_with(&[
+        (Piece::KING, Color::WHITE, Position::new(3, 1)),
+        (Piece::PAWN, Color::WHITE, Position::new(2, 7)),
+        (Piece::KING, Color::BLACK, Position::new(8, 8)),
+        (Piece::ROOK, Color::BLACK, Position::new(1, 8)),
+    ]);
+    assert_eq!(san(&game_state, "bxa8=Q+"), "bxa8=Q+");
+    assert_eq!(san(&game_state, "bxa8Q"), "bxa8=Q+");
+    assert_eq!(san(&game_state, "b7a8n"), "bxa8=N");
+    assert_eq!(san(&game_state, "b8=R"), "b8=R+");
+    assert!(parse_san(&game_state, "bxa8").is_err());
+}
+
+#[test]
+fn disambiguation() {
+    let rooks = position_with(&[
+        (Piece::KING, Color::WHITE, Position::new(8, 2)),
+        (Piece::ROOK, Color::WHITE, Position::new(1, 1)),
+        (Piece::ROOK, Color::WHITE, Position::new(8, 1)),
+        (Piece::ROOK, Color::WHITE, Position::new(1, 5)),
+        (Piece::KING, Color::BLACK, Position::new(8, 7)),
+    ]);
+    assert_eq!(san(&rooks, "Rad1"), "Rad1");
+    assert_eq!(san(&rooks, "R1a3"), "R1a3");
+    assert!(parse_san(&rooks, "Rd1").unwrap_err().contains("Ambiguous"));
+
+    let queens = position_with(&[
+        (Piece::KING, Color::WHITE, Position::new(8, 1)),
+        (Piece::QUEEN, Color::WHITE, Position::new(1, 1)),
+        (Piece::QUEEN, Color::WHITE, Position::new(3, 1)),
+        (Piece::QUEEN, Color::WHITE, Position::new(1, 3)),
+        (Piece::KING, Color::BLACK, Position::new(8, 7)),
+    ]);
+    assert_eq!(san(&queens, "Qa1b2"), "Qa1b2");
+    assert_eq!(san(&queens, "Qa1xb2"), "Qa1b2");
+}
+
+#[test]
+fn all_legal_moves_round_trip() {
+    let move_generator = MoveGenerator::new();
+    let positions = [
+        GameState::new(),
+        position(&["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "d2d4"]),
+        position(&["d2d4", "d7d5", "c2c4", "e7e6", "b1c3", "g8f6", "c1g5"]),
+    ];
+
+    for game_state in positions.iter() {
+        for m in move_generator.generate_moves(game_state).moves {
+            let notation = move_to_san(game_state, &m);
+            assert_eq!(parse_san(game_state, &notation), Ok(m), "{}", notation);
+        }
+    }
+}
+
+#[test]
+fn invalid_and_illegal_moves_are_rejected() {
+    let game_state = GameState::new();
+
+    assert!(parse_san(&game_state, "Nf6")
+        .unwrap_err()
+        .contains("Illegal"));
+    assert!(parse_san(&game_state, "e5")
+        .unwrap_err()
+        .contains("Illegal"));
+    assert!(parse_san(&game_state, "Zz9")
+        .unwrap_err()
+        .contains("Invalid"));
+    assert!(parse_san(&game_state, "").is_err());
+}
@@ -0,0 +1,60 @@
+use crate::model::game_state::{Color, Move};
+
+// history scores are halved when they grow beyond this, keeping recent cutoffs relevant
+const HISTORY_MAX: i32 = 1 << 20;
+
+// History heuristic: quiet moves that caused beta cutoffs elsewhere in the tree are tried first,
+// indexed by the side to move and the from and to squares of the move.
+pub struct HistoryTable {
+    scores: Box<[[[i32; 64]; 64]; 2]>,
+}
+
+impl HistoryTable {
+    pub fn new() -> HistoryTable {
+        HistoryTable {
+            scores: Box::new([[[0; 64]; 64]; 2]),
+        }
+    }
+
+    pub fn score(&self, color: Color, m: &Move) -> i32 {
+        self.scores[color_index(color)][usize::from(m.from.to_numeric())]
+            [usize::from(m.to.to_numeric())]
+    }
+
+    // deeper cutoffs prune larger subtrees, so they weigh more
+    pub fn update(&mut self, color: Color, m: &Move, depth: u16) {
+        let score = &mut self.scores[color_index(color)][usize::from(m.from.to_numeric())]
+            [usize::from(m.to.to_numeric())];
+        *score += i32::from(depth) * i32::from(depth);
+
+        if *score > HISTORY_MAX {
+            self.age();
+        }
+    }
+
+    // called between searches, so that older results count less
+    pub fn age(&mut self) {
+        for score in self.scores.iter_mut().flatten().flatten() {
+            *score /= 2;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for score in self.scores.iter_mut().flatten().flatten() {
+            *score = 0;
+        }
+    }
+}
+
+impl Default for HistoryTable {
+    fn default() -> Self {
+        HistoryTable::new()
+    }
+}
+
+fn color_index(color: Color) -> usize {
+    match color {
+        Color::WHITE => 0,
+        Color::BLACK => 1,
+    }
+}
@@ -1,414 +1,1014 @@
-use std::sync::mpsc;
-use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::cmp::Reverse;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use crossbeam::thread::scope;
 
-use crate::model::evaluator;
-use crate::model::game_state::{Color, GameState, Move};
+use crate::model::evaluator::{HandcraftedEvaluator, PositionEvaluator};
+use crate::model::game_state::{Color, GameState, Move, MoveType, Piece};
 use crate::model::move_generator::MoveGenerator;
-use crate::search::transposition_table::{MatchType, TranspositionTable};
+#[cfg(feature = "syzygy")]
+use crate::model::syzygy::{Tablebase, Wdl};
+use crate::search::history_table::HistoryTable;
+use crate::search::score;
+use crate::search::score::{Score, MATE_BOUND, MAX_PLY};
+use crate::search::search_control::{ControlPoller, SearchControl, SearchProgress};
+use crate::search::searcher::SearchFeatures;
+use crate::search::shared_transposition_table::SharedTranspositionTable;
+use crate::search::transposition_table::{MatchType, TranspositionStore, TranspositionTable};
 
 // we rely on the invariance of the expression EVAL_MIN = -EVAL_MAX
 // which does not hold for i32::MAX and i32::MIN
 const EVAL_MAX: i32 = i32::MAX;
 const EVAL_MIN: i32 = -EVAL_MAX;
 
+// iterative deepening gives up deepening beyond this depth
+const MAX_DEPTH: u16 = 64;
+
+// half width of the initial aspiration window, a quarter of a pawn
+const ASPIRATION_WINDOW: i32 = 250;
+
+const NULL_MOVE_MIN_DEPTH: u16 = 3;
+const NULL_MOVE_REDUCTION: u16 = 2;
+
+// late move reductions apply to quiet moves after the first few, at sufficient depth
+const LMR_MIN_DEPTH: u16 = 3;
+const LMR_FULL_DEPTH_MOVES: usize = 3;
+const LMR_REDUCTION: u16 = 1;
+
+// pruning based on the static evaluation, margins in evaluation units per remaining depth
+const SHALLOW_PRUNING_DEPTH: u16 = 3;
+const REVERSE_FUTILITY_MARGIN: i32 = 1000;
+const RAZORING_DEPTH: u16 = 2;
+const RAZORING_MARGIN: i32 = 3000;
+const FUTILITY_DEPTH: u16 = 2;
+const FUTILITY_MARGIN: i32 = 2000;
+
 type Evaluation = i32;
 type SearchCount = u64;
 type SearchResult = (Option<Move>, Evaluation, SearchCount);
 
+// a root move with its evaluation and principal variation, which starts with the move itself
+pub(crate) type RootLine = (Move, Evaluation, Vec<Move>);
+
 pub fn negamax_alpha_beta(
     game_state: &mut GameState,
     move_generator: &MoveGenerator,
     depth: u16,
 ) -> SearchResult {
-    negamax_alpha_beta_helper(game_state, move_generator, EVAL_MIN, EVAL_MAX, depth)
+    // never consulted, the table is switched off
+    let mut table = TranspositionTable::with_capacity(1);
+
+    fixed_depth_search(
+        game_state,
+        move_generator,
+        &mut table,
+        &mut HandcraftedEvaluator,
+        SearchFeatures::none(),
+        depth,
+    )
 }
 
-fn negamax_alpha_beta_helper(
+pub fn negamax_alpha_beta_with_trasposition_table(
     game_state: &mut GameState,
     move_generator: &MoveGenerator,
-    alpha: i32,
-    beta: i32,
+    table: &mut TranspositionTable,
     depth: u16,
-) -> SearchResult {
-    let color_multiplier = if game_state.to_move() == Color::WHITE {
-        1
-    } else {
-        -1
-    };
-
-    if depth == 0 {
-        return (None, color_multiplier * evaluator::evaluate(game_state), 1);
-    }
-
-    let next_moves = move_generator.generate_moves(game_state);
-
-    if next_moves.is_checkmate() {
-        return (None, -EVAL_MAX, 1);
-    }
-
-    if next_moves.moves.is_empty() {
-        return (None, color_multiplier * evaluator::evaluate(&game_state), 1);
-    }
+) -> (Option<Move>, i32, u64) {
+    negamax_alpha_beta_with_trasposition_table_and_evaluator(
+        game_state,
+        move_generator,
+        table,
+        &mut HandcraftedEvaluator,
+        depth,
+    )
+}
 
-    // could order moves here ...
+pub fn negamax_alpha_beta_with_trasposition_table_and_evaluator<
+    T: TranspositionStore,
+    E: PositionEvaluator,
+>(
+    game_state: &mut GameState,
+    move_generator: &MoveGenerator,
+    table: &mut T,
+    evaluator: &mut E,
+    depth: u16,
+) -> (Option<Move>, i32, u64) {
+    let features = SearchFeatures {
+        transposition_table: true,
+        ..SearchFeatures::none()
+    };
 
-    let mut best_eval = EVAL_MIN;
-    let mut best_move = None;
-    let mut node_count: u64 = 1;
-    let mut current_alpha = alpha;
+    fixed_depth_search(
+        game_state,
+        move_generator,
+        table,
+        evaluator,
+        features,
+        depth,
+    )
+}
 
-    for next_move in next_moves.moves {
-        game_state.apply_move_mut(next_move);
-        let (_, eval, child_node_count) =
-            negamax_alpha_beta_helper(game_state, move_generator, -beta, -current_alpha, depth - 1);
-        game_state.unapply_move_mut(next_move);
+fn fixed_depth_search<T: TranspositionStore, E: PositionEvaluator>(
+    game_state: &mut GameState,
+    move_generator: &MoveGenerator,
+    table: &mut T,
+    evaluator: &mut E,
+    features: SearchFeatures,
+    depth: u16,
+) -> SearchResult {
+    let control = SearchControl::new();
+    let mut history = HistoryTable::new();
+    let mut search = Search::new(
+        move_generator,
+        table,
+        evaluator,
+        &mut history,
+        features,
+        ControlPoller::new(&control, None),
+    );
+
+    search.table.new_search();
+    search.negamax(game_state, EVAL_MIN, EVAL_MAX, None, depth, 0, true)
+}
 
-        node_count += child_node_count;
+pub fn iterative_alpha_beta(
+    game_state: &mut GameState,
+    move_generator: &MoveGenerator,
+    table: &mut TranspositionTable,
+    search_time: Duration,
+) -> (Option<Move>, Score, u16) {
+    iterative_alpha_beta_with_evaluator(
+        game_state,
+        move_generator,
+        table,
+        &mut HandcraftedEvaluator,
+        search_time,
+    )
+}
 
-        if -eval > best_eval {
-            best_eval = -eval;
-            best_move = Some(next_move);
-        }
+pub fn iterative_alpha_beta_with_evaluator<T: TranspositionStore, E: PositionEvaluator>(
+    game_state: &mut GameState,
+    move_generator: &MoveGenerator,
+    table: &mut T,
+    evaluator: &mut E,
+    search_time: Duration,
+) -> (Option<Move>, Score, u16) {
+    iterative_alpha_beta_with_control(
+        game_state,
+        move_generator,
+        table,
+        evaluator,
+        Some(search_time),
+        &SearchControl::new(),
+    )
+}
 
-        current_alpha = i32::max(current_alpha, best_eval);
+// Iterative deepening until the search time runs out or the control stops the search. Without a
+// search time, the search only ends when stopped through the control or at the maximum depth.
+pub fn iterative_alpha_beta_with_control<T: TranspositionStore, E: PositionEvaluator>(
+    game_state: &mut GameState,
+    move_generator: &MoveGenerator,
+    table: &mut T,
+    evaluator: &mut E,
+    search_time: Option<Duration>,
+    control: &SearchControl,
+) -> (Option<Move>, Score, u16) {
+    let mut history = HistoryTable::new();
+    let mut search = Search::new(
+        move_generator,
+        table,
+        evaluator,
+        &mut history,
+        SearchFeatures::all(),
+        ControlPoller::new(control, search_time),
+    );
 
-        if current_alpha >= beta {
-            break;
-        }
-    }
+    search.table.new_search();
+    let (best_move, best_eval, depth) = search.iterative_deepening(game_state);
 
-    (best_move, best_eval, node_count)
+    (best_move, Score::from_evaluation(best_eval), depth)
 }
 
-pub fn negamax_alpha_beta_with_trasposition_table(
+pub fn parallel_iterative_alpha_beta(
     game_state: &mut GameState,
     move_generator: &MoveGenerator,
-    table: &mut TranspositionTable,
-    depth: u16,
-) -> (Option<Move>, i32, u64) {
-    negamax_alpha_beta_with_trasposition_table_helper(
+    table: &SharedTranspositionTable,
+    threads: usize,
+    search_time: Duration,
+) -> (Option<Move>, Score, u16, u64) {
+    parallel_iterative_alpha_beta_with_control(
         game_state,
         move_generator,
         table,
-        EVAL_MIN,
-        EVAL_MAX,
-        depth,
-        depth,
+        threads,
+        Some(search_time),
+        &SearchControl::new(),
     )
 }
 
-fn negamax_alpha_beta_with_trasposition_table_helper(
+// Returns the best move, its score, the depth reached and the nodes searched by all threads.
+pub fn parallel_iterative_alpha_beta_with_control(
     game_state: &mut GameState,
     move_generator: &MoveGenerator,
-    table: &mut TranspositionTable,
-    alpha: i32,
-    beta: i32,
-    depth: u16,
-    starting_depth: u16,
-) -> SearchResult {
-    let color_multiplier = if game_state.to_move() == Color::WHITE {
-        1
-    } else {
-        -1
-    };
+    table: &SharedTranspositionTable,
+    threads: usize,
+    search_time: Option<Duration>,
+    control: &SearchControl,
+) -> (Option<Move>, Score, u16, u64) {
+    let mut table = table;
+    let mut evaluator = HandcraftedEvaluator;
+    let mut history = HistoryTable::new();
+    let mut search = Search::new(
+        move_generator,
+        &mut table,
+        &mut evaluator,
+        &mut history,
+        SearchFeatures::all(),
+        ControlPoller::new(control, search_time),
+    );
+
+    search.table.new_search();
+    let (best_move, best_eval, depth) = search.parallel_iterative_deepening(game_state, threads);
+
+    (
+        best_move,
+        Score::from_evaluation(best_eval),
+        depth,
+        control.nodes(),
+    )
+}
 
-    let mut current_alpha = alpha;
-    let mut current_beta = beta;
+// The search core behind every entry point. Features that are switched off are skipped, so the
+// same negamax serves both the plain fixed depth searches and the full iterative search.
+pub(crate) struct Search<'a, T: TranspositionStore, E: PositionEvaluator> {
+    move_generator: &'a MoveGenerator,
+    table: &'a mut T,
+    evaluator: &'a mut E,
+    history: &'a mut HistoryTable,
+    features: SearchFeatures,
+    poller: ControlPoller<'a>,
+    multi_pv: usize,
+    // the search is restricted to these root moves when given
+    root_moves: Option<Vec<Move>>,
+    // root moves skipped by the search, the moves of the lines already found at this depth
+    excluded_root_moves: Vec<Move>,
+    // triangular table, the principal variation found for the node at each ply
+    principal_variation: Vec<Vec<Move>>,
+    root_lines: Vec<RootLine>,
+    #[cfg(feature = "syzygy")]
+    tablebase: Option<&'a Tablebase>,
+}
 
-    // on starting depth, do not check transposition table, because we need the move, not only the eval
-    if depth != starting_depth {
-        // transposition table lookup
-        if let Some((match_type, eval)) = table.check(game_state.zobrist_hash, depth) {
-            match match_type {
-                MatchType::EXACT => {
-                    return (None, eval, 0);
-                }
-                MatchType::LOWERBOUND => {
-                    current_alpha = i32::max(current_alpha, eval);
+impl<'a, T: TranspositionStore, E: PositionEvaluator> Search<'a, T, E> {
+    pub(crate) fn new(
+        move_generator: &'a MoveGenerator,
+        table: &'a mut T,
+        evaluator: &'a mut E,
+        history: &'a mut HistoryTable,
+        features: SearchFeatures,
+        poller: ControlPoller<'a>,
+    ) -> Self {
+        Search {
+            move_generator,
+            table,
+            evaluator,
+            history,
+            features,
+            poller,
+            multi_pv: 1,
+            root_moves: None,
+            excluded_root_moves: Vec::new(),
+            principal_variation: vec![Vec::new(); usize::from(MAX_PLY) + 1],
+            root_lines: Vec::new(),
+            #[cfg(feature = "syzygy")]
+            tablebase: None,
+        }
+    }
+
+    // number of best root moves searched with their own principal variation
+    pub(crate) fn with_multi_pv(mut self, multi_pv: usize) -> Self {
+        self.multi_pv = multi_pv.max(1);
+        self
+    }
+
+    pub(crate) fn with_root_moves(mut self, root_moves: Option<Vec<Move>>) -> Self {
+        self.root_moves = root_moves;
+        self
+    }
+
+    #[cfg(feature = "syzygy")]
+    pub(crate) fn with_tablebase(mut self, tablebase: Option<&'a Tablebase>) -> Self {
+        self.tablebase = tablebase;
+        self
+    }
+
+    // lines of the deepest completed iteration, best first
+    pub(crate) fn root_lines(&self) -> &[RootLine] {
+        &self.root_lines
+    }
+
+    // Returns the best move and evaluation of the deepest completed iteration, and its depth. In
+    // MultiPV mode every iteration searches the root once per line, excluding the moves of the
+    // lines already found.
+    pub(crate) fn iterative_deepening(
+        &mut self,
+        game_state: &mut GameState,
+    ) -> (Option<Move>, i32, u16) {
+        // the first iteration visits too few nodes to ever poll the control, so it always completes
+        let mut depth = 1;
+        let mut lines: Vec<RootLine> = Vec::new();
+        while lines.len() < self.multi_pv {
+            self.excluded_root_moves = lines.iter().map(|line| line.0).collect();
+            let (best_move, best_eval, _) =
+                self.negamax(game_state, EVAL_MIN, EVAL_MAX, None, depth, 0, true);
+
+            match best_move {
+                Some(m) => lines.push((m, best_eval, self.principal_variation[0].clone())),
+                // the root has no legal moves, so no depth was searched
+                None if lines.is_empty() => {
+                    self.poller.flush();
+                    return (None, best_eval, 0);
                 }
-                MatchType::UPPERBOUND => {
-                    current_beta = i32::min(current_beta, eval);
+                None => break,
+            }
+        }
+        sort_lines(&mut lines);
+        self.report_lines(depth, &lines);
+
+        let max_depth = self
+            .poller
+            .depth_limit()
+            .map_or(MAX_DEPTH, |limit| u16::min(limit, MAX_DEPTH));
+
+        'iterations: while !self.poller.is_out_of_time()
+            && !self.poller.control().is_stopped()
+            && depth < max_depth
+        {
+            let mut current_lines: Vec<RootLine> = Vec::new();
+
+            for index in 0..self.multi_pv {
+                self.excluded_root_moves = current_lines.iter().map(|line| line.0).collect();
+                let previous_line = lines.get(index).cloned();
+
+                // aspiration window: expect a score close to the previous iteration, and widen the
+                // window on the failing side until the score falls inside it
+                let mut window_size = ASPIRATION_WINDOW;
+                let (mut alpha, mut beta) = match &previous_line {
+                    Some((_, eval, _)) => (
+                        eval.saturating_sub(window_size).max(EVAL_MIN),
+                        eval.saturating_add(window_size),
+                    ),
+                    None => (EVAL_MIN, EVAL_MAX),
+                };
+
+                let line = loop {
+                    let principal_move = previous_line.as_ref().map(|line| &line.0);
+                    let (current_best_move, current_best_eval, _) =
+                        self.negamax(game_state, alpha, beta, principal_move, depth + 1, 0, true);
+
+                    // an interrupted iteration is discarded
+                    if self.poller.is_stopped() {
+                        break 'iterations;
+                    }
+
+                    window_size = window_size.saturating_mul(4);
+
+                    if current_best_eval <= alpha && alpha > EVAL_MIN {
+                        if let Some((_, _, pv)) = &previous_line {
+                            self.report_progress(
+                                depth + 1,
+                                current_best_eval,
+                                MatchType::UPPERBOUND,
+                                index + 1,
+                                pv.clone(),
+                            );
+                        }
+                        alpha = current_best_eval.saturating_sub(window_size).max(EVAL_MIN);
+                    } else if current_best_eval >= beta && beta < EVAL_MAX {
+                        if current_best_move.is_some() {
+                            self.report_progress(
+                                depth + 1,
+                                current_best_eval,
+                                MatchType::LOWERBOUND,
+                                index + 1,
+                                self.principal_variation[0].clone(),
+                            );
+                        }
+                        beta = current_best_eval.saturating_add(window_size);
+                    } else {
+                        let pv = self.principal_variation[0].clone();
+                        break current_best_move.map(|m| (m, current_best_eval, pv));
+                    }
+                };
+
+                match line {
+                    Some(line) => current_lines.push(line),
+                    // fewer legal moves than lines
+                    None => break,
                 }
             }
 
-            if current_alpha >= current_beta {
-                return (None, eval, 0);
+            if current_lines.is_empty() {
+                break;
             }
+            sort_lines(&mut current_lines);
+            lines = current_lines;
+            depth += 1;
+            self.report_lines(depth, &lines);
         }
-    }
 
-    if depth == 0 {
-        let eval = color_multiplier * evaluator::evaluate(&game_state);
-        return (None, eval, 1);
+        self.poller.flush();
+        let (best_move, best_eval, _) = lines[0].clone();
+        self.root_lines = lines;
+        (Some(best_move), best_eval, depth)
     }
 
-    let next_moves = move_generator.generate_moves(game_state);
+    #[allow(clippy::too_many_arguments)]
+    fn negamax(
+        &mut self,
+        game_state: &mut GameState,
+        alpha: i32,
+        beta: i32,
+        principal_move: Option<&Move>,
+        depth: u16,
+        ply: u16,
+        allow_null_move: bool,
+    ) -> SearchResult {
+        // stop when signaled
+        if self.poller.visit_node() {
+            return (None, 0, 0);
+        }
 
-    if next_moves.is_checkmate() {
-        return (
-            None,
-            -(EVAL_MAX - 10_000 * i32::from(starting_depth - depth)),
-            1,
-        );
-    }
+        self.principal_variation[usize::from(ply)].clear();
 
-    if next_moves.moves.is_empty() {
-        return (None, 0, 1);
-    }
+        let features = self.features;
+        let color_multiplier = if game_state.to_move() == Color::WHITE {
+            1
+        } else {
+            -1
+        };
 
-    // could order moves here ...
+        let mut current_alpha = alpha;
+        let mut current_beta = beta;
 
-    let mut best_eval = EVAL_MIN;
-    let mut best_move = None;
-    let mut node_count: u64 = 1;
+        // the last move may have won by the rules of the variant, which scores like checkmate
+        if ply != 0 && game_state.variant().winner(game_state).is_some() {
+            return (None, score::mated_in(ply), 1);
+        }
 
-    for next_move in next_moves.moves {
-        game_state.apply_move_mut(next_move);
-        let (_, eval, child_node_count) = negamax_alpha_beta_with_trasposition_table_helper(
-            game_state,
-            move_generator,
-            table,
-            -current_beta,
-            -current_alpha,
-            depth - 1,
-            starting_depth,
-        );
-        game_state.unapply_move_mut(next_move);
+        // neither side can win, no need to search further
+        if ply != 0 && game_state.is_insufficient_material() {
+            return (None, 0, 1);
+        }
+
+        // the result is known, results decided by the fifty move rule are draws
+        #[cfg(feature = "syzygy")]
+        if let Some(wdl) = self
+            .tablebase
+            .filter(|_| ply != 0)
+            .and_then(|tablebase| tablebase.probe_wdl(game_state))
+        {
+            let eval = match wdl {
+                Wdl::Win => score::tablebase_win_in(ply),
+                Wdl::Loss => -score::tablebase_win_in(ply),
+                _ => 0,
+            };
+            return (None, eval, 1);
+        }
+
+        // mate distance pruning: no line from here is better than mating with the next move, or
+        // worse than being mated right now
+        if features.pruning && ply != 0 {
+            current_alpha = i32::max(current_alpha, score::mated_in(ply));
+            current_beta = i32::min(current_beta, -score::mated_in(ply + 1));
 
-        node_count += child_node_count;
+            if current_alpha >= current_beta {
+                return (None, current_alpha, 1);
+            }
+        }
+
+        // on the root, do not check transposition table, because we need the move, not only the eval
+        if features.transposition_table && ply != 0 {
+            if let Some((match_type, table_eval)) = self.table.check(game_state.zobrist_hash, depth)
+            {
+                let eval = score::from_table(table_eval, ply);
+                match match_type {
+                    MatchType::EXACT => {
+                        return (None, eval, 0);
+                    }
+                    MatchType::LOWERBOUND => {
+                        current_alpha = i32::max(current_alpha, eval);
+                    }
+                    MatchType::UPPERBOUND => {
+                        current_beta = i32::min(current_beta, eval);
+                    }
+                }
 
-        if -eval > best_eval {
-            best_eval = -eval;
-            best_move = Some(next_move);
+                if current_alpha >= current_beta {
+                    return (None, eval, 0);
+                }
+            }
+        }
+
+        if depth == 0 || ply >= MAX_PLY {
+            let eval = color_multiplier * self.evaluator.evaluate(game_state);
+            return (None, eval, 1);
         }
 
-        current_alpha = i32::max(current_alpha, best_eval);
+        let mut next_moves = self.move_generator.generate_moves(game_state);
 
-        if current_alpha >= current_beta {
-            break;
+        if next_moves.is_checkmate() {
+            return (None, score::mated_in(ply), 1);
         }
-    }
 
-    let match_type = if best_eval <= alpha {
-        MatchType::UPPERBOUND
-    } else if best_eval >= current_beta {
-        MatchType::LOWERBOUND
-    } else {
-        MatchType::EXACT
-    };
+        if next_moves.moves.is_empty() {
+            return (None, 0, 1);
+        }
 
-    table.update(game_state.zobrist_hash, depth, best_eval, match_type);
+        if ply == 0 && self.is_root_restricted() {
+            let root_moves = &self.root_moves;
+            let excluded = &self.excluded_root_moves;
+            next_moves.moves.retain(|m| {
+                root_moves.as_ref().map_or(true, |moves| moves.contains(m)) && !excluded.contains(m)
+            });
 
-    (best_move, best_eval, node_count)
-}
+            if next_moves.moves.is_empty() {
+                return (None, EVAL_MIN, 1);
+            }
+        }
 
-fn negamax_alpha_beta_with_trasposition_table_and_principal_variation(
-    game_state: &mut GameState,
-    move_generator: &MoveGenerator,
-    table: &mut TranspositionTable,
-    principal_move: Option<&Move>,
-    depth: u16,
-    stop: Arc<Mutex<bool>>,
-) -> (Option<Move>, i32, u64) {
-    negamax_alpha_beta_with_trasposition_table_and_principal_variation_helper(
-        game_state,
-        move_generator,
-        table,
-        EVAL_MIN,
-        EVAL_MAX,
-        principal_move,
-        depth,
-        depth,
-        stop,
-    )
-}
+        // check extension: evasions are searched one ply deeper, so that forcing lines are not cut
+        // short by the horizon
+        let child_depth = if features.check_extensions && next_moves.is_check {
+            depth
+        } else {
+            depth - 1
+        };
+
+        let mut node_count: u64 = 1;
+
+        // Shallow depth pruning relies on the static evaluation. It is not applied in check, at the
+        // root, or when the window holds mate scores.
+        let shallow = features.shallow_pruning
+            && ply != 0
+            && depth <= SHALLOW_PRUNING_DEPTH
+            && !next_moves.is_check
+            && current_alpha > -MATE_BOUND
+            && current_beta < MATE_BOUND;
+        let static_eval = if shallow {
+            color_multiplier * self.evaluator.evaluate(game_state)
+        } else {
+            0
+        };
+
+        // reverse futility pruning: the position is so far above beta that no reply is expected to
+        // bring it back into the window
+        if shallow && static_eval - REVERSE_FUTILITY_MARGIN * i32::from(depth) >= current_beta {
+            return (None, static_eval, node_count);
+        }
 
-fn negamax_alpha_beta_with_trasposition_table_and_principal_variation_helper(
-    game_state: &mut GameState,
-    move_generator: &MoveGenerator,
-    table: &mut TranspositionTable,
-    alpha: i32,
-    beta: i32,
-    principal_move: Option<&Move>,
-    depth: u16,
-    starting_depth: u16,
-    stop: Arc<Mutex<bool>>,
-) -> (Option<Move>, i32, u64) {
-    // stop when signaled
-    if *stop.lock().unwrap() {
-        return (None, 0, 0);
-    }
+        // razoring: the position is so far below alpha that only captures could save it, which is
+        // verified with a quiescence search
+        if shallow
+            && depth <= RAZORING_DEPTH
+            && static_eval + RAZORING_MARGIN * i32::from(depth) < current_alpha
+        {
+            let (eval, child_node_count) =
+                self.quiescence(game_state, current_alpha, current_alpha + 1, ply);
+            node_count += child_node_count;
+
+            if eval <= current_alpha {
+                return (None, eval, node_count);
+            }
+        }
 
-    let color_multiplier = if game_state.to_move() == Color::WHITE {
-        1
-    } else {
-        -1
-    };
+        // futility pruning: quiet moves cannot raise a position this far below alpha
+        let futile = shallow
+            && depth <= FUTILITY_DEPTH
+            && static_eval + FUTILITY_MARGIN * i32::from(depth) <= current_alpha;
+
+        // null move pruning: if passing the turn still fails high in a reduced search, a real move
+        // would do so too. Not valid in check, and not when only pawns are left, where being forced
+        // to move can be a disadvantage.
+        if features.pruning
+            && allow_null_move
+            && ply != 0
+            && depth >= NULL_MOVE_MIN_DEPTH
+            && !next_moves.is_check
+            && beta < MATE_BOUND
+            && game_state.has_non_pawn_material(game_state.to_move())
+        {
+            game_state.apply_null_move_mut();
+            let (_, eval, child_node_count) = self.negamax(
+                game_state,
+                -beta,
+                -beta + 1,
+                principal_move,
+                depth - 1 - NULL_MOVE_REDUCTION,
+                ply + 1,
+                false,
+            );
+            game_state.unapply_null_move_mut();
+            node_count += child_node_count;
 
-    let mut current_alpha = alpha;
-    let mut current_beta = beta;
+            if -eval >= beta && !self.poller.is_stopped() {
+                return (None, beta, node_count);
+            }
+        }
 
-    // on starting depth, do not check transposition table, because we need the move, not only the eval
-    if depth != starting_depth {
-        // transposition table lookup
-        if let Some((match_type, eval)) = table.check(game_state.zobrist_hash, depth) {
-            match match_type {
-                MatchType::EXACT => {
-                    return (None, eval, 0);
-                }
-                MatchType::LOWERBOUND => {
-                    current_alpha = i32::max(current_alpha, eval);
+        if features.move_ordering {
+            // below the root, the best move stored for the position is searched first
+            let table_move = if features.transposition_table && ply != 0 {
+                self.table
+                    .best_move(game_state.zobrist_hash)
+                    .and_then(|m| m.unpack(game_state))
+            } else {
+                None
+            };
+            self.order_moves(
+                game_state.to_move(),
+                &mut next_moves.moves,
+                table_move.as_ref().or(principal_move),
+            );
+        }
+
+        let mut best_eval = EVAL_MIN;
+        let mut best_move = None;
+
+        for (index, next_move) in next_moves.moves.into_iter().enumerate() {
+            // late move pruning: at shallow depth, quiet moves this late in the ordering are skipped
+            let prunable = index > 0
+                && is_quiet(&next_move)
+                && best_eval > -MATE_BOUND
+                && (futile || shallow && index >= late_move_count(depth));
+
+            self.evaluator.apply_move(game_state, next_move);
+            game_state.apply_move_mut(next_move);
+
+            // moves giving check are never pruned, they may be the start of a mating attack
+            if prunable
+                && !self
+                    .move_generator
+                    .is_check(game_state, game_state.to_move())
+            {
+                game_state.unapply_move_mut(next_move);
+                self.evaluator.unapply_move(game_state, next_move);
+                continue;
+            }
+
+            // principal variation search: the first move is searched with the full window, the
+            // rest only need to be proven worse with a null window, and are searched again if they
+            // are not. Late quiet moves are unlikely to be best, so they are first searched with
+            // reduced depth.
+            let eval = if index == 0 || !features.pruning {
+                self.search_child(
+                    game_state,
+                    current_alpha,
+                    current_beta,
+                    principal_move,
+                    child_depth,
+                    ply,
+                    &mut node_count,
+                )
+            } else {
+                let reduction = if depth >= LMR_MIN_DEPTH
+                    && index >= LMR_FULL_DEPTH_MOVES
+                    && !next_moves.is_check
+                    && is_quiet(&next_move)
+                {
+                    LMR_REDUCTION
+                } else {
+                    0
+                };
+
+                let mut eval = self.search_child(
+                    game_state,
+                    current_alpha,
+                    current_alpha + 1,
+                    principal_move,
+                    child_depth - reduction,
+                    ply,
+                    &mut node_count,
+                );
+                if reduction > 0 && eval > current_alpha {
+                    eval = self.search_child(
+                        game_state,
+                        current_alpha,
+                        current_alpha + 1,
+                        principal_move,
+                        child_depth,
+                        ply,
+                        &mut node_count,
+                    );
                 }
-                MatchType::UPPERBOUND => {
-                    current_beta = i32::min(current_beta, eval);
+                if eval > current_alpha && eval < current_beta {
+                    eval = self.search_child(
+                        game_state,
+                        current_alpha,
+                        current_beta,
+                        principal_move,
+                        child_depth,
+                        ply,
+                        &mut node_count,
+                    );
                 }
+                eval
+            };
+            game_state.unapply_move_mut(next_move);
+            self.evaluator.unapply_move(game_state, next_move);
+
+            if eval > best_eval {
+                best_eval = eval;
+                best_move = Some(next_move);
+                self.update_principal_variation(ply, next_move);
             }
 
+            current_alpha = i32::max(current_alpha, best_eval);
+
             if current_alpha >= current_beta {
-                return (None, eval, 0);
+                if features.move_ordering && is_quiet(&next_move) {
+                    self.history.update(game_state.to_move(), &next_move, depth);
+                }
+                break;
             }
         }
-    }
 
-    if depth == 0 {
-        let eval = color_multiplier * evaluator::evaluate(&game_state);
-        return (None, eval, 1);
-    }
+        // the evaluation of an interrupted search is incomplete, do not store it
+        if self.poller.is_stopped() {
+            return (None, 0, node_count);
+        }
 
-    let mut next_moves = move_generator.generate_moves(game_state);
+        // the root evaluation of a subset of the moves is not the evaluation of the position
+        if features.transposition_table && (ply != 0 || !self.is_root_restricted()) {
+            let match_type = if best_eval <= alpha {
+                MatchType::UPPERBOUND
+            } else if best_eval >= current_beta {
+                MatchType::LOWERBOUND
+            } else {
+                MatchType::EXACT
+            };
 
-    if next_moves.is_checkmate() {
-        return (
-            None,
-            -(EVAL_MAX - 10_000 * i32::from(starting_depth - depth)),
-            1,
-        );
-    }
+            self.table.update(
+                game_state.zobrist_hash,
+                depth,
+                score::to_table(best_eval, ply),
+                match_type,
+                best_move.map(|m: Move| m.pack()),
+            );
+        }
 
-    if next_moves.moves.is_empty() {
-        return (None, 0, 1);
+        (best_move, best_eval, node_count)
     }
 
-    //order moves, taking the principal variation first if available
-    if let Some(index) = next_moves
-        .moves
-        .iter()
-        .position(|m| Some(m) == principal_move)
-    {
-        next_moves.moves.swap(0, index);
+    // searches the position after a move from the point of view of the side that made it
+    #[allow(clippy::too_many_arguments)]
+    fn search_child(
+        &mut self,
+        game_state: &mut GameState,
+        alpha: i32,
+        beta: i32,
+        principal_move: Option<&Move>,
+        depth: u16,
+        ply: u16,
+        node_count: &mut u64,
+    ) -> i32 {
+        let (_, eval, child_node_count) = self.negamax(
+            game_state,
+            -beta,
+            -alpha,
+            principal_move,
+            depth,
+            ply + 1,
+            true,
+        );
+        *node_count += child_node_count;
+        -eval
     }
 
-    let mut best_eval = EVAL_MIN;
-    let mut best_move = None;
-    let mut node_count: u64 = 1;
+    // Searches captures and promotions until the position is quiet, so that the evaluation is not
+    // taken in the middle of an exchange. The side to move may also stand pat on the static
+    // evaluation.
+    fn quiescence(
+        &mut self,
+        game_state: &mut GameState,
+        alpha: i32,
+        beta: i32,
+        ply: u16,
+    ) -> (i32, u64) {
+        if self.poller.visit_node() {
+            return (0, 0);
+        }
 
-    for next_move in next_moves.moves {
-        game_state.apply_move_mut(next_move);
-        let (_, eval, child_node_count) =
-            negamax_alpha_beta_with_trasposition_table_and_principal_variation_helper(
-                game_state,
-                move_generator,
-                table,
-                -current_beta,
-                -current_alpha,
-                principal_move,
-                depth - 1,
-                starting_depth,
-                stop.clone(),
-            );
-        game_state.unapply_move_mut(next_move);
+        if game_state.variant().winner(game_state).is_some() {
+            return (score::mated_in(ply), 1);
+        }
 
-        node_count += child_node_count;
+        let color_multiplier = if game_state.to_move() == Color::WHITE {
+            1
+        } else {
+            -1
+        };
 
-        if -eval > best_eval {
-            best_eval = -eval;
-            best_move = Some(next_move);
+        let stand_pat = color_multiplier * self.evaluator.evaluate(game_state);
+        if stand_pat >= beta || ply >= MAX_PLY {
+            return (stand_pat, 1);
         }
 
-        current_alpha = i32::max(current_alpha, best_eval);
-
-        if current_alpha >= current_beta {
-            break;
+        let mut captures: Vec<Move> = self
+            .move_generator
+            .generate_moves(game_state)
+            .moves
+            .into_iter()
+            .filter(|m| !is_quiet(m))
+            .collect();
+        self.order_moves(game_state.to_move(), &mut captures, None);
+
+        let mut best_eval = stand_pat;
+        let mut current_alpha = i32::max(alpha, stand_pat);
+        let mut node_count: u64 = 1;
+
+        for capture in captures {
+            self.evaluator.apply_move(game_state, capture);
+            game_state.apply_move_mut(capture);
+            let (eval, child_node_count) =
+                self.quiescence(game_state, -beta, -current_alpha, ply + 1);
+            game_state.unapply_move_mut(capture);
+            self.evaluator.unapply_move(game_state, capture);
+
+            node_count += child_node_count;
+            best_eval = i32::max(best_eval, -eval);
+            current_alpha = i32::max(current_alpha, best_eval);
+
+            if current_alpha >= beta {
+                break;
+            }
         }
+
+        (best_eval, node_count)
     }
 
-    let match_type = if best_eval <= alpha {
-        MatchType::UPPERBOUND
-    } else if best_eval >= current_beta {
-        MatchType::LOWERBOUND
-    } else {
-        MatchType::EXACT
-    };
+    // principal move first, then captures and promotions with the most valuable victim and the
+    // least valuable attacker first, then quiet moves by their history score
+    fn order_moves(&self, to_move: Color, moves: &mut [Move], principal_move: Option<&Move>) {
+        moves.sort_by_key(|m| {
+            if Some(m) == principal_move {
+                return (0, 0);
+            }
 
-    table.update(game_state.zobrist_hash, depth, best_eval, match_type);
+            let victim = match m.move_type {
+                MoveType::Capture(piece) => piece_order_value(piece),
+                MoveType::EnPassant => piece_order_value(Piece::PAWN),
+                _ => 0,
+            };
+            let promotion = m.promotes_to.map_or(0, piece_order_value);
+
+            if victim + promotion == 0 {
+                (2, -self.history.score(to_move, m))
+            } else {
+                (
+                    1,
+                    -(10 * (victim + promotion) - piece_order_value(m.moving_piece)),
+                )
+            }
+        });
+    }
 
-    (best_move, best_eval, node_count)
-}
+    fn is_root_restricted(&self) -> bool {
+        self.root_moves.is_some() || !self.excluded_root_moves.is_empty()
+    }
 
-pub fn iterative_alpha_beta(
-    game_state: &mut GameState,
-    move_generator: &MoveGenerator,
-    table: &mut TranspositionTable,
-    search_time: Duration,
-) -> (Option<Move>, i32, u16) {
-    let start = Instant::now();
-
-    let stop_signal = Arc::new(Mutex::new(false));
-    let mut depth = 1;
-    let stop_signal_clone = stop_signal.clone();
-    let (init_best_move, init_best_eval, _) =
-        negamax_alpha_beta_with_trasposition_table_and_principal_variation(
-            game_state,
-            move_generator,
-            table,
-            None,
-            1,
-            stop_signal_clone,
-        );
-    let mut best_move = match init_best_move {
-        Some(m) => m,
-        None => return (None, init_best_eval, depth),
-    };
-    let mut best_eval = init_best_eval;
+    // the principal variation of a node is its best move followed by that of the child
+    fn update_principal_variation(&mut self, ply: u16, best_move: Move) {
+        let (node, children) = self.principal_variation.split_at_mut(usize::from(ply) + 1);
+        let pv = &mut node[usize::from(ply)];
+        pv.clear();
+        pv.push(best_move);
+        if let Some(child_pv) = children.first() {
+            pv.extend_from_slice(child_pv);
+        }
+    }
+
+    fn report_lines(&self, depth: u16, lines: &[RootLine]) {
+        for (index, (_, eval, pv)) in lines.iter().enumerate() {
+            self.report_progress(depth, *eval, MatchType::EXACT, index + 1, pv.clone());
+        }
+    }
+
+    fn report_progress(
+        &self,
+        depth: u16,
+        evaluation: i32,
+        bound: MatchType,
+        multipv: usize,
+        pv: Vec<Move>,
+    ) {
+        let control = self.poller.control();
+        control.report(&SearchProgress {
+            depth,
+            multipv,
+            score: Score::from_evaluation(evaluation),
+            bound,
+            best_move: pv[0],
+            pv,
+            nodes: self.poller.nodes(),
+            elapsed: control.elapsed(),
+        });
+    }
+}
 
-    while start.elapsed() < search_time {
-        depth += 1;
-        let (sender, receiver) = mpsc::channel();
-        let stop_signal_clone = stop_signal.clone();
-        let best_move_clone = best_move.clone();
+impl<'a, 'b, E: PositionEvaluator + Clone + Send> Search<'a, &'b SharedTranspositionTable, E> {
+    // Lazy SMP: helper threads run their own iterative deepening over the shared table, filling
+    // it with results the main thread can use. The best move is decided by the main thread alone.
+    pub(crate) fn parallel_iterative_deepening(
+        &mut self,
+        game_state: &mut GameState,
+        threads: usize,
+    ) -> (Option<Move>, i32, u16) {
+        let move_generator = self.move_generator;
+        let table = *self.table;
+        let features = self.features;
+        #[cfg(feature = "syzygy")]
+        let tablebase = self.tablebase;
+        let finished = AtomicBool::new(false);
 
         scope(|s| {
-            s.spawn(|_| {
-                let sender = sender;
-                let (current_best_move, current_best_eval, _) =
-                    negamax_alpha_beta_with_trasposition_table_and_principal_variation(
-                        game_state,
+            for helper in 1..threads {
+                let mut helper_state = *game_state;
+                let mut evaluator = self.evaluator.clone();
+                let root_moves = self.root_moves.clone();
+                let poller = self.poller.helper(&finished);
+
+                s.spawn(move |_| {
+                    let mut table = table;
+                    let mut history = HistoryTable::new();
+                    let mut search = Search::new(
                         move_generator,
-                        table,
-                        Some(&best_move_clone),
-                        depth,
-                        stop_signal_clone,
-                    );
-                sender.send((current_best_move, current_best_eval)).unwrap();
-            });
-
-            match receiver.recv_timeout(search_time - start.elapsed()) {
-                Ok((cur_best_move, cur_best_eval)) => {
-                    if let Some(m) = cur_best_move {
-                        best_move = m;
+                        &mut table,
+                        &mut evaluator,
+                        &mut history,
+                        features,
+                        poller,
+                    )
+                    .with_root_moves(root_moves);
+                    #[cfg(feature = "syzygy")]
+                    {
+                        search.tablebase = tablebase;
                     }
-                    best_eval = cur_best_eval;
-                }
-                Err(_) => {
-                    *stop_signal.lock().unwrap() = true;
-                }
-            };
+                    let mut best_move = None;
+                    // start helpers at different depths, so that the threads search different trees
+                    let mut depth = 1 + (helper % 2) as u16;
+
+                    while !search.poller.is_stopped() && depth <= MAX_DEPTH {
+                        let (current_best_move, _, _) = search.negamax(
+                            &mut helper_state,
+                            EVAL_MIN,
+                            EVAL_MAX,
+                            best_move.as_ref(),
+                            depth,
+                            0,
+                            true,
+                        );
+
+                        if current_best_move.is_none() {
+                            break;
+                        }
+                        best_move = current_best_move;
+                        depth += 1;
+                    }
+
+                    search.poller.flush();
+                });
+            }
+
+            let result = self.iterative_deepening(game_state);
+            finished.store(true, Ordering::Relaxed);
+            result
         })
-        .unwrap();
+        .unwrap()
     }
+}
 
-    (Some(best_move), best_eval, depth)
+// best evaluation first, keeping the search order of equal lines
+fn sort_lines(lines: &mut [RootLine]) {
+    lines.sort_by_key(|line| Reverse(line.1));
+}
+
+// number of moves searched before late move pruning applies at the given depth
+fn late_move_count(depth: u16) -> usize {
+    5 + 2 * usize::from(depth * depth)
+}
+
+fn is_quiet(m: &Move) -> bool {
+    matches!(m.move_type, MoveType::Step | MoveType::Castling) && m.promotes_to.is_none()
+}
+
+fn piece_order_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::PAWN => 1,
+        Piece::KNIGHT | Piece::BISHOP => 3,
+        Piece::ROOK => 5,
+        Piece::QUEEN => 9,
+        Piece::KING => 100,
+    }
 }
@@ -1,4 +1,10 @@
+pub mod history_table;
 pub mod minimax_search;
+pub mod score;
+pub mod search_control;
+pub mod searcher;
+pub mod shared_transposition_table;
 pub mod test_utils;
 mod tests;
+mod transposition_entry;
 pub mod transposition_table;
@@ -0,0 +1,69 @@
+// Evaluation of checkmate, reduced by the number of plies until the mate is delivered, so that
+// faster mates score higher. Far above any evaluation of a position, but leaving room for the
+// search window bounds.
+pub const MATE: i32 = 1_000_000_000;
+
+// the search never goes deeper than this many plies from the root
+pub const MAX_PLY: u16 = 128;
+
+// evaluations beyond this bound are checkmates
+pub const MATE_BOUND: i32 = MATE - MAX_PLY as i32;
+
+// positions won according to the tablebases score below every checkmate, but above any evaluation
+pub const TABLEBASE_WIN: i32 = MATE_BOUND - MAX_PLY as i32;
+
+// evaluations beyond this bound are tablebase wins or checkmates
+pub const TABLEBASE_BOUND: i32 = TABLEBASE_WIN - MAX_PLY as i32;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Score {
+    // evaluation from the point of view of the side to move, a pawn is worth 1000
+    Evaluation(i32),
+    // full moves until checkmate, negative when the side to move gets mated and 0 when it is
+    // checkmated already
+    MateIn(i32),
+}
+
+impl Score {
+    pub fn from_evaluation(evaluation: i32) -> Score {
+        if evaluation >= MATE_BOUND {
+            Score::MateIn((MATE - evaluation + 1) / 2)
+        } else if evaluation <= -MATE_BOUND {
+            Score::MateIn(-(MATE + evaluation + 1) / 2)
+        } else {
+            Score::Evaluation(evaluation)
+        }
+    }
+}
+
+// evaluation of the side to move being checkmated at the given ply
+pub fn mated_in(ply: u16) -> i32 {
+    -(MATE - i32::from(ply))
+}
+
+// evaluation of a tablebase win reached at the given ply, preferring the shortest way there
+pub fn tablebase_win_in(ply: u16) -> i32 {
+    TABLEBASE_WIN - i32::from(ply)
+}
+
+// The transposition table stores mate and tablebase scores relative to the stored position rather
+// than to the root, so that they stay valid when the position is reached at another ply.
+pub(crate) fn to_table(evaluation: i32, ply: u16) -> i32 {
+    if evaluation >= TABLEBASE_BOUND {
+        evaluation + i32::from(ply)
+    } else if evaluation <= -TABLEBASE_BOUND {
+        evaluation - i32::from(ply)
+    } else {
+        evaluation
+    }
+}
+
+pub(crate) fn from_table(evaluation: i32, ply: u16) -> i32 {
+    if evaluation >= TABLEBASE_BOUND {
+        evaluation - i32::from(ply)
+    } else if evaluation <= -TABLEBASE_BOUND {
+        evaluation + i32::from(ply)
+    } else {
+        evaluation
+    }
+}
@@ -0,0 +1,233 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::model::game_state::Move;
+use crate::search::score::Score;
+use crate::search::searcher::SearchLimits;
+use crate::search::transposition_table::MatchType;
+
+// number of nodes a search thread visits between polls of the shared control
+const CHECK_INTERVAL: u64 = 1024;
+
+// The score is only a bound when the iteration failed high (lower bound) or low (upper bound)
+// and is searched again. In MultiPV mode, multipv is the rank of the line, starting from 1.
+pub struct SearchProgress {
+    pub depth: u16,
+    pub multipv: usize,
+    pub score: Score,
+    pub bound: MatchType,
+    pub best_move: Move,
+    pub pv: Vec<Move>,
+    pub nodes: u64,
+    pub elapsed: Duration,
+}
+
+type ProgressCallback = Box<dyn Fn(&SearchProgress) + Send + Sync>;
+
+// Handle for controlling a running search, shared by the caller and all search threads. The
+// search can be stopped from another thread, limited by the number of nodes searched or by
+// depth, or started in ponder mode, in which case the time limit only applies after ponderhit.
+// A control is meant for a single search, a stopped control stops every search it is passed to.
+pub struct SearchControl {
+    stopped: AtomicBool,
+    pondering: AtomicBool,
+    nodes: AtomicU64,
+    node_limit: Option<u64>,
+    depth_limit: Option<u16>,
+    clock_start: Mutex<Instant>,
+    progress: Option<ProgressCallback>,
+}
+
+impl SearchControl {
+    pub fn new() -> SearchControl {
+        SearchControl {
+            stopped: AtomicBool::new(false),
+            pondering: AtomicBool::new(false),
+            nodes: AtomicU64::new(0),
+            node_limit: None,
+            depth_limit: None,
+            clock_start: Mutex::new(Instant::now()),
+            progress: None,
+        }
+    }
+
+    pub fn with_node_limit(mut self, node_limit: u64) -> SearchControl {
+        self.node_limit = Some(node_limit);
+        self
+    }
+
+    // iterative deepening ends after completing this depth
+    pub fn with_depth_limit(mut self, depth_limit: u16) -> SearchControl {
+        self.depth_limit = Some(depth_limit);
+        self
+    }
+
+    pub fn with_ponder(self) -> SearchControl {
+        self.pondering.store(true, Ordering::Relaxed);
+        self
+    }
+
+    // called after every iteration of iterative deepening, and when an iteration is searched again
+    pub fn with_progress<F: Fn(&SearchProgress) + Send + Sync + 'static>(
+        mut self,
+        callback: F,
+    ) -> SearchControl {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    // the opponent played the expected move, so the time limit starts counting from now
+    pub fn ponderhit(&self) {
+        *self.clock_start.lock().unwrap() = Instant::now();
+        self.pondering.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_pondering(&self) -> bool {
+        self.pondering.load(Ordering::Relaxed)
+    }
+
+    // nodes reported by the search threads so far
+    pub fn nodes(&self) -> u64 {
+        self.nodes.load(Ordering::Relaxed)
+    }
+
+    // the search time counts from the creation of the control, or from ponderhit
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.clock_start.lock().unwrap().elapsed()
+    }
+
+    pub(crate) fn report(&self, progress: &SearchProgress) {
+        if let Some(callback) = &self.progress {
+            callback(progress);
+        }
+    }
+}
+
+impl Default for SearchControl {
+    fn default() -> Self {
+        SearchControl::new()
+    }
+}
+
+// Per thread view of the control. Nodes are counted locally and the shared state is only polled
+// every CHECK_INTERVAL nodes, so the search does not touch shared memory on every node. A thread
+// hitting the time or node limit stops all other threads of the search as well.
+pub(crate) struct ControlPoller<'a> {
+    control: &'a SearchControl,
+    search_time: Option<Duration>,
+    node_limit: Option<u64>,
+    depth_limit: Option<u16>,
+    // set when the main thread of a parallel search is done, stopping its helpers
+    finished: Option<&'a AtomicBool>,
+    unreported_nodes: u64,
+    stopped: bool,
+}
+
+impl<'a> ControlPoller<'a> {
+    pub(crate) fn new(control: &'a SearchControl, search_time: Option<Duration>) -> Self {
+        ControlPoller {
+            control,
+            search_time,
+            node_limit: control.node_limit,
+            depth_limit: control.depth_limit,
+            finished: None,
+            unreported_nodes: 0,
+            stopped: false,
+        }
+    }
+
+    // the tighter of the limits of the control and the given limits applies
+    pub(crate) fn with_limits(control: &'a SearchControl, limits: &SearchLimits) -> Self {
+        ControlPoller {
+            node_limit: tighter(control.node_limit, limits.nodes),
+            depth_limit: tighter(control.depth_limit, limits.depth),
+            ..ControlPoller::new(control, limits.time)
+        }
+    }
+
+    // helper threads of a parallel search run until the main thread is finished
+    pub(crate) fn helper<'b>(&self, finished: &'b AtomicBool) -> ControlPoller<'b>
+    where
+        'a: 'b,
+    {
+        ControlPoller {
+            control: self.control,
+            search_time: None,
+            node_limit: self.node_limit,
+            depth_limit: None,
+            finished: Some(finished),
+            unreported_nodes: 0,
+            stopped: false,
+        }
+    }
+
+    pub(crate) fn control(&self) -> &'a SearchControl {
+        self.control
+    }
+
+    pub(crate) fn depth_limit(&self) -> Option<u16> {
+        self.depth_limit
+    }
+
+    // counts a visited node, returns true when the search should stop
+    pub(crate) fn visit_node(&mut self) -> bool {
+        self.unreported_nodes += 1;
+        if self.unreported_nodes >= CHECK_INTERVAL {
+            self.poll();
+        }
+        self.stopped
+    }
+
+    pub(crate) fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    pub(crate) fn is_out_of_time(&self) -> bool {
+        match self.search_time {
+            Some(search_time) => {
+                !self.control.is_pondering() && self.control.elapsed() >= search_time
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn nodes(&self) -> u64 {
+        self.control.nodes() + self.unreported_nodes
+    }
+
+    pub(crate) fn flush(&mut self) {
+        self.control
+            .nodes
+            .fetch_add(self.unreported_nodes, Ordering::Relaxed);
+        self.unreported_nodes = 0;
+    }
+
+    fn poll(&mut self) {
+        self.flush();
+
+        let node_limit_reached = matches!(
+            self.node_limit,
+            Some(limit) if self.control.nodes() >= limit
+        );
+
+        if node_limit_reached || self.is_out_of_time() {
+            self.control.stop();
+        }
+
+        self.stopped = self.control.is_stopped()
+            || matches!(self.finished, Some(finished) if finished.load(Ordering::Relaxed));
+    }
+}
+
+fn tighter<T: Ord>(first: Option<T>, second: Option<T>) -> Option<T> {
+    first.into_iter().chain(second).min()
+}
@@ -0,0 +1,328 @@
+#[cfg(feature = "syzygy")]
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::model::evaluator::{HandcraftedEvaluator, PositionEvaluator};
+use crate::model::game_state::{GameState, Move};
+use crate::model::move_generator::MoveGenerator;
+#[cfg(feature = "syzygy")]
+use crate::model::syzygy::Tablebase;
+use crate::search::history_table::HistoryTable;
+use crate::search::minimax_search::Search;
+use crate::search::score::Score;
+use crate::search::search_control::{ControlPoller, SearchControl};
+use crate::search::shared_transposition_table::SharedTranspositionTable;
+
+const DEFAULT_TABLE_SIZE_MB: usize = 16;
+
+// Parts of the search that can be switched off, for example to measure what they are worth.
+// Pruning covers null window searches, null move pruning, late move reductions and mate distance
+// pruning. Shallow pruning covers futility and reverse futility pruning, razoring and late move
+// pruning near the leaves.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SearchFeatures {
+    pub transposition_table: bool,
+    pub move_ordering: bool,
+    pub pruning: bool,
+    pub shallow_pruning: bool,
+    pub check_extensions: bool,
+}
+
+impl SearchFeatures {
+    pub fn all() -> SearchFeatures {
+        SearchFeatures {
+            transposition_table: true,
+            move_ordering: true,
+            pruning: true,
+            shallow_pruning: true,
+            check_extensions: true,
+        }
+    }
+
+    // plain alpha-beta
+    pub fn none() -> SearchFeatures {
+        SearchFeatures {
+            transposition_table: false,
+            move_ordering: false,
+            pruning: false,
+            shallow_pruning: false,
+            check_extensions: false,
+        }
+    }
+}
+
+impl Default for SearchFeatures {
+    fn default() -> Self {
+        SearchFeatures::all()
+    }
+}
+
+// Without any limit, the search only ends when stopped through a control or at the maximum depth.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct SearchLimits {
+    pub depth: Option<u16>,
+    pub time: Option<Duration>,
+    pub nodes: Option<u64>,
+}
+
+// Lines are the best root moves of the deepest completed iteration with their scores and
+// principal variations, best first. There are as many as requested with MultiPV, unless there
+// are fewer legal moves.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SearchOutcome {
+    pub best_move: Option<Move>,
+    pub score: Score,
+    pub depth: u16,
+    pub nodes: u64,
+    pub lines: Vec<(Move, Score, Vec<Move>)>,
+}
+
+// Owns everything a search needs, so that it is kept between the searches of a game. Searches
+// with more than one thread share the transposition table (Lazy SMP), each helper thread
+// evaluating with its own copy of the evaluator.
+pub struct Searcher<E: PositionEvaluator = HandcraftedEvaluator> {
+    move_generator: MoveGenerator,
+    table: SharedTranspositionTable,
+    evaluator: E,
+    history: HistoryTable,
+    features: SearchFeatures,
+    limits: SearchLimits,
+    threads: usize,
+    multi_pv: usize,
+    #[cfg(feature = "syzygy")]
+    tablebase: Option<Arc<Tablebase>>,
+}
+
+impl Searcher<HandcraftedEvaluator> {
+    pub fn new() -> Searcher<HandcraftedEvaluator> {
+        Searcher::with_evaluator(HandcraftedEvaluator)
+    }
+}
+
+impl Default for Searcher<HandcraftedEvaluator> {
+    fn default() -> Self {
+        Searcher::new()
+    }
+}
+
+impl<E: PositionEvaluator + Clone + Send> Searcher<E> {
+    pub fn with_evaluator(evaluator: E) -> Searcher<E> {
+        Searcher {
+            move_generator: MoveGenerator::new(),
+            table: SharedTranspositionTable::with_size_mb(DEFAULT_TABLE_SIZE_MB),
+            evaluator,
+            history: HistoryTable::new(),
+            features: SearchFeatures::all(),
+            limits: SearchLimits::default(),
+            threads: 1,
+            multi_pv: 1,
+            #[cfg(feature = "syzygy")]
+            tablebase: None,
+        }
+    }
+
+    pub fn with_table_size_mb(mut self, size_mb: usize) -> Searcher<E> {
+        self.table.resize_mb(size_mb);
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Searcher<E> {
+        self.set_threads(threads);
+        self
+    }
+
+    pub fn with_features(mut self, features: SearchFeatures) -> Searcher<E> {
+        self.features = features;
+        self
+    }
+
+    pub fn with_multi_pv(mut self, multi_pv: usize) -> Searcher<E> {
+        self.set_multi_pv(multi_pv);
+        self
+    }
+
+    pub fn with_limits(mut self, limits: SearchLimits) -> Searcher<E> {
+        self.limits = limits;
+        self
+    }
+
+    #[cfg(feature = "syzygy")]
+    pub fn with_tablebase(mut self, tablebase: Option<Arc<Tablebase>>) -> Searcher<E> {
+        self.tablebase = tablebase;
+        self
+    }
+
+    pub fn move_generator(&self) -> &MoveGenerator {
+        &self.move_generator
+    }
+
+    pub fn transposition_table(&self) -> &SharedTranspositionTable {
+        &self.table
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn multi_pv(&self) -> usize {
+        self.multi_pv
+    }
+
+    pub fn features(&self) -> SearchFeatures {
+        self.features
+    }
+
+    pub fn limits(&self) -> SearchLimits {
+        self.limits
+    }
+
+    #[cfg(feature = "syzygy")]
+    pub fn tablebase(&self) -> Option<&Tablebase> {
+        self.tablebase.as_deref()
+    }
+
+    pub fn resize_table(&mut self, size_mb: usize) {
+        self.table.resize_mb(size_mb);
+    }
+
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    pub fn set_multi_pv(&mut self, multi_pv: usize) {
+        self.multi_pv = multi_pv.max(1);
+    }
+
+    pub fn set_features(&mut self, features: SearchFeatures) {
+        self.features = features;
+    }
+
+    pub fn set_limits(&mut self, limits: SearchLimits) {
+        self.limits = limits;
+    }
+
+    #[cfg(feature = "syzygy")]
+    pub fn set_tablebase(&mut self, tablebase: Option<Arc<Tablebase>>) {
+        self.tablebase = tablebase;
+    }
+
+    // forgets everything learned in earlier searches
+    pub fn new_game(&mut self) {
+        self.table.clear();
+        self.history.clear();
+    }
+
+    pub fn search(&mut self, game_state: &mut GameState) -> SearchOutcome {
+        self.search_with_control(game_state, &SearchControl::new())
+    }
+
+    // Iterative deepening within the limits of both the searcher and the control, which can also
+    // stop the search from another thread and receives its progress.
+    pub fn search_with_control(
+        &mut self,
+        game_state: &mut GameState,
+        control: &SearchControl,
+    ) -> SearchOutcome {
+        self.run_search(game_state, None, control)
+    }
+
+    // searches only the given root moves, which must be legal in the position
+    pub fn search_moves(
+        &mut self,
+        game_state: &mut GameState,
+        moves: &[Move],
+    ) -> Result<SearchOutcome, String> {
+        self.search_moves_with_control(game_state, moves, &SearchControl::new())
+    }
+
+    pub fn search_moves_with_control(
+        &mut self,
+        game_state: &mut GameState,
+        moves: &[Move],
+        control: &SearchControl,
+    ) -> Result<SearchOutcome, String> {
+        if moves.is_empty() {
+            return Err("No moves to search".to_string());
+        }
+
+        let legal_moves = self.move_generator.generate_moves(game_state).moves;
+        if let Some(illegal) = moves.iter().find(|m| !legal_moves.contains(m)) {
+            return Err(format!("Illegal move: {}", illegal));
+        }
+
+        Ok(self.run_search(game_state, Some(moves.to_vec()), control))
+    }
+
+    fn run_search(
+        &mut self,
+        game_state: &mut GameState,
+        root_moves: Option<Vec<Move>>,
+        control: &SearchControl,
+    ) -> SearchOutcome {
+        self.table.new_search();
+        self.history.age();
+        self.evaluator.refresh(game_state);
+        #[cfg(feature = "syzygy")]
+        let root_moves = self.tablebase_root_moves(game_state, root_moves);
+
+        let mut table = &self.table;
+        let mut search = Search::new(
+            &self.move_generator,
+            &mut table,
+            &mut self.evaluator,
+            &mut self.history,
+            self.features,
+            ControlPoller::with_limits(control, &self.limits),
+        )
+        .with_multi_pv(self.multi_pv)
+        .with_root_moves(root_moves);
+        #[cfg(feature = "syzygy")]
+        {
+            search = search.with_tablebase(self.tablebase.as_deref());
+        }
+        let (best_move, best_eval, depth) =
+            search.parallel_iterative_deepening(game_state, self.threads);
+        let lines = search
+            .root_lines()
+            .iter()
+            .map(|(m, eval, pv)| (*m, Score::from_evaluation(*eval), pv.clone()))
+            .collect();
+
+        SearchOutcome {
+            best_move,
+            score: Score::from_evaluation(best_eval),
+            depth,
+            nodes: control.nodes(),
+            lines,
+        }
+    }
+    // Only the root moves keeping the best result by the tablebases are searched, the search then
+    // finds the way to it. Given root moves are kept when none of them does.
+    #[cfg(feature = "syzygy")]
+    fn tablebase_root_moves(
+        &self,
+        game_state: &GameState,
+        root_moves: Option<Vec<Move>>,
+    ) -> Option<Vec<Move>> {
+        let best_moves = match self
+            .tablebase
+            .as_ref()
+            .and_then(|tablebase| tablebase.best_root_moves(game_state))
+        {
+            Some(best_moves) => best_moves,
+            None => return root_moves,
+        };
+
+        match root_moves {
+            Some(root_moves) => {
+                let kept: Vec<Move> = root_moves
+                    .iter()
+                    .filter(|m| best_moves.contains(m))
+                    .copied()
+                    .collect();
+                Some(if kept.is_empty() { root_moves } else { kept })
+            }
+            None => Some(best_moves),
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::mem;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use crate::model::game_state::PackedMove;
+use crate::search::transposition_entry::{
+    self, Bucket, EntryData, Slot, ENTRIES_PER_BUCKET, GENERATION_STEP,
+};
+use crate::search::transposition_table::{MatchType, TranspositionStore, ZobristHash};
+
+// Transposition table which can be probed and updated from several threads at once without
+// locking. Each entry is stored as two atomic words, the packed data and the zobrist hash xored
+// with the data. A torn entry, written concurrently by two threads, fails the key verification
+// and is treated as a miss.
+pub struct SharedTranspositionTable {
+    buckets: Vec<Bucket<AtomicEntry>>,
+    generation: AtomicU8,
+}
+
+impl SharedTranspositionTable {
+    pub fn with_capacity(capacity: usize) -> SharedTranspositionTable {
+        let bucket_count = usize::max(1, capacity / ENTRIES_PER_BUCKET);
+
+        SharedTranspositionTable {
+            buckets: (0..bucket_count).map(|_| Bucket::default()).collect(),
+            generation: AtomicU8::new(0),
+        }
+    }
+
+    pub fn with_size_mb(size_mb: usize) -> SharedTranspositionTable {
+        SharedTranspositionTable::with_capacity(
+            size_mb * 1024 * 1024 / mem::size_of::<Bucket<AtomicEntry>>() * ENTRIES_PER_BUCKET,
+        )
+    }
+
+    pub fn resize_mb(&mut self, size_mb: usize) {
+        *self = SharedTranspositionTable::with_size_mb(size_mb);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buckets.len() * ENTRIES_PER_BUCKET
+    }
+
+    pub fn clear(&self) {
+        for entry in self.buckets.iter().flat_map(|bucket| bucket.entries.iter()) {
+            entry.store(0, EntryData::default());
+        }
+        self.generation.store(0, Ordering::Relaxed);
+    }
+
+    pub fn new_search(&self) {
+        self.generation
+            .fetch_add(GENERATION_STEP, Ordering::Relaxed);
+    }
+
+    // permille of sampled entries used by the current search
+    pub fn hashfull(&self) -> u16 {
+        transposition_entry::hashfull(&self.buckets, self.generation.load(Ordering::Relaxed))
+    }
+
+    pub fn check(&self, zobrist_hash: ZobristHash, depth: u16) -> Option<(MatchType, i32)> {
+        transposition_entry::probe(self.bucket(zobrist_hash), zobrist_hash)
+            .filter(|data| u16::from(data.depth()) >= depth)
+            .map(|data| (data.match_type(), data.evaluation()))
+    }
+
+    // the best move found in the position at any depth, to be searched first
+    pub fn best_move(&self, zobrist_hash: ZobristHash) -> Option<PackedMove> {
+        transposition_entry::probe(self.bucket(zobrist_hash), zobrist_hash)
+            .and_then(|data| data.best_move())
+    }
+
+    pub fn update(
+        &self,
+        zobrist_hash: ZobristHash,
+        depth: u16,
+        evaluation: i32,
+        match_type: MatchType,
+        best_move: Option<PackedMove>,
+    ) {
+        let generation = self.generation.load(Ordering::Relaxed);
+        let data = EntryData::new(depth, evaluation, match_type, generation, best_move);
+        let bucket = self.bucket(zobrist_hash);
+
+        if let Some((slot, data)) =
+            transposition_entry::replacement(bucket, zobrist_hash, data, generation)
+        {
+            bucket.entries[slot].store(zobrist_hash, data);
+        }
+    }
+
+    fn bucket(&self, zobrist_hash: ZobristHash) -> &Bucket<AtomicEntry> {
+        &self.buckets[transposition_entry::table_index(zobrist_hash, self.buckets.len())]
+    }
+}
+
+impl TranspositionStore for &SharedTranspositionTable {
+    fn check(&self, zobrist_hash: ZobristHash, depth: u16) -> Option<(MatchType, i32)> {
+        SharedTranspositionTable::check(self, zobrist_hash, depth)
+    }
+
+    fn best_move(&self, zobrist_hash: ZobristHash) -> Option<PackedMove> {
+        SharedTranspositionTable::best_move(self, zobrist_hash)
+    }
+
+    fn update(
+        &mut self,
+        zobrist_hash: ZobristHash,
+        depth: u16,
+        evaluation: i32,
+        match_type: MatchType,
+        best_move: Option<PackedMove>,
+    ) {
+        SharedTranspositionTable::update(
+            self,
+            zobrist_hash,
+            depth,
+            evaluation,
+            match_type,
+            best_move,
+        )
+    }
+
+    fn new_search(&mut self) {
+        SharedTranspositionTable::new_search(self)
+    }
+}
+
+#[derive(Default)]
+struct AtomicEntry {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+impl AtomicEntry {
+    fn store(&self, zobrist_hash: ZobristHash, data: EntryData) {
+        self.key.store(zobrist_hash ^ data.0, Ordering::Relaxed);
+        self.data.store(data.0, Ordering::Relaxed);
+    }
+}
+
+impl Slot for AtomicEntry {
+    fn load(&self, zobrist_hash: ZobristHash) -> Option<EntryData> {
+        let key = self.key.load(Ordering::Relaxed);
+        let data = self.data.load(Ordering::Relaxed);
+
+        if key ^ data == zobrist_hash {
+            Some(EntryData(data))
+        } else {
+            None
+        }
+    }
+
+    fn data(&self) -> EntryData {
+        EntryData(self.data.load(Ordering::Relaxed))
+    }
+}
@@ -12,7 +12,7 @@ where
         .map(|m| m.unwrap());
 
     for m in parsed_moves {
-        let matching_move = move_generator.get_move(game_state, m.0, m.1).unwrap();
+        let matching_move = move_generator.find_move(game_state, &m).unwrap();
         game_state.apply_move_mut(matching_move);
     }
 }
@@ -0,0 +1,26 @@
+#[cfg(test)]
+use crate::model::game_state::{Color, GameState};
+#[cfg(test)]
+use crate::model::move_generator::MoveGenerator;
+#[cfg(test)]
+use crate::search::history_table::HistoryTable;
+
+#[test]
+fn cutoffs_are_scored_by_depth_and_side() {
+    let move_generator = MoveGenerator::new();
+    let moves = move_generator.generate_moves(&GameState::new()).moves;
+    let mut history = HistoryTable::new();
+
+    history.update(Color::WHITE, &moves[0], 3);
+    history.update(Color::WHITE, &moves[0], 2);
+
+    assert_eq!(13, history.score(Color::WHITE, &moves[0]));
+    assert_eq!(0, history.score(Color::BLACK, &moves[0]));
+    assert_eq!(0, history.score(Color::WHITE, &moves[1]));
+
+    history.age();
+    assert_eq!(6, history.score(Color::WHITE, &moves[0]));
+
+    history.clear();
+    assert_eq!(0, history.score(Color::WHITE, &moves[0]));
+}
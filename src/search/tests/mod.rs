@@ -1,3 +1,9 @@
 mod checkmate_tests;
+mod history_table_tests;
 mod negamax_tests;
+mod score_tests;
+mod search_control_tests;
+mod searcher_tests;
+mod shared_transposition_table_tests;
+mod transposition_table_tests;
 pub mod utils;
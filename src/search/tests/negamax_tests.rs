@@ -7,6 +7,8 @@ use crate::search::minimax_search::{
     iterative_alpha_beta, negamax_alpha_beta, negamax_alpha_beta_with_trasposition_table,
 };
 #[cfg(test)]
+use crate::search::score::Score;
+#[cfg(test)]
 use crate::search::transposition_table::TranspositionTable;
 #[cfg(test)]
 use crate::uci::uci_utils;
@@ -18,7 +20,7 @@ use std::time::Duration;
 fn negamax_does_not_modify_game_state() {
     let move_generator = MoveGenerator::new();
     let mut state = GameState::new();
-    let initial_state = state.clone();
+    let initial_state = state;
     let depth = 4;
 
     let (_, _, _) = negamax_alpha_beta(&mut state, &move_generator, depth);
@@ -43,7 +45,7 @@ fn negamax_should_be_deterministic() {
 fn negamax_with_transposition_table_does_not_modify_game_state() {
     let move_generator = MoveGenerator::new();
     let mut state = GameState::new();
-    let initial_state = state.clone();
+    let initial_state = state;
     let depth = 4;
 
     let transposition_table = &mut TranspositionTable::with_capacity(10_000);
@@ -121,7 +123,7 @@ fn negamax_finds_fools_mate() {
 
     assert_eq!(
         "d8h4",
-        uci_utils::move_to_uci(&first_move.unwrap()).to_string()
+        uci_utils::move_to_uci(&first_move.unwrap(), false).to_string()
     );
     assert!(first_eval > 1000000);
 }
@@ -145,9 +147,9 @@ fn iterative_deepening_finds_fools_mate() {
 
     assert_eq!(
         "d8h4",
-        uci_utils::move_to_uci(&first_move.unwrap()).to_string()
+        uci_utils::move_to_uci(&first_move.unwrap(), false).to_string()
     );
-    assert!(first_eval > 1000000);
+    assert_eq!(Score::MateIn(1), first_eval);
 }
 
 #[cfg(test)]
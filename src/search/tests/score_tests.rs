@@ -0,0 +1,106 @@
+#[cfg(test)]
+use crate::model::evaluator::HandcraftedEvaluator;
+#[cfg(test)]
+use crate::model::game_state::{CastlingRights, Color, GameState, Piece, Position};
+#[cfg(test)]
+use crate::model::move_generator::MoveGenerator;
+#[cfg(test)]
+use crate::model::zobrist_hash;
+#[cfg(test)]
+use crate::search::minimax_search::iterative_alpha_beta_with_control;
+#[cfg(test)]
+use crate::search::score;
+#[cfg(test)]
+use crate::search::score::{Score, MATE};
+#[cfg(test)]
+use crate::search::search_control::SearchControl;
+#[cfg(test)]
+use crate::search::transposition_table::TranspositionTable;
+
+#[test]
+fn mate_scores_are_converted_to_moves() {
+    assert_eq!(Score::Evaluation(1500), Score::from_evaluation(1500));
+    assert_eq!(Score::MateIn(1), Score::from_evaluation(MATE - 1));
+    assert_eq!(Score::MateIn(2), Score::from_evaluation(MATE - 3));
+    assert_eq!(
+        Score::MateIn(-1),
+        Score::from_evaluation(score::mated_in(2))
+    );
+    assert_eq!(Score::MateIn(0), Score::from_evaluation(score::mated_in(0)));
+}
+
+#[test]
+fn table_scores_are_relative_to_the_stored_position() {
+    // mated two plies below a node at ply 3 is mated at ply 5 from the root
+    let stored = score::to_table(score::mated_in(5), 3);
+
+    assert_eq!(score::mated_in(2), stored);
+    assert_eq!(score::mated_in(6), score::from_table(stored, 4));
+    assert_eq!(1234, score::from_table(score::to_table(1234, 7), 7));
+}
+
+#[test]
+fn tablebase_scores_are_relative_to_the_stored_position() {
+    let stored = score::to_table(score::tablebase_win_in(5), 3);
+
+    assert_eq!(score::tablebase_win_in(2), stored);
+    assert_eq!(score::tablebase_win_in(6), score::from_table(stored, 4));
+    assert_eq!(
+        -score::tablebase_win_in(6),
+        score::from_table(score::to_table(-score::tablebase_win_in(5), 3), 4)
+    );
+}
+
+#[test]
+fn checkmated_root_is_reported_without_searching() {
+    // black: Kh8, white: Qg7, Kg6
+    let mut game_state = GameState::new_empty();
+    game_state.castling_rights = CastlingRights::none();
+    game_state.set_piece(Piece::KING, Color::BLACK, Position::new(8, 8));
+    game_state.set_piece(Piece::QUEEN, Color::WHITE, Position::new(7, 7));
+    game_state.set_piece(Piece::KING, Color::WHITE, Position::new(7, 6));
+    game_state.to_move = Color::BLACK;
+    game_state.zobrist_hash = zobrist_hash::hash(&game_state);
+
+    let move_generator = MoveGenerator::new();
+    let mut table = TranspositionTable::with_capacity(1000);
+
+    let (best_move, score, depth) = iterative_alpha_beta_with_control(
+        &mut game_state,
+        &move_generator,
+        &mut table,
+        &mut HandcraftedEvaluator,
+        None,
+        &SearchControl::new(),
+    );
+
+    assert_eq!(None, best_move);
+    assert_eq!(Score::MateIn(0), score);
+    assert_eq!(0, depth);
+}
+
+#[test]
+fn rook_mate_in_two_is_reported() {
+    // white: Kf6, Ra1, black: Kh8. 1. Kg6 Kg8 2. Ra8# or 1. Kf7 Kh7 2. Rh1#
+    let mut game_state = GameState::new_empty();
+    game_state.castling_rights = CastlingRights::none();
+    game_state.set_piece(Piece::KING, Color::WHITE, Position::new(6, 6));
+    game_state.set_piece(Piece::ROOK, Color::WHITE, Position::new(1, 1));
+    game_state.set_piece(Piece::KING, Color::BLACK, Position::new(8, 8));
+    game_state.zobrist_hash = zobrist_hash::hash(&game_state);
+
+    let move_generator = MoveGenerator::new();
+    let mut table = TranspositionTable::with_capacity(100_000);
+
+    let (best_move, score, _) = iterative_alpha_beta_with_control(
+        &mut game_state,
+        &move_generator,
+        &mut table,
+        &mut HandcraftedEvaluator,
+        None,
+        &SearchControl::new().with_node_limit(50_000),
+    );
+
+    assert_eq!(Score::MateIn(2), score);
+    assert_eq!(Piece::KING, best_move.unwrap().moving_piece);
+}
@@ -0,0 +1,241 @@
+#[cfg(test)]
+use crate::model::evaluator::HandcraftedEvaluator;
+#[cfg(test)]
+use crate::model::game_state::{GameState, Position};
+#[cfg(test)]
+use crate::model::move_generator::MoveGenerator;
+#[cfg(test)]
+use crate::search::minimax_search::{
+    iterative_alpha_beta_with_control, parallel_iterative_alpha_beta_with_control,
+};
+#[cfg(test)]
+use crate::search::search_control::SearchControl;
+#[cfg(test)]
+use crate::search::shared_transposition_table::SharedTranspositionTable;
+#[cfg(test)]
+use crate::search::test_utils;
+#[cfg(test)]
+use crate::search::transposition_table::{MatchType, TranspositionTable};
+
+#[cfg(test)]
+use crossbeam::thread::scope;
+#[cfg(test)]
+use std::sync::{Arc, Mutex};
+#[cfg(test)]
+use std::thread;
+#[cfg(test)]
+use std::time::{Duration, Instant};
+
+#[cfg(test)]
+fn fools_mate_position(move_generator: &MoveGenerator) -> GameState {
+    let mut game_state = GameState::new();
+    let moves = ["f2f3", "e7e6", "g2g4"].iter().map(|s| s.to_string());
+    test_utils::apply_position(moves, &mut game_state, move_generator);
+    game_state
+}
+
+#[test]
+fn search_without_time_limit_is_stopped_from_another_thread() {
+    let move_generator = MoveGenerator::new();
+    let mut game_state = fools_mate_position(&move_generator);
+    let mut table = TranspositionTable::with_capacity(100_000);
+    let control = SearchControl::new();
+    let start = Instant::now();
+
+    let (best_move, _, _) = scope(|s| {
+        s.spawn(|_| {
+            thread::sleep(Duration::from_millis(100));
+            control.stop();
+        });
+
+        iterative_alpha_beta_with_control(
+            &mut game_state,
+            &move_generator,
+            &mut table,
+            &mut HandcraftedEvaluator,
+            None,
+            &control,
+        )
+    })
+    .unwrap();
+
+    assert_eq!(Position::new(8, 4), best_move.unwrap().to);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn node_limit_ends_search() {
+    let move_generator = MoveGenerator::new();
+    let mut game_state = GameState::new();
+    let table = SharedTranspositionTable::with_capacity(100_000);
+    let control = SearchControl::new().with_node_limit(20_000);
+
+    let (best_move, _, _, nodes) = parallel_iterative_alpha_beta_with_control(
+        &mut game_state,
+        &move_generator,
+        &table,
+        2,
+        None,
+        &control,
+    );
+
+    assert!(best_move.is_some());
+    assert!(control.is_stopped());
+    assert!(nodes >= 20_000);
+    assert!(nodes < 30_000);
+}
+
+#[test]
+fn progress_is_reported_for_every_iteration() {
+    let move_generator = MoveGenerator::new();
+    let mut game_state = GameState::new();
+    let mut table = TranspositionTable::with_capacity(100_000);
+    let depths = Arc::new(Mutex::new(Vec::new()));
+    let reported_depths = depths.clone();
+    let control = SearchControl::new()
+        .with_node_limit(50_000)
+        .with_progress(move |progress| {
+            if progress.bound == MatchType::EXACT {
+                reported_depths.lock().unwrap().push(progress.depth);
+            }
+        });
+
+    let (_, _, depth) = iterative_alpha_beta_with_control(
+        &mut game_state,
+        &move_generator,
+        &mut table,
+        &mut HandcraftedEvaluator,
+        None,
+        &control,
+    );
+
+    let expected: Vec<u16> = (1..=depth).collect();
+    assert_eq!(expected, *depths.lock().unwrap());
+}
+
+#[test]
+fn depth_limit_ends_iterative_deepening() {
+    let move_generator = MoveGenerator::new();
+    let mut game_state = GameState::new();
+    let initial_state = game_state;
+    let mut table = TranspositionTable::with_capacity(100_000);
+    let control = SearchControl::new().with_depth_limit(4);
+
+    let (best_move, _, depth) = iterative_alpha_beta_with_control(
+        &mut game_state,
+        &move_generator,
+        &mut table,
+        &mut HandcraftedEvaluator,
+        None,
+        &control,
+    );
+
+    assert!(best_move.is_some());
+    assert_eq!(4, depth);
+    assert!(!control.is_stopped());
+    assert_eq!(initial_state, game_state);
+}
+
+#[test]
+fn hanging_queen_is_captured_with_pruning() {
+    let move_generator = MoveGenerator::new();
+    let mut game_state = GameState::new();
+    let moves = ["e2e4", "e7e5", "d1h5", "g8f6", "f1c4"];
+    test_utils::apply_position(
+        moves.iter().map(|s| s.to_string()),
+        &mut game_state,
+        &move_generator,
+    );
+    let mut table = TranspositionTable::with_capacity(100_000);
+    let control = SearchControl::new().with_depth_limit(4);
+
+    let (best_move, _, _) = iterative_alpha_beta_with_control(
+        &mut game_state,
+        &move_generator,
+        &mut table,
+        &mut HandcraftedEvaluator,
+        None,
+        &control,
+    );
+
+    // the white queen on h5 is left en prise to the knight on f6
+    assert_eq!(Position::new(8, 5), best_move.unwrap().to);
+}
+
+#[test]
+fn time_limit_applies_only_after_ponderhit() {
+    let move_generator = MoveGenerator::new();
+    let mut game_state = fools_mate_position(&move_generator);
+    let mut table = TranspositionTable::with_capacity(100_000);
+    let control = SearchControl::new().with_ponder();
+    let start = Instant::now();
+
+    scope(|s| {
+        s.spawn(|_| {
+            thread::sleep(Duration::from_millis(100));
+            assert!(!control.is_stopped());
+            control.ponderhit();
+        });
+
+        iterative_alpha_beta_with_control(
+            &mut game_state,
+            &move_generator,
+            &mut table,
+            &mut HandcraftedEvaluator,
+            Some(Duration::from_millis(10)),
+            &control,
+        )
+    })
+    .unwrap();
+
+    assert!(!control.is_pondering());
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
+
+#[test]
+fn failed_aspiration_window_is_searched_again() {
+    let move_generator = MoveGenerator::new();
+    let mut game_state = GameState::new();
+    let moves = ["e2e4", "d7d5", "e4d5", "d8d5", "b1c3"];
+    test_utils::apply_position(
+        moves.iter().map(|s| s.to_string()),
+        &mut game_state,
+        &move_generator,
+    );
+    let mut table = TranspositionTable::with_capacity(100_000);
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let reported = reports.clone();
+    let control = SearchControl::new()
+        .with_node_limit(5_000)
+        .with_progress(move |progress| {
+            reported
+                .lock()
+                .unwrap()
+                .push((progress.depth, progress.bound, progress.score))
+        });
+
+    iterative_alpha_beta_with_control(
+        &mut game_state,
+        &move_generator,
+        &mut table,
+        &mut HandcraftedEvaluator,
+        None,
+        &control,
+    );
+
+    // the queen is attacked, so the score swings between iterations and falls outside the
+    // aspiration window, after which the iteration is completed with a wider window
+    let reports = reports.lock().unwrap();
+    let last_depth = reports.last().unwrap().0;
+    let failed_depths: Vec<u16> = reports
+        .iter()
+        .filter(|report| report.1 != MatchType::EXACT && report.0 < last_depth)
+        .map(|report| report.0)
+        .collect();
+
+    assert!(!failed_depths.is_empty());
+    for depth in failed_depths {
+        let last_report = reports.iter().rfind(|report| report.0 == depth);
+        assert_eq!(MatchType::EXACT, last_report.unwrap().1);
+    }
+}
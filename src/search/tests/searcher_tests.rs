@@ -0,0 +1,238 @@
+#[cfg(test)]
+use crate::model::game_state::{GameState, Move, Position};
+#[cfg(test)]
+use crate::search::score::Score;
+#[cfg(test)]
+use crate::search::search_control::SearchControl;
+#[cfg(test)]
+use crate::search::searcher::{SearchFeatures, SearchLimits, Searcher};
+#[cfg(test)]
+use crate::search::test_utils;
+#[cfg(test)]
+use crate::uci::uci_utils::parse_move;
+
+#[cfg(test)]
+use std::time::Duration;
+
+#[cfg(test)]
+fn position(moves: &[&str], searcher: &Searcher) -> GameState {
+    let mut game_state = GameState::new();
+    test_utils::apply_position(
+        moves.iter().map(|s| s.to_string()),
+        &mut game_state,
+        searcher.move_generator(),
+    );
+    game_state
+}
+
+#[cfg(test)]
+fn depth_limit(depth: u16) -> SearchLimits {
+    SearchLimits {
+        depth: Some(depth),
+        ..SearchLimits::default()
+    }
+}
+
+#[test]
+fn searcher_finds_fools_mate() {
+    let mut searcher = Searcher::new().with_limits(depth_limit(3));
+    let mut game_state = position(&["f2f3", "e7e6", "g2g4"], &searcher);
+    let initial_state = game_state;
+
+    let outcome = searcher.search(&mut game_state);
+
+    assert_eq!(Position::new(8, 4), outcome.best_move.unwrap().to);
+    assert_eq!(Score::MateIn(1), outcome.score);
+    assert_eq!(3, outcome.depth);
+    assert!(outcome.nodes > 0);
+    assert_eq!(initial_state, game_state);
+}
+
+#[test]
+fn search_features_can_be_switched_off() {
+    let mut full = Searcher::new().with_limits(depth_limit(4));
+    let mut plain = Searcher::new()
+        .with_limits(depth_limit(4))
+        .with_features(SearchFeatures::none());
+    let mut game_state = GameState::new();
+
+    let full_outcome = full.search(&mut game_state);
+    let plain_outcome = plain.search(&mut game_state);
+
+    assert!(full_outcome.best_move.is_some());
+    assert!(plain_outcome.best_move.is_some());
+    assert!(plain_outcome.nodes > full_outcome.nodes);
+}
+
+#[test]
+fn shallow_pruning_can_be_switched_off_alone() {
+    let mut full = Searcher::new().with_limits(depth_limit(5));
+    let mut without_shallow =
+        Searcher::new()
+            .with_limits(depth_limit(5))
+            .with_features(SearchFeatures {
+                shallow_pruning: false,
+                ..SearchFeatures::all()
+            });
+    let mut game_state = position(&["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "f8c5"], &full);
+
+    let full_outcome = full.search(&mut game_state);
+    let without_shallow_outcome = without_shallow.search(&mut game_state);
+
+    assert!(without_shallow_outcome.best_move.is_some());
+    assert!(without_shallow_outcome.nodes > full_outcome.nodes);
+}
+
+#[test]
+fn plain_search_finds_fools_mate() {
+    let mut searcher = Searcher::new()
+        .with_limits(depth_limit(2))
+        .with_features(SearchFeatures::none());
+    let mut game_state = position(&["f2f3", "e7e6", "g2g4"], &searcher);
+
+    let outcome = searcher.search(&mut game_state);
+
+    assert_eq!(Position::new(8, 4), outcome.best_move.unwrap().to);
+    assert_eq!(Score::MateIn(1), outcome.score);
+}
+
+#[test]
+fn searcher_limits_and_control_both_apply() {
+    let mut searcher = Searcher::new().with_threads(2).with_limits(SearchLimits {
+        time: Some(Duration::from_secs(60)),
+        ..SearchLimits::default()
+    });
+    let mut game_state = GameState::new();
+    let control = SearchControl::new().with_node_limit(20_000);
+
+    let outcome = searcher.search_with_control(&mut game_state, &control);
+
+    assert!(outcome.best_move.is_some());
+    assert!(outcome.nodes >= 20_000);
+    assert!(outcome.nodes < 30_000);
+}
+
+#[test]
+fn searcher_is_reused_between_searches() {
+    // a small table, so that the entries of a shallow search show up in the hashfull sample
+    let mut searcher = Searcher::new()
+        .with_table_size_mb(1)
+        .with_limits(depth_limit(3));
+    let mut game_state = GameState::new();
+
+    let first = searcher.search(&mut game_state);
+    assert!(searcher.transposition_table().hashfull() > 0);
+    let second = searcher.search(&mut game_state);
+
+    assert_eq!(first.best_move, second.best_move);
+
+    searcher.new_game();
+    assert_eq!(0, searcher.transposition_table().hashfull());
+}
+
+#[test]
+fn multi_pv_returns_ranked_lines() {
+    let mut searcher = Searcher::new().with_limits(depth_limit(3)).with_multi_pv(3);
+    let mut game_state = position(&["f2f3", "e7e6", "g2g4"], &searcher);
+
+    let outcome = searcher.search(&mut game_state);
+
+    assert_eq!(3, outcome.lines.len());
+    let (best_move, best_score, best_pv) = &outcome.lines[0];
+    assert_eq!(outcome.best_move, Some(*best_move));
+    assert_eq!(Score::MateIn(1), *best_score);
+    assert_eq!(vec![*best_move], *best_pv);
+
+    for (m, _, pv) in outcome.lines.iter() {
+        assert_eq!(*m, pv[0]);
+        assert_eq!(1, outcome.lines.iter().filter(|line| line.0 == *m).count());
+    }
+    for pair in outcome.lines.windows(2) {
+        assert!(!matches!(pair[1].1, Score::MateIn(moves) if moves > 0));
+    }
+}
+
+#[test]
+fn multi_pv_with_fewer_legal_moves() {
+    let mut searcher = Searcher::new().with_limits(depth_limit(3)).with_multi_pv(3);
+    let mut game_state = position(&["e2e4", "f7f5", "d1h5"], &searcher);
+
+    let outcome = searcher.search(&mut game_state);
+
+    assert_eq!(1, outcome.lines.len());
+    assert_eq!(Position::new(7, 6), outcome.best_move.unwrap().to);
+}
+
+#[test]
+fn principal_variation_is_legal() {
+    let mut searcher = Searcher::new().with_limits(depth_limit(4));
+    let mut game_state = GameState::new();
+
+    let outcome = searcher.search(&mut game_state);
+
+    let pv = &outcome.lines[0].2;
+    assert!(pv.len() > 1);
+    for m in pv.iter() {
+        let legal_moves = searcher.move_generator().generate_moves(&game_state).moves;
+        assert!(legal_moves.contains(m));
+        game_state.apply_move_mut(*m);
+    }
+}
+
+#[cfg(test)]
+fn find_moves(uci_moves: &[&str], game_state: &GameState, searcher: &Searcher) -> Vec<Move> {
+    uci_moves
+        .iter()
+        .map(|uci_move| {
+            let parsed = parse_move(uci_move).unwrap();
+            searcher
+                .move_generator()
+                .find_move(game_state, &parsed)
+                .unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn search_is_restricted_to_given_moves() {
+    let mut searcher = Searcher::new().with_limits(depth_limit(3));
+    let mut game_state = position(&["f2f3", "e7e6", "g2g4"], &searcher);
+    let candidates = find_moves(&["a7a6", "b7b6"], &game_state, &searcher);
+
+    let outcome = searcher.search_moves(&mut game_state, &candidates).unwrap();
+
+    assert!(candidates.contains(&outcome.best_move.unwrap()));
+    assert!(matches!(outcome.score, Score::Evaluation(_)));
+
+    // the restricted search does not leave its root evaluation for later searches
+    let outcome = searcher.search(&mut game_state);
+    assert_eq!(Score::MateIn(1), outcome.score);
+}
+
+#[test]
+fn candidate_move_is_evaluated() {
+    let mut searcher = Searcher::new().with_limits(depth_limit(3)).with_multi_pv(3);
+    let mut game_state = position(&["f2f3", "e7e6", "g2g4"], &searcher);
+    let candidates = find_moves(&["d8h4", "a7a6"], &game_state, &searcher);
+
+    let outcome = searcher.search_moves(&mut game_state, &candidates).unwrap();
+
+    assert_eq!(2, outcome.lines.len());
+    assert_eq!(candidates[0], outcome.lines[0].0);
+    assert_eq!(Score::MateIn(1), outcome.lines[0].1);
+    assert_eq!(candidates[1], outcome.lines[1].0);
+}
+
+#[test]
+fn illegal_or_missing_moves_are_rejected() {
+    let mut searcher = Searcher::new().with_limits(depth_limit(2));
+    let mut game_state = GameState::new();
+    let white_move = find_moves(&["e2e4"], &game_state, &searcher);
+    game_state.apply_move_mut(white_move[0]);
+
+    assert_eq!(
+        Err("Illegal move: e2e4".to_string()),
+        searcher.search_moves(&mut game_state, &white_move)
+    );
+    assert!(searcher.search_moves(&mut game_state, &[]).is_err());
+}
@@ -0,0 +1,207 @@
+#[cfg(test)]
+use crate::model::evaluator::HandcraftedEvaluator;
+#[cfg(test)]
+use crate::model::game_state::{GameState, PackedMove, Piece, Position};
+#[cfg(test)]
+use crate::model::move_generator::MoveGenerator;
+#[cfg(test)]
+use crate::search::minimax_search::{
+    iterative_alpha_beta_with_evaluator, negamax_alpha_beta,
+    negamax_alpha_beta_with_trasposition_table_and_evaluator, parallel_iterative_alpha_beta,
+};
+#[cfg(test)]
+use crate::search::shared_transposition_table::SharedTranspositionTable;
+#[cfg(test)]
+use crate::search::test_utils;
+#[cfg(test)]
+use crate::search::transposition_table::MatchType;
+
+#[cfg(test)]
+use crossbeam::thread::scope;
+#[cfg(test)]
+use std::time::Duration;
+
+#[test]
+fn stored_entry_is_found() {
+    let table = SharedTranspositionTable::with_capacity(1_000);
+
+    table.update(12345, 4, -100, MatchType::UPPERBOUND, None);
+
+    assert_eq!(Some((MatchType::UPPERBOUND, -100)), table.check(12345, 3));
+    assert_eq!(None, table.check(12345, 5));
+    assert_eq!(None, table.check(54321, 0));
+}
+
+#[test]
+fn best_move_is_stored_with_the_entry() {
+    let table = SharedTranspositionTable::with_capacity(1_000);
+    let best_move = PackedMove::new(Position::new(5, 7), Position::new(5, 8), Some(Piece::QUEEN));
+
+    table.update(12345, 4, -100, MatchType::EXACT, Some(best_move));
+    assert_eq!(Some((MatchType::EXACT, -100)), table.check(12345, 4));
+    assert_eq!(Some(best_move), table.best_move(12345));
+
+    table.update(12345, 5, 200, MatchType::LOWERBOUND, None);
+    assert_eq!(Some((MatchType::LOWERBOUND, 200)), table.check(12345, 5));
+    assert_eq!(Some(best_move), table.best_move(12345));
+    assert_eq!(None, table.best_move(54321));
+}
+
+#[test]
+fn entries_with_other_keys_in_same_bucket_are_not_matched() {
+    let table = SharedTranspositionTable::with_capacity(1);
+
+    table.update(1, 4, 10, MatchType::EXACT, None);
+    table.update(2, 4, 20, MatchType::EXACT, None);
+
+    assert_eq!(Some((MatchType::EXACT, 10)), table.check(1, 0));
+    assert_eq!(Some((MatchType::EXACT, 20)), table.check(2, 0));
+    assert_eq!(None, table.check(3, 0));
+}
+
+#[test]
+fn clear_and_hashfull() {
+    let table = SharedTranspositionTable::with_capacity(8);
+
+    table.update(0, 1, 0, MatchType::EXACT, None);
+    table.update(u64::MAX, 1, 0, MatchType::EXACT, None);
+    assert_eq!(250, table.hashfull());
+
+    table.clear();
+    assert_eq!(0, table.hashfull());
+    assert_eq!(None, table.check(0, 0));
+}
+
+#[test]
+fn concurrent_updates_from_many_threads() {
+    let table = SharedTranspositionTable::with_size_mb(1);
+    let thread_count: u64 = 4;
+    let entries_per_thread: u64 = 1_000;
+
+    scope(|s| {
+        for thread in 0..thread_count {
+            let table = &table;
+            s.spawn(move |_| {
+                for i in 0..entries_per_thread {
+                    let hash = (thread * entries_per_thread + i + 1)
+                        .wrapping_mul(0x9e37_79b9_7f4a_7c15_u64);
+                    table.update(hash, 1, i as i32, MatchType::EXACT, None);
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    let mut found = 0;
+    for thread in 0..thread_count {
+        for i in 0..entries_per_thread {
+            let hash =
+                (thread * entries_per_thread + i + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15_u64);
+            if let Some(entry) = table.check(hash, 1) {
+                assert_eq!((MatchType::EXACT, i as i32), entry);
+                found += 1;
+            }
+        }
+    }
+    assert!(found > thread_count * entries_per_thread * 9 / 10);
+}
+
+#[test]
+fn shared_table_does_not_change_eval() {
+    let move_generator = MoveGenerator::new();
+    let mut state = GameState::new();
+    let table = SharedTranspositionTable::with_capacity(10_000);
+
+    for depth in 0..=3 {
+        let (first_move, first_eval, _) = negamax_alpha_beta(&mut state, &move_generator, depth);
+        let (second_move, second_eval, _) =
+            negamax_alpha_beta_with_trasposition_table_and_evaluator(
+                &mut state,
+                &move_generator,
+                &mut &table,
+                &mut HandcraftedEvaluator,
+                depth,
+            );
+
+        assert_eq!(first_eval, second_eval);
+        assert_eq!(first_move, second_move);
+    }
+}
+
+#[test]
+fn parallel_searches_share_table() {
+    let move_generator = MoveGenerator::new();
+    let table = SharedTranspositionTable::with_capacity(100_000);
+    let mut game_state = GameState::new();
+    let moves = ["f2f3", "e7e6", "g2g4"].iter().map(|s| s.to_string());
+    test_utils::apply_position(moves, &mut game_state, &move_generator);
+
+    scope(|s| {
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let mut game_state = game_state;
+                let move_generator = &move_generator;
+                let mut table = &table;
+                s.spawn(move |_| {
+                    iterative_alpha_beta_with_evaluator(
+                        &mut game_state,
+                        move_generator,
+                        &mut table,
+                        &mut HandcraftedEvaluator,
+                        Duration::from_millis(50),
+                    )
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            let (best_move, _, _) = handle.join().unwrap();
+            assert_eq!(Position::new(8, 4), best_move.unwrap().to);
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn lazy_smp_finds_fools_mate() {
+    let move_generator = MoveGenerator::new();
+    let table = SharedTranspositionTable::with_capacity(100_000);
+    let mut game_state = GameState::new();
+    let moves = ["f2f3", "e7e6", "g2g4"].iter().map(|s| s.to_string());
+    test_utils::apply_position(moves, &mut game_state, &move_generator);
+    let original_hash = game_state.zobrist_hash;
+
+    let (best_move, _, depth, nodes) = parallel_iterative_alpha_beta(
+        &mut game_state,
+        &move_generator,
+        &table,
+        4,
+        Duration::from_millis(100),
+    );
+
+    assert_eq!(Position::new(8, 4), best_move.unwrap().to);
+    assert!(depth > 1);
+    assert!(nodes > 0);
+    assert_eq!(original_hash, game_state.zobrist_hash);
+}
+
+#[test]
+fn lazy_smp_without_legal_moves() {
+    let move_generator = MoveGenerator::new();
+    let table = SharedTranspositionTable::with_capacity(1_000);
+    let mut game_state = GameState::new();
+    let moves = ["f2f3", "e7e5", "g2g4", "d8h4"]
+        .iter()
+        .map(|s| s.to_string());
+    test_utils::apply_position(moves, &mut game_state, &move_generator);
+
+    let (best_move, _, _, _) = parallel_iterative_alpha_beta(
+        &mut game_state,
+        &move_generator,
+        &table,
+        2,
+        Duration::from_millis(20),
+    );
+
+    assert_eq!(None, best_move);
+}
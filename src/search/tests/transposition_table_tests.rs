@@ -0,0 +1,99 @@
+#[cfg(test)]
+use crate::model::game_state::{PackedMove, Position};
+#[cfg(test)]
+use crate::search::transposition_table::{MatchType, TranspositionTable};
+
+#[test]
+fn stored_entry_is_found() {
+    let mut table = TranspositionTable::with_capacity(1_000);
+
+    table.update(12345, 4, 100, MatchType::EXACT, None);
+
+    assert_eq!(Some((MatchType::EXACT, 100)), table.check(12345, 4));
+    assert_eq!(Some((MatchType::EXACT, 100)), table.check(12345, 2));
+    assert_eq!(None, table.check(12345, 5));
+    assert_eq!(None, table.check(54321, 0));
+}
+
+#[test]
+fn deep_entries_are_not_replaced_by_shallow_ones() {
+    // a single bucket, so all positions compete for the same slots
+    let mut table = TranspositionTable::with_capacity(1);
+
+    for hash in 0..3 {
+        table.update(hash, 10, hash as i32, MatchType::EXACT, None);
+    }
+    for hash in 100..110 {
+        table.update(hash, 1, 0, MatchType::LOWERBOUND, None);
+    }
+
+    for hash in 0..3 {
+        assert_eq!(Some((MatchType::EXACT, hash as i32)), table.check(hash, 10));
+    }
+    // the latest shallow entry is kept in the always replace slot
+    assert_eq!(Some((MatchType::LOWERBOUND, 0)), table.check(109, 1));
+    assert_eq!(None, table.check(100, 1));
+}
+
+#[test]
+fn entries_from_old_searches_are_replaced() {
+    let mut table = TranspositionTable::with_capacity(1);
+
+    for hash in 0..3 {
+        table.update(hash, 4, 0, MatchType::EXACT, None);
+    }
+    table.new_search();
+    table.update(100, 1, 0, MatchType::UPPERBOUND, None);
+    table.update(101, 1, 0, MatchType::UPPERBOUND, None);
+
+    assert_eq!(Some((MatchType::UPPERBOUND, 0)), table.check(100, 1));
+    assert_eq!(Some((MatchType::UPPERBOUND, 0)), table.check(101, 1));
+}
+
+#[test]
+fn clear_removes_all_entries() {
+    let mut table = TranspositionTable::with_capacity(1_000);
+
+    table.update(1, 1, 1, MatchType::EXACT, None);
+    table.clear();
+
+    assert_eq!(None, table.check(1, 0));
+    assert_eq!(0, table.hashfull());
+}
+
+#[test]
+fn hashfull_counts_entries_of_current_search() {
+    let mut table = TranspositionTable::with_capacity(8);
+
+    assert_eq!(0, table.hashfull());
+
+    table.update(0, 1, 0, MatchType::EXACT, None);
+    table.update(u64::MAX, 1, 0, MatchType::EXACT, None);
+    assert_eq!(250, table.hashfull());
+
+    table.new_search();
+    assert_eq!(0, table.hashfull());
+}
+
+#[test]
+fn size_in_megabytes() {
+    let table = TranspositionTable::with_size_mb(1);
+
+    // four entries in each 64 byte bucket
+    assert_eq!(1024 * 1024 / 64 * 4, table.capacity());
+}
+
+#[test]
+fn best_move_is_kept_at_any_depth() {
+    let mut table = TranspositionTable::with_capacity(1_000);
+    let best_move = PackedMove::new(Position::new(7, 1), Position::new(6, 3), None);
+
+    table.update(12345, 4, 100, MatchType::LOWERBOUND, Some(best_move));
+    assert_eq!(Some(best_move), table.best_move(12345));
+    assert_eq!(None, table.best_move(54321));
+
+    // a deeper search without a best move replaces the entry but keeps the move
+    table.update(12345, 6, 50, MatchType::UPPERBOUND, None);
+    assert_eq!(Some((MatchType::UPPERBOUND, 50)), table.check(12345, 6));
+    assert_eq!(Some(best_move), table.best_move(12345));
+}
@@ -6,6 +6,7 @@ use crate::model::move_generator::MoveGenerator;
 use crate::uci::uci_utils::parse_move;
 
 #[cfg(test)]
+#[allow(dead_code)]
 pub fn apply_position<I>(moves: I, game_state: &mut GameState, move_generator: &MoveGenerator)
 where
     I: IntoIterator<Item = String>,
@@ -16,7 +17,7 @@ where
         .map(|m| m.unwrap());
 
     for m in parsed_moves {
-        let matching_move = move_generator.get_move(game_state, m.0, m.1).unwrap();
+        let matching_move = move_generator.find_move(game_state, &m).unwrap();
         game_state.apply_move_mut(matching_move);
     }
 }
@@ -0,0 +1,189 @@
+use std::convert::TryFrom;
+
+use crate::model::game_state::PackedMove;
+use crate::search::transposition_table::{MatchType, ZobristHash};
+
+// Entry packing, bucket layout and replacement policy of the transposition tables. The single
+// threaded and the shared table only differ in how an entry is stored in its slot.
+
+pub(crate) const ENTRIES_PER_BUCKET: usize = 4;
+// the last entry of a bucket is always replaced, the others only by deeper or newer entries
+const ALWAYS_REPLACE_SLOT: usize = ENTRIES_PER_BUCKET - 1;
+
+const BOUND_BITS: u8 = 0b11;
+const GENERATION_BITS: u8 = 0b1111_1100;
+pub(crate) const GENERATION_STEP: u8 = 0b100;
+
+const HASHFULL_SAMPLE_BUCKETS: usize = 250;
+
+#[repr(C, align(64))]
+#[derive(Copy, Clone, Default)]
+pub(crate) struct Bucket<S> {
+    pub(crate) entries: [S; ENTRIES_PER_BUCKET],
+}
+
+// a place in a bucket holding one entry
+pub(crate) trait Slot {
+    // the stored data if the entry is for the given position
+    fn load(&self, zobrist_hash: ZobristHash) -> Option<EntryData>;
+
+    // the stored data of whatever position the entry is for
+    fn data(&self) -> EntryData;
+}
+
+// maps the hash uniformly onto the buckets without a division
+pub(crate) fn table_index(zobrist_hash: ZobristHash, bucket_count: usize) -> usize {
+    let bucket_count = u128::try_from(bucket_count).unwrap();
+    usize::try_from((u128::from(zobrist_hash) * bucket_count) >> 64).unwrap()
+}
+
+pub(crate) fn probe<S: Slot>(bucket: &Bucket<S>, zobrist_hash: ZobristHash) -> Option<EntryData> {
+    bucket
+        .entries
+        .iter()
+        .filter_map(|entry| entry.load(zobrist_hash))
+        .find(|data| !data.is_empty())
+}
+
+// The slot of the bucket the new data goes to, and the data to store there. None when the entry
+// already stored for the position is worth more than the new one.
+pub(crate) fn replacement<S: Slot>(
+    bucket: &Bucket<S>,
+    zobrist_hash: ZobristHash,
+    data: EntryData,
+    generation: u8,
+) -> Option<(usize, EntryData)> {
+    let existing = bucket
+        .entries
+        .iter()
+        .enumerate()
+        .find_map(|(index, entry)| {
+            entry
+                .load(zobrist_hash)
+                .filter(|data| !data.is_empty())
+                .map(|data| (index, data))
+        });
+
+    if let Some((index, existing_data)) = existing {
+        if data.depth() >= existing_data.depth()
+            || data.match_type() == MatchType::EXACT
+            || existing_data.generation() != generation
+        {
+            // a search without a best move keeps the one found earlier
+            let data = match (data.best_move(), existing_data.best_move()) {
+                (None, Some(existing_move)) => data.with_best_move(existing_move),
+                _ => data,
+            };
+            return Some((index, data));
+        }
+        return None;
+    }
+
+    let (victim_index, victim_data) = bucket.entries[..ALWAYS_REPLACE_SLOT]
+        .iter()
+        .map(Slot::data)
+        .enumerate()
+        .min_by_key(|(_, candidate)| candidate.replacement_priority(generation))
+        .unwrap();
+
+    if victim_data.is_empty()
+        || victim_data.generation() != generation
+        || data.depth() >= victim_data.depth()
+    {
+        Some((victim_index, data))
+    } else {
+        Some((ALWAYS_REPLACE_SLOT, data))
+    }
+}
+
+// permille of sampled entries used by the current search
+pub(crate) fn hashfull<S: Slot>(buckets: &[Bucket<S>], generation: u8) -> u16 {
+    let sample = &buckets[..usize::min(HASHFULL_SAMPLE_BUCKETS, buckets.len())];
+    let used = sample
+        .iter()
+        .flat_map(|bucket| bucket.entries.iter())
+        .map(Slot::data)
+        .filter(|data| !data.is_empty() && data.generation() == generation)
+        .count();
+
+    u16::try_from(used * 1000 / (sample.len() * ENTRIES_PER_BUCKET)).unwrap()
+}
+
+// evaluation in the low 32 bits, then depth, flags (generation in the high six bits, match type in
+// the low two bits, zero when empty) and the packed best move (zero when there is none)
+#[derive(Copy, Clone, Default)]
+pub(crate) struct EntryData(pub(crate) u64);
+
+impl EntryData {
+    pub(crate) fn new(
+        depth: u16,
+        evaluation: i32,
+        match_type: MatchType,
+        generation: u8,
+        best_move: Option<PackedMove>,
+    ) -> EntryData {
+        let bound: u8 = match match_type {
+            MatchType::EXACT => 1,
+            MatchType::LOWERBOUND => 2,
+            MatchType::UPPERBOUND => 3,
+        };
+        let depth = u8::try_from(depth).unwrap_or(u8::MAX);
+        let flags = (generation & GENERATION_BITS) | bound;
+
+        EntryData(
+            u64::from(evaluation as u32) | (u64::from(depth) << 32) | (u64::from(flags) << 40),
+        )
+        .with_best_move_bits(best_move.map_or(0, |m| m.bits()))
+    }
+
+    fn with_best_move(self, best_move: PackedMove) -> EntryData {
+        self.with_best_move_bits(best_move.bits())
+    }
+
+    fn with_best_move_bits(self, bits: u16) -> EntryData {
+        EntryData(self.0 & !(u64::from(u16::MAX) << 48) | (u64::from(bits) << 48))
+    }
+
+    pub(crate) fn best_move(&self) -> Option<PackedMove> {
+        let bits = (self.0 >> 48) as u16;
+        Some(PackedMove::from_bits(bits)).filter(|_| bits != 0)
+    }
+
+    pub(crate) fn evaluation(&self) -> i32 {
+        self.0 as u32 as i32
+    }
+
+    pub(crate) fn depth(&self) -> u8 {
+        (self.0 >> 32) as u8
+    }
+
+    fn flags(&self) -> u8 {
+        (self.0 >> 40) as u8
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.flags() & BOUND_BITS == 0
+    }
+
+    fn generation(&self) -> u8 {
+        self.flags() & GENERATION_BITS
+    }
+
+    pub(crate) fn match_type(&self) -> MatchType {
+        match self.flags() & BOUND_BITS {
+            1 => MatchType::EXACT,
+            2 => MatchType::LOWERBOUND,
+            _ => MatchType::UPPERBOUND,
+        }
+    }
+
+    // lower values are replaced first: empty entries, then old and shallow ones
+    fn replacement_priority(&self, generation: u8) -> i32 {
+        if self.is_empty() {
+            return i32::MIN;
+        }
+
+        let age = generation.wrapping_sub(self.generation()) / GENERATION_STEP;
+        i32::from(self.depth()) - 8 * i32::from(age)
+    }
+}
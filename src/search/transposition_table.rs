@@ -1,40 +1,94 @@
-use std::convert::TryFrom;
+use crate::model::game_state::PackedMove;
+use crate::search::transposition_entry::{
+    self, Bucket, EntryData, Slot, ENTRIES_PER_BUCKET, GENERATION_STEP,
+};
+
+use std::mem;
 
 pub type ZobristHash = u64;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum MatchType {
     EXACT,
     LOWERBOUND,
     UPPERBOUND,
 }
 
+// Transposition table operations used by the search, implemented both by the single threaded
+// table and by references to the shared table.
+pub trait TranspositionStore {
+    fn check(&self, zobrist_hash: ZobristHash, depth: u16) -> Option<(MatchType, i32)>;
+
+    fn best_move(&self, zobrist_hash: ZobristHash) -> Option<PackedMove>;
+
+    fn update(
+        &mut self,
+        zobrist_hash: ZobristHash,
+        depth: u16,
+        evaluation: i32,
+        match_type: MatchType,
+        best_move: Option<PackedMove>,
+    );
+
+    fn new_search(&mut self);
+}
+
 pub struct TranspositionTable {
-    data: Vec<Option<TranspositionEntry>>,
-    capacity: usize,
+    buckets: Vec<Bucket<Entry>>,
+    generation: u8,
 }
 
 impl TranspositionTable {
     pub fn with_capacity(capacity: usize) -> TranspositionTable {
+        let bucket_count = usize::max(1, capacity / ENTRIES_PER_BUCKET);
+
         TranspositionTable {
-            data: vec![None; capacity],
-            capacity: capacity,
+            buckets: vec![Bucket::default(); bucket_count],
+            generation: 0,
         }
     }
 
-    pub fn check(&self, zobrist_hash: ZobristHash, depth: u16) -> Option<(MatchType, i32)> {
-        let table_index = self.get_table_index(zobrist_hash);
-
-        match self.data[table_index] {
-            None => None,
-            Some(entry) => {
-                if entry.zobrist_hash == zobrist_hash && entry.depth >= depth {
-                    Some((entry.match_type, entry.evaluation))
-                } else {
-                    None
-                }
-            }
+    pub fn with_size_mb(size_mb: usize) -> TranspositionTable {
+        TranspositionTable::with_capacity(
+            size_mb * 1024 * 1024 / mem::size_of::<Bucket<Entry>>() * ENTRIES_PER_BUCKET,
+        )
+    }
+
+    pub fn resize_mb(&mut self, size_mb: usize) {
+        *self = TranspositionTable::with_size_mb(size_mb);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buckets.len() * ENTRIES_PER_BUCKET
+    }
+
+    pub fn clear(&mut self) {
+        for bucket in self.buckets.iter_mut() {
+            *bucket = Bucket::default();
         }
+        self.generation = 0;
+    }
+
+    // entries stored in earlier searches become preferred candidates for replacement
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(GENERATION_STEP);
+    }
+
+    // permille of sampled entries used by the current search
+    pub fn hashfull(&self) -> u16 {
+        transposition_entry::hashfull(&self.buckets, self.generation)
+    }
+
+    pub fn check(&self, zobrist_hash: ZobristHash, depth: u16) -> Option<(MatchType, i32)> {
+        transposition_entry::probe(self.bucket(zobrist_hash), zobrist_hash)
+            .filter(|data| u16::from(data.depth()) >= depth)
+            .map(|data| (data.match_type(), data.evaluation()))
+    }
+
+    // the best move found in the position at any depth, to be searched first
+    pub fn best_move(&self, zobrist_hash: ZobristHash) -> Option<PackedMove> {
+        transposition_entry::probe(self.bucket(zobrist_hash), zobrist_hash)
+            .and_then(|data| data.best_move())
     }
 
     pub fn update(
@@ -43,27 +97,64 @@ impl TranspositionTable {
         depth: u16,
         evaluation: i32,
         match_type: MatchType,
+        best_move: Option<PackedMove>,
     ) {
-        let table_index = self.get_table_index(zobrist_hash);
-        let entry = TranspositionEntry {
-            evaluation: evaluation,
-            depth: depth,
-            match_type: match_type,
-            zobrist_hash,
-        };
+        let data = EntryData::new(depth, evaluation, match_type, self.generation, best_move);
+        let index = transposition_entry::table_index(zobrist_hash, self.buckets.len());
+        let bucket = &mut self.buckets[index];
+
+        if let Some((slot, data)) =
+            transposition_entry::replacement(bucket, zobrist_hash, data, self.generation)
+        {
+            bucket.entries[slot] = Entry {
+                key: zobrist_hash,
+                data,
+            };
+        }
+    }
+
+    fn bucket(&self, zobrist_hash: ZobristHash) -> &Bucket<Entry> {
+        &self.buckets[transposition_entry::table_index(zobrist_hash, self.buckets.len())]
+    }
+}
+
+impl TranspositionStore for TranspositionTable {
+    fn check(&self, zobrist_hash: ZobristHash, depth: u16) -> Option<(MatchType, i32)> {
+        TranspositionTable::check(self, zobrist_hash, depth)
+    }
+
+    fn best_move(&self, zobrist_hash: ZobristHash) -> Option<PackedMove> {
+        TranspositionTable::best_move(self, zobrist_hash)
+    }
 
-        self.data[table_index] = Some(entry);
+    fn update(
+        &mut self,
+        zobrist_hash: ZobristHash,
+        depth: u16,
+        evaluation: i32,
+        match_type: MatchType,
+        best_move: Option<PackedMove>,
+    ) {
+        TranspositionTable::update(self, zobrist_hash, depth, evaluation, match_type, best_move)
     }
 
-    fn get_table_index(&self, zobrist_hash: ZobristHash) -> usize {
-        usize::try_from(zobrist_hash % u64::try_from(self.capacity).unwrap()).unwrap()
+    fn new_search(&mut self) {
+        TranspositionTable::new_search(self)
     }
 }
 
-#[derive(Copy, Clone)]
-struct TranspositionEntry {
-    pub evaluation: i32,
-    pub depth: u16,
-    pub match_type: MatchType,
-    pub zobrist_hash: ZobristHash,
+#[derive(Copy, Clone, Default)]
+struct Entry {
+    key: ZobristHash,
+    data: EntryData,
+}
+
+impl Slot for Entry {
+    fn load(&self, zobrist_hash: ZobristHash) -> Option<EntryData> {
+        Some(self.data).filter(|_| self.key == zobrist_hash)
+    }
+
+    fn data(&self) -> EntryData {
+        self.data
+    }
 }
@@ -0,0 +1,589 @@
+use std::convert::TryFrom;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::model::game_state::{Color, GameState, Move};
+use crate::model::game_state_builder::GameStateBuilder;
+#[cfg(feature = "syzygy")]
+use crate::model::syzygy::Tablebase;
+use crate::model::variant::Variant;
+use crate::notation::fen::parse_fen;
+use crate::search::score::Score;
+use crate::search::search_control::{SearchControl, SearchProgress};
+use crate::search::searcher::{SearchLimits, SearchOutcome, Searcher};
+use crate::search::transposition_table::MatchType;
+use crate::uci::uci_utils::{move_to_uci, parse_move};
+
+const ENGINE_NAME: &str = "rust-chess";
+const ENGINE_AUTHOR: &str = "Jiri Hartikka";
+
+const DEFAULT_HASH_MB: usize = 16;
+const MAX_HASH_MB: usize = 4096;
+
+const DEFAULT_THREADS: usize = 1;
+const MAX_THREADS: usize = 256;
+
+const DEFAULT_MULTI_PV: usize = 1;
+const MAX_MULTI_PV: usize = 256;
+
+// how often a finished ponder or infinite search checks whether it may send its best move
+const WAIT_INTERVAL: Duration = Duration::from_millis(1);
+
+const DEFAULT_MOVE_TIME: Duration = Duration::from_secs(1);
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+
+// evaluation units per centipawn
+const EVAL_PER_CENTIPAWN: i32 = 10;
+
+// A search started by go runs in a background thread, which sends its info lines and the best
+// move itself, so that stop and ponderhit are handled while it runs.
+struct RunningSearch {
+    control: Arc<SearchControl>,
+    infinite: bool,
+    // set by the search thread once it has sent its best move
+    finished: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+// Commands other than stop, ponderhit, isready and quit wait for a running search to finish, a
+// ponder or infinite search is stopped first.
+pub struct UciEngine<W: Write + Send + 'static> {
+    output: Arc<Mutex<W>>,
+    game_state: GameState,
+    searcher: Arc<Mutex<Searcher>>,
+    search: Option<RunningSearch>,
+    // castling moves are sent as the king taking its rook
+    chess960: bool,
+    // the rules of the positions set up by the GUI
+    variant: Variant,
+}
+
+impl<W: Write + Send + 'static> UciEngine<W> {
+    pub fn new(output: W) -> Self {
+        UciEngine {
+            output: Arc::new(Mutex::new(output)),
+            game_state: GameState::new(),
+            searcher: Arc::new(Mutex::new(
+                Searcher::new()
+                    .with_table_size_mb(DEFAULT_HASH_MB)
+                    .with_threads(DEFAULT_THREADS),
+            )),
+            search: None,
+            chess960: false,
+            variant: Variant::Standard,
+        }
+    }
+
+    pub fn game_state(&self) -> &GameState {
+        &self.game_state
+    }
+
+    // entries of the transposition table
+    pub fn table_capacity(&self) -> usize {
+        self.searcher
+            .lock()
+            .unwrap()
+            .transposition_table()
+            .capacity()
+    }
+
+    pub fn threads(&self) -> usize {
+        self.searcher.lock().unwrap().threads()
+    }
+
+    pub fn is_searching(&self) -> bool {
+        matches!(&self.search, Some(search) if !search.finished.load(Ordering::Relaxed))
+    }
+
+    // returns false when the engine should quit
+    pub fn handle_command(&mut self, command: &str) -> bool {
+        let mut tokens = command.split_whitespace();
+
+        let result = match tokens.next() {
+            Some("uci") => {
+                self.uci();
+                Ok(())
+            }
+            Some("isready") => {
+                self.send("readyok");
+                Ok(())
+            }
+            Some("ucinewgame") => {
+                self.end_search();
+                self.searcher.lock().unwrap().new_game();
+                with_variant(&GameState::new(), self.variant)
+                    .map(|game_state| self.game_state = game_state)
+            }
+            Some("setoption") => {
+                self.end_search();
+                self.set_option(&tokens.collect::<Vec<_>>())
+            }
+            Some("position") => {
+                self.end_search();
+                self.position(&tokens.collect::<Vec<_>>())
+            }
+            Some("go") => {
+                self.end_search();
+                self.go(&tokens.collect::<Vec<_>>())
+            }
+            Some("stop") => {
+                self.stop_search();
+                Ok(())
+            }
+            Some("ponderhit") => {
+                if let Some(search) = &self.search {
+                    search.control.ponderhit();
+                }
+                Ok(())
+            }
+            Some("quit") => {
+                self.stop_search();
+                return false;
+            }
+            Some(unknown) => Err(format!("Unknown command: {}", unknown)),
+            None => Ok(()),
+        };
+
+        if let Err(message) = result {
+            self.send(&format!("info string {}", message));
+        }
+
+        true
+    }
+
+    fn uci(&mut self) {
+        self.send(&format!("id name {}", ENGINE_NAME));
+        self.send(&format!("id author {}", ENGINE_AUTHOR));
+        self.send(&format!(
+            "option name Hash type spin default {} min 1 max {}",
+            DEFAULT_HASH_MB, MAX_HASH_MB
+        ));
+        self.send(&format!(
+            "option name Threads type spin default {} min 1 max {}",
+            DEFAULT_THREADS, MAX_THREADS
+        ));
+        self.send(&format!(
+            "option name MultiPV type spin default {} min 1 max {}",
+            DEFAULT_MULTI_PV, MAX_MULTI_PV
+        ));
+        self.send("option name Ponder type check default false");
+        self.send("option name UCI_Chess960 type check default false");
+        let variants: Vec<String> = Variant::ALL
+            .iter()
+            .map(|variant| format!("var {}", variant))
+            .collect();
+        self.send(&format!(
+            "option name UCI_Variant type combo default {} {}",
+            Variant::Standard,
+            variants.join(" ")
+        ));
+        #[cfg(feature = "syzygy")]
+        self.send("option name SyzygyPath type string default <empty>");
+        self.send("uciok");
+    }
+
+    fn set_option(&mut self, args: &[&str]) -> Result<(), String> {
+        let value_index = args.iter().position(|arg| *arg == "value");
+        let name = args[..value_index.unwrap_or(args.len())]
+            .iter()
+            .skip_while(|arg| **arg == "name")
+            .copied()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let value = value_index
+            .map(|index| args[index + 1..].join(" "))
+            .unwrap_or_default();
+
+        let mut searcher = self.searcher.lock().unwrap();
+
+        match name.to_lowercase().as_str() {
+            "hash" => {
+                let size_mb = value
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid Hash value: {}", value))?;
+                searcher.resize_table(size_mb.clamp(1, MAX_HASH_MB));
+                Ok(())
+            }
+            "threads" => {
+                let threads = value
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid Threads value: {}", value))?;
+                searcher.set_threads(threads.clamp(1, MAX_THREADS));
+                Ok(())
+            }
+            "multipv" => {
+                let multi_pv = value
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid MultiPV value: {}", value))?;
+                searcher.set_multi_pv(multi_pv.clamp(1, MAX_MULTI_PV));
+                Ok(())
+            }
+            // pondering is controlled by the GUI through go ponder, nothing to set up
+            "ponder" => Ok(()),
+            "uci_chess960" => {
+                self.chess960 = value
+                    .parse::<bool>()
+                    .map_err(|_| format!("Invalid UCI_Chess960 value: {}", value))?;
+                Ok(())
+            }
+            "uci_variant" => {
+                let variant = value.parse::<Variant>()?;
+                // positions of another variant are searched and scored differently
+                if variant != self.variant {
+                    self.variant = variant;
+                    self.game_state = with_variant(&GameState::new(), variant)?;
+                    searcher.new_game();
+                }
+                Ok(())
+            }
+            // directories separated like those of the PATH environment variable
+            #[cfg(feature = "syzygy")]
+            "syzygypath" => {
+                let tablebase = match value.as_str() {
+                    "" | "<empty>" => None,
+                    paths => {
+                        let tablebase = Tablebase::open(paths)?;
+                        send(
+                            &self.output,
+                            &format!(
+                                "info string Found {} tablebases with up to {} pieces",
+                                tablebase.table_count(),
+                                tablebase.max_pieces()
+                            ),
+                        );
+                        Some(Arc::new(tablebase))
+                    }
+                };
+                searcher.set_tablebase(tablebase);
+                Ok(())
+            }
+            _ => Err(format!("Unknown option: {}", name)),
+        }
+    }
+
+    fn position(&mut self, args: &[&str]) -> Result<(), String> {
+        let moves_index = args
+            .iter()
+            .position(|arg| *arg == "moves")
+            .unwrap_or(args.len());
+        let (setup, moves) = args.split_at(moves_index);
+
+        let mut game_state = match setup.split_first() {
+            Some((&"startpos", [])) => GameState::new(),
+            Some((&"fen", fen)) => parse_fen(&fen.join(" "))?,
+            Some((other, _)) => return Err(format!("Unsupported position: {}", other)),
+            None => return Err("Missing position".to_string()),
+        };
+        game_state = with_variant(&game_state, self.variant)?;
+        let searcher = self.searcher.lock().unwrap();
+
+        for uci_move in moves.iter().skip_while(|arg| **arg == "moves") {
+            let parsed = parse_move(uci_move)?;
+            let to_apply = searcher
+                .move_generator()
+                .find_move(&game_state, &parsed)
+                .ok_or_else(|| format!("Illegal move: {}", uci_move))?;
+            game_state.apply_move_mut(to_apply);
+        }
+
+        drop(searcher);
+        self.game_state = game_state;
+        Ok(())
+    }
+
+    fn go(&mut self, args: &[&str]) -> Result<(), String> {
+        let parameters = GoParameters::parse(args)?;
+        let limits = parameters.search_limits(self.game_state.to_move());
+        let search_moves = self.search_moves(&parameters.search_moves)?;
+        let multi_pv = self.searcher.lock().unwrap().multi_pv() > 1;
+
+        let progress_output = self.output.clone();
+        let chess960 = self.chess960;
+        let control = SearchControl::new().with_progress(move |progress| {
+            send(
+                &progress_output,
+                &progress_info(progress, multi_pv, chess960),
+            )
+        });
+        let control = Arc::new(if parameters.ponder {
+            control.with_ponder()
+        } else {
+            control
+        });
+
+        let search_control = control.clone();
+        let searcher = self.searcher.clone();
+        let output = self.output.clone();
+        let mut game_state = self.game_state;
+        let infinite = parameters.infinite;
+        let chess960 = self.chess960;
+        let finished = Arc::new(AtomicBool::new(false));
+        let search_finished = finished.clone();
+
+        let handle = thread::spawn(move || {
+            let mut searcher = searcher.lock().unwrap();
+            searcher.set_limits(limits);
+            let outcome = match search_moves {
+                Some(moves) => searcher
+                    .search_moves_with_control(&mut game_state, &moves, &search_control)
+                    .unwrap(),
+                None => searcher.search_with_control(&mut game_state, &search_control),
+            };
+
+            // the best move of a ponder or infinite search is only sent once the GUI asks for it
+            while (search_control.is_pondering() || infinite) && !search_control.is_stopped() {
+                thread::sleep(WAIT_INTERVAL);
+            }
+
+            let hashfull = searcher.transposition_table().hashfull();
+            send_result(&output, &outcome, hashfull, chess960);
+            search_finished.store(true, Ordering::Relaxed);
+        });
+
+        self.search = Some(RunningSearch {
+            control,
+            infinite,
+            finished,
+            handle,
+        });
+        Ok(())
+    }
+
+    // the moves of go searchmoves, None when all moves are searched
+    fn search_moves(&self, uci_moves: &[String]) -> Result<Option<Vec<Move>>, String> {
+        if uci_moves.is_empty() {
+            return Ok(None);
+        }
+
+        let searcher = self.searcher.lock().unwrap();
+        let mut moves = Vec::new();
+
+        for uci_move in uci_moves {
+            let parsed = parse_move(uci_move)?;
+            let m = searcher
+                .move_generator()
+                .find_move(&self.game_state, &parsed)
+                .ok_or_else(|| format!("Illegal move: {}", uci_move))?;
+            moves.push(m);
+        }
+
+        Ok(Some(moves))
+    }
+
+    fn stop_search(&mut self) {
+        if let Some(search) = &self.search {
+            search.control.stop();
+        }
+        self.wait_for_search();
+    }
+
+    // a search that would not end by itself is stopped
+    fn end_search(&mut self) {
+        if let Some(search) = &self.search {
+            if search.infinite || search.control.is_pondering() {
+                search.control.stop();
+            }
+        }
+        self.wait_for_search();
+    }
+
+    fn wait_for_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.handle.join().unwrap();
+        }
+    }
+
+    fn send(&mut self, message: &str) {
+        send(&self.output, message);
+    }
+}
+
+impl<W: Write + Send + 'static> Drop for UciEngine<W> {
+    fn drop(&mut self) {
+        self.end_search();
+    }
+}
+
+fn send<W: Write>(output: &Mutex<W>, message: &str) {
+    let mut output = output.lock().unwrap();
+    writeln!(output, "{}", message).unwrap();
+    output.flush().unwrap();
+}
+
+fn send_result<W: Write>(
+    output: &Mutex<W>,
+    outcome: &SearchOutcome,
+    hashfull: u16,
+    chess960: bool,
+) {
+    send(
+        output,
+        &format!(
+            "info depth {} score {} nodes {} hashfull {}",
+            outcome.depth,
+            uci_score(outcome.score),
+            outcome.nodes,
+            hashfull
+        ),
+    );
+
+    // the expected reply is the second move of the principal variation
+    let ponder_move = outcome.lines.first().and_then(|(_, _, pv)| pv.get(1));
+
+    match (outcome.best_move, ponder_move) {
+        (Some(m), Some(ponder_move)) => send(
+            output,
+            &format!(
+                "bestmove {} ponder {}",
+                move_to_uci(&m, chess960),
+                move_to_uci(ponder_move, chess960)
+            ),
+        ),
+        (Some(m), None) => send(output, &format!("bestmove {}", move_to_uci(&m, chess960))),
+        (None, _) => send(output, "bestmove 0000"),
+    }
+}
+
+fn uci_score(score: Score) -> String {
+    match score {
+        Score::Evaluation(eval) => format!("cp {}", eval / EVAL_PER_CENTIPAWN),
+        Score::MateIn(moves) => format!("mate {}", moves),
+    }
+}
+
+// the position played by the rules of the variant
+fn with_variant(game_state: &GameState, variant: Variant) -> Result<GameState, String> {
+    let game_state = GameStateBuilder::from(game_state)
+        .with_variant(variant)
+        .build()?;
+    Ok(game_state)
+}
+
+// the rank of the line is only shown when more than one line is searched
+fn progress_info(progress: &SearchProgress, multi_pv: bool, chess960: bool) -> String {
+    let multipv = if multi_pv {
+        format!(" multipv {}", progress.multipv)
+    } else {
+        String::new()
+    };
+    let bound = match progress.bound {
+        MatchType::EXACT => "",
+        MatchType::LOWERBOUND => " lowerbound",
+        MatchType::UPPERBOUND => " upperbound",
+    };
+    let pv = progress
+        .pv
+        .iter()
+        .map(|m| move_to_uci(m, chess960).to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!(
+        "info depth {}{} score {}{} nodes {} time {} pv {}",
+        progress.depth,
+        multipv,
+        uci_score(progress.score),
+        bound,
+        progress.nodes,
+        progress.elapsed.as_millis(),
+        pv
+    )
+}
+
+#[derive(Default)]
+struct GoParameters {
+    depth: Option<u16>,
+    move_time: Option<u64>,
+    white_time: Option<u64>,
+    black_time: Option<u64>,
+    white_increment: u64,
+    black_increment: u64,
+    moves_to_go: Option<u64>,
+    nodes: Option<u64>,
+    ponder: bool,
+    infinite: bool,
+    search_moves: Vec<String>,
+}
+
+impl GoParameters {
+    fn parse(args: &[&str]) -> Result<GoParameters, String> {
+        let mut parameters = GoParameters::default();
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            let mut value = || -> Result<u64, String> {
+                iter.next()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .ok_or_else(|| format!("Missing or invalid value for {}", arg))
+            };
+
+            match *arg {
+                "depth" => {
+                    let depth = value()?;
+                    parameters.depth = Some(
+                        u16::try_from(depth)
+                            .map_err(|_| format!("Depth out of range: {}", depth))?,
+                    );
+                }
+                "movetime" => parameters.move_time = Some(value()?),
+                "wtime" => parameters.white_time = Some(value()?),
+                "btime" => parameters.black_time = Some(value()?),
+                "winc" => parameters.white_increment = value()?,
+                "binc" => parameters.black_increment = value()?,
+                "movestogo" => parameters.moves_to_go = Some(value()?),
+                "nodes" => parameters.nodes = Some(value()?),
+                "ponder" => parameters.ponder = true,
+                "infinite" => parameters.infinite = true,
+                "searchmoves" => {
+                    while let Some(uci_move) =
+                        iter.as_slice().first().filter(|m| parse_move(m).is_ok())
+                    {
+                        parameters.search_moves.push(uci_move.to_string());
+                        iter.next();
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        Ok(parameters)
+    }
+
+    fn search_limits(&self, to_move: Color) -> SearchLimits {
+        SearchLimits {
+            depth: self.depth,
+            time: self.move_time(to_move),
+            nodes: self.nodes,
+        }
+    }
+
+    // no time limit for infinite searches, or when only the depth or the number of nodes is limited
+    fn move_time(&self, to_move: Color) -> Option<Duration> {
+        if self.infinite {
+            return None;
+        }
+
+        if let Some(move_time) = self.move_time {
+            return Some(Duration::from_millis(move_time));
+        }
+
+        let (time, increment) = match to_move {
+            Color::WHITE => (self.white_time, self.white_increment),
+            Color::BLACK => (self.black_time, self.black_increment),
+        };
+
+        match time {
+            Some(time) => {
+                let moves_to_go = self.moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+                let budget = time / moves_to_go + increment / 2;
+                Some(Duration::from_millis(budget.min(time / 2)))
+            }
+            None if self.depth.is_some() || self.nodes.is_some() => None,
+            None => Some(DEFAULT_MOVE_TIME),
+        }
+    }
+}
@@ -1 +1,3 @@
+pub mod engine;
+mod tests;
 pub mod uci_utils;
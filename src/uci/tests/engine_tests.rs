@@ -0,0 +1,383 @@
+#[cfg(test)]
+use crate::model::game_state::{Color, Piece, Position};
+#[cfg(test)]
+use crate::model::variant::Variant;
+#[cfg(test)]
+use crate::uci::engine::UciEngine;
+
+#[cfg(test)]
+use std::io::{self, Write};
+#[cfg(test)]
+use std::sync::{Arc, Mutex};
+#[cfg(test)]
+use std::thread;
+#[cfg(test)]
+use std::time::{Duration, Instant};
+
+// output written by the engine and by its search thread
+#[cfg(test)]
+#[derive(Clone, Default)]
+struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+#[cfg(test)]
+impl SharedOutput {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+
+    fn wait_for(&self, text: &str) -> String {
+        let start = Instant::now();
+        while !self.contents().contains(text) && start.elapsed() < Duration::from_secs(10) {
+            thread::sleep(Duration::from_millis(5));
+        }
+        self.contents()
+    }
+}
+
+#[cfg(test)]
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn run_commands(commands: &[&str]) -> String {
+    let output = SharedOutput::default();
+    {
+        let mut engine = UciEngine::new(output.clone());
+        for command in commands {
+            if !engine.handle_command(command) {
+                break;
+            }
+        }
+    }
+    output.contents()
+}
+
+#[test]
+fn uci_handshake_lists_hash_option() {
+    let output = run_commands(&["uci", "isready"]);
+
+    assert!(output.contains("id name rust-chess"));
+    assert!(output.contains("option name Hash type spin"));
+    assert!(output.contains("option name Threads type spin default 1"));
+    assert!(output.contains("option name MultiPV type spin default 1"));
+    assert!(output.contains("uciok"));
+    assert!(output.ends_with("readyok\n"));
+}
+
+#[test]
+fn set_hash_option_resizes_table() {
+    let mut engine = UciEngine::new(SharedOutput::default());
+
+    engine.handle_command("setoption name Hash value 1");
+    let small = engine.table_capacity();
+    engine.handle_command("setoption name Hash value 4");
+    let large = engine.table_capacity();
+
+    assert!(small > 0);
+    assert_eq!(4 * small, large);
+}
+
+#[test]
+fn invalid_option_value_is_reported() {
+    let output = run_commands(&["setoption name Hash value lots"]);
+
+    assert!(output.starts_with("info string"));
+}
+
+// 65537 would wrap around to depth 1
+#[test]
+fn out_of_range_depth_is_reported() {
+    let output = run_commands(&["go depth 65537"]);
+
+    assert!(output.contains("info string Depth out of range: 65537"));
+    assert!(!output.contains("bestmove"));
+}
+
+#[test]
+fn go_with_depth_finds_fools_mate() {
+    let output = run_commands(&["position startpos moves f2f3 e7e6 g2g4", "go depth 3"]);
+
+    assert!(output.ends_with("bestmove d8h4\n"));
+}
+
+#[test]
+fn go_with_several_threads() {
+    let output = SharedOutput::default();
+    {
+        let mut engine = UciEngine::new(output.clone());
+        engine.handle_command("setoption name Threads value 3");
+        assert_eq!(3, engine.threads());
+        engine.handle_command("position startpos moves f2f3 e7e6 g2g4");
+        engine.handle_command("go movetime 100");
+    }
+    let output = output.contents();
+
+    assert!(output.contains("info depth 1 score cp"));
+    assert!(output.contains(" pv d8h4"));
+    assert!(output.contains(" nodes "));
+    assert!(output.ends_with("bestmove d8h4\n"));
+}
+
+#[test]
+fn go_with_node_limit() {
+    let output = run_commands(&["position startpos moves f2f3 e7e6 g2g4", "go nodes 5000"]);
+
+    assert!(output.ends_with("bestmove d8h4\n"));
+}
+
+#[test]
+fn mate_score_is_reported() {
+    let output = run_commands(&["position startpos moves f2f3 e7e6 g2g4", "go movetime 50"]);
+
+    assert!(output.contains("score mate 1 "));
+}
+
+#[test]
+fn checkmate_on_the_board_is_reported() {
+    let output = run_commands(&["position startpos moves f2f3 e7e6 g2g4 d8h4", "go depth 3"]);
+
+    assert!(output.contains("info depth 0 score mate 0 "));
+    assert!(output.ends_with("bestmove 0000\n"));
+}
+
+#[test]
+fn illegal_move_in_position_is_reported() {
+    let output = run_commands(&["position startpos moves e2e5"]);
+
+    assert!(output.contains("Illegal move: e2e5"));
+}
+
+#[test]
+fn position_applies_underpromotion() {
+    let mut engine = UciEngine::new(SharedOutput::default());
+
+    engine.handle_command("position startpos moves a2a4 b7b5 a4b5 a7a6 b5a6 c8b7 a6b7 g8f6 b7a8n");
+
+    assert_eq!(
+        Some((Piece::KNIGHT, Color::WHITE)),
+        engine.game_state().get_piece(Position::new(1, 8))
+    );
+}
+
+#[test]
+fn quit_stops_the_engine() {
+    let output = run_commands(&["quit", "isready"]);
+
+    assert!(output.is_empty());
+}
+
+#[test]
+fn multi_pv_lines_are_reported() {
+    let output = run_commands(&[
+        "setoption name MultiPV value 2",
+        "position startpos moves f2f3 e7e6 g2g4",
+        "go depth 2",
+    ]);
+
+    assert!(output.contains("info depth 2 multipv 1 score mate 1 "));
+    assert!(output.contains("info depth 2 multipv 2 score "));
+    assert!(output.ends_with("bestmove d8h4\n"));
+}
+
+#[test]
+fn best_move_comes_with_ponder_move() {
+    let output = run_commands(&["position startpos", "go depth 3"]);
+
+    let last_line = output.lines().last().unwrap();
+    let tokens: Vec<_> = last_line.split_whitespace().collect();
+    assert_eq!(4, tokens.len());
+    assert_eq!("bestmove", tokens[0]);
+    assert_eq!("ponder", tokens[2]);
+}
+
+#[test]
+fn go_returns_while_searching_and_stop_ends_the_search() {
+    let output = SharedOutput::default();
+    let mut engine = UciEngine::new(output.clone());
+
+    engine.handle_command("position startpos moves f2f3 e7e6 g2g4");
+    engine.handle_command("go infinite");
+    thread::sleep(Duration::from_millis(100));
+    engine.handle_command("isready");
+
+    assert!(engine.is_searching());
+    assert!(output.contents().contains("readyok"));
+    assert!(!output.contents().contains("bestmove"));
+
+    engine.handle_command("stop");
+
+    assert!(!engine.is_searching());
+    assert!(output.contents().ends_with("bestmove d8h4\n"));
+}
+
+#[test]
+fn ponder_search_becomes_timed_on_ponderhit() {
+    let output = SharedOutput::default();
+    let mut engine = UciEngine::new(output.clone());
+
+    engine.handle_command("position startpos moves f2f3 e7e6 g2g4");
+    engine.handle_command("go ponder movetime 50");
+    thread::sleep(Duration::from_millis(200));
+
+    // the time limit does not apply while pondering
+    assert!(!output.contents().contains("bestmove"));
+
+    engine.handle_command("ponderhit");
+
+    assert!(output.wait_for("bestmove").ends_with("bestmove d8h4\n"));
+}
+
+#[test]
+fn stop_ends_ponder_search() {
+    let output = SharedOutput::default();
+    let mut engine = UciEngine::new(output.clone());
+
+    engine.handle_command("position startpos moves f2f3 e7e6 g2g4");
+    engine.handle_command("go ponder wtime 1000 btime 1000");
+    thread::sleep(Duration::from_millis(50));
+    engine.handle_command("stop");
+
+    assert!(output.contents().ends_with("bestmove d8h4\n"));
+}
+
+#[test]
+fn position_and_go_stop_infinite_and_ponder_searches() {
+    let output = SharedOutput::default();
+    let mut engine = UciEngine::new(output.clone());
+
+    engine.handle_command("position startpos moves f2f3 e7e6 g2g4");
+    engine.handle_command("go infinite");
+    engine.handle_command("position startpos moves f2f3 e7e6 g2g4");
+    engine.handle_command("go ponder wtime 1000 btime 1000");
+    engine.handle_command("ucinewgame");
+
+    assert!(!engine.is_searching());
+    assert_eq!(2, output.contents().matches("bestmove").count());
+}
+
+#[test]
+fn quit_stops_infinite_search() {
+    let output = run_commands(&["position startpos", "go infinite", "quit"]);
+
+    assert!(output.contains("bestmove "));
+}
+
+#[test]
+fn go_searchmoves_restricts_the_root_moves() {
+    let output = run_commands(&[
+        "position startpos moves f2f3 e7e6 g2g4",
+        "go depth 2 searchmoves a7a6 b7b6",
+    ]);
+
+    let best_move = output.lines().last().unwrap().split_whitespace().nth(1);
+    assert!(matches!(best_move, Some("a7a6") | Some("b7b6")));
+}
+
+#[test]
+fn illegal_searchmoves_are_reported() {
+    let output = run_commands(&["position startpos", "go depth 2 searchmoves e2e5"]);
+
+    assert!(output.contains("Illegal move: e2e5"));
+    assert!(!output.contains("bestmove"));
+}
+
+#[test]
+fn uci_handshake_lists_chess960_option() {
+    let output = run_commands(&["uci"]);
+
+    assert!(output.contains("option name UCI_Chess960 type check default false"));
+}
+
+#[test]
+fn position_fen_is_applied() {
+    let mut engine = UciEngine::new(SharedOutput::default());
+
+    engine.handle_command("position fen rk4r1/8/8/8/8/8/8/RK4R1 w GAga - 0 1 moves b1a1");
+
+    assert_eq!(
+        Some((Piece::KING, Color::WHITE)),
+        engine.game_state().get_piece(Position::new(3, 1))
+    );
+    assert_eq!(
+        Some((Piece::ROOK, Color::WHITE)),
+        engine.game_state().get_piece(Position::new(4, 1))
+    );
+}
+
+#[test]
+fn castling_is_sent_as_king_takes_rook_in_chess960() {
+    let position = "position startpos moves e2e4 e7e5 g1f3 b8c6 f1c4 g8f6";
+
+    let standard = run_commands(&[position, "go depth 1 searchmoves e1g1"]);
+    let chess960 = run_commands(&[
+        "setoption name UCI_Chess960 value true",
+        position,
+        "go depth 1 searchmoves e1h1",
+    ]);
+
+    assert!(standard.ends_with("bestmove e1g1\n"));
+    assert!(chess960.ends_with("bestmove e1h1\n"));
+}
+
+#[test]
+fn uci_handshake_lists_variants() {
+    let output = run_commands(&["uci"]);
+
+    assert!(output.contains(
+        "option name UCI_Variant type combo default chess var chess var kingofthehill var 3check"
+    ));
+}
+
+#[test]
+fn king_of_the_hill_is_played_after_setting_the_variant() {
+    let output = run_commands(&[
+        "setoption name UCI_Variant value kingofthehill",
+        "position fen 4k3/8/8/8/8/4K3/8/8 w - - 0 1",
+        "go depth 2",
+    ]);
+
+    assert!(output.contains("score mate 1 "));
+    assert!(output.ends_with("bestmove e3e4\n"));
+}
+
+#[test]
+fn variant_applies_to_the_positions_set_up() {
+    let mut engine = UciEngine::new(SharedOutput::default());
+
+    engine.handle_command("setoption name UCI_Variant value 3check");
+    engine.handle_command("position startpos moves e2e4 d7d5 f1b5");
+
+    assert_eq!(Variant::ThreeCheck, engine.game_state().variant());
+    assert_eq!(1, engine.game_state().checks_given(Color::WHITE));
+}
+
+#[test]
+fn unknown_variant_is_reported() {
+    let output = run_commands(&["setoption name UCI_Variant value atomic"]);
+
+    assert!(output.contains("info string Unknown variant: atomic"));
+}
+
+#[cfg(feature = "syzygy")]
+#[test]
+fn uci_handshake_lists_syzygy_path() {
+    let output = run_commands(&["uci"]);
+
+    assert!(output.contains("option name SyzygyPath type string default <empty>"));
+}
+
+#[cfg(feature = "syzygy")]
+#[test]
+fn unreadable_syzygy_path_is_reported() {
+    let output = run_commands(&["setoption name SyzygyPath value does/not/exist"]);
+
+    assert!(output.contains("info string Cannot read tablebase directory does/not/exist"));
+}
@@ -0,0 +1,2 @@
+mod engine_tests;
+mod uci_utils_tests;
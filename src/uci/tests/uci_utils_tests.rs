@@ -0,0 +1,36 @@
+#[cfg(test)]
+use crate::model::game_state::{Piece, Position};
+#[cfg(test)]
+use crate::uci::uci_utils::{parse_move, UciMove};
+
+#[test]
+fn parse_and_format_moves() {
+    for uci_move in ["e2e4", "a7a8q", "h2h1n"].iter() {
+        assert_eq!(*uci_move, parse_move(uci_move).unwrap().to_string());
+    }
+}
+
+#[test]
+fn invalid_moves_are_rejected() {
+    for invalid in [
+        "",
+        "e2",
+        "e2e9",
+        "e0e4",
+        "i2i4",
+        "e7e8k",
+        "e2e4qq",
+        "e2\u{e9}4",
+    ]
+    .iter()
+    {
+        assert!(invalid.parse::<UciMove>().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn format_invalid_promotion() {
+    let uci_move = UciMove(Position::new(5, 7), Position::new(5, 8), Some(Piece::KING));
+
+    assert_eq!("e7e8k", uci_move.to_string());
+}